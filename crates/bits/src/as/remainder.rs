@@ -25,7 +25,7 @@ impl BitUnpackAs<Vec<u8>> for Remainder {
         R: BitReader,
     {
         let bits: BitVec<u8, Msb0> = reader.unpack_as::<_, Self>()?;
-        if bits.len() % 8 != 0 {
+        if !bits.len().is_multiple_of(8) {
             return Err(Error::custom("EOF"));
         }
         Ok(bits.into_vec())
@@ -8,7 +8,7 @@ use either::Either;
 
 use crate::{
     r#as::{FromInto, Same},
-    ResultExt,
+    Error, ResultExt,
 };
 
 use super::{BitReader, BitReaderExt};
@@ -195,7 +195,9 @@ impl BitUnpackWithArgs for BitVec<u8, Msb0> {
     {
         let mut dst = BitVec::with_capacity(len);
         dst.resize(len, false);
-        reader.read_bits_into(&mut dst)?;
+        if reader.read_bits_into(&mut dst)? != len {
+            return Err(Error::custom("EOF"));
+        }
         Ok(dst)
     }
 }
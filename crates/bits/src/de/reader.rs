@@ -70,7 +70,9 @@ pub trait BitReaderExt: BitReader {
     #[inline]
     fn read_bytes_array<const N: usize>(&mut self) -> Result<[u8; N], Self::Error> {
         let mut arr = [0; N];
-        self.read_bits_into(arr.as_mut_bits())?;
+        if self.read_bits_into(arr.as_mut_bits())? != N * 8 {
+            return Err(Error::custom("EOF"));
+        }
         Ok(arr)
     }
 
@@ -325,7 +327,7 @@ impl BitReader for &BitSlice<u8, Msb0> {
     fn read_bits_into(&mut self, dst: &mut BitSlice<u8, Msb0>) -> Result<usize, Self::Error> {
         let n = dst.len().min(self.bits_left());
         let (v, rest) = self.split_at(n);
-        dst.copy_from_bitslice(v);
+        dst[..n].copy_from_bitslice(v);
         *self = rest;
         Ok(n)
     }
@@ -142,7 +142,9 @@ macro_rules! impl_bit_serde_for_integers {
                 const BITS_SIZE: usize = bits_of::<$t>();
                 assert!(BITS <= BITS_SIZE, "excessive bits for type");
                 let mut arr = [0u8; mem::size_of::<$t>()];
-                reader.read_bits_into(&mut arr.as_mut_bits()[BITS_SIZE - BITS..])?;
+                if reader.read_bits_into(&mut arr.as_mut_bits()[BITS_SIZE - BITS..])? != BITS {
+                    return Err(Error::custom("EOF"));
+                }
                 Ok($t::from_be_bytes(arr))
             }
         }
@@ -280,6 +282,21 @@ mod tests {
         assert_pack_unpack_eq(12345_u32);
     }
 
+    #[test]
+    fn unpack_uint_eof() {
+        let mut bits = bits![u8, Msb0; 1, 0, 1];
+        assert!(bits.unpack::<u32>().is_err());
+    }
+
+    #[test]
+    fn unpack_nbits_uint_eof() {
+        let mut bits = bits![u8, Msb0; 1, 0, 1];
+        assert!(bits.unpack_as::<u8, NBits<4>>().is_err());
+
+        let mut bits = bits![u8, Msb0; 1, 0, 1];
+        assert!(bits.unpack_as::<BigUint, NBits<4>>().is_err());
+    }
+
     #[test]
     fn store_nbits_uint() {
         assert_eq!(
@@ -6,3 +6,7 @@ pub mod wallet;
 #[cfg(feature = "jetton")]
 #[cfg_attr(docsrs, doc(cfg(feature = "jetton")))]
 pub mod jetton;
+
+#[cfg(feature = "multisig")]
+#[cfg_attr(docsrs, doc(cfg(feature = "multisig")))]
+pub mod multisig;
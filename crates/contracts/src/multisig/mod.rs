@@ -0,0 +1,537 @@
+//! [Multisig v2](https://github.com/ton-blockchain/multisig-contract-v2)
+mod order;
+
+pub use self::order::*;
+
+use bitvec::{field::BitField, order::Msb0, vec::BitVec, view::AsBits};
+use chrono::{DateTime, Utc};
+use num_bigint::BigUint;
+use tlb::{
+    bits::{
+        de::{r#as::BitUnpackAs, BitReader, BitReaderExt},
+        integer::ConstU32,
+        r#as::NBits,
+        ser::{r#as::BitPackAs, BitWriter, BitWriterExt},
+    },
+    de::{r#as::CellDeserializeAs, CellDeserialize, CellParser, CellParserError},
+    r#as::{Data, NoArgs, Ref},
+    ser::{r#as::CellSerializeAs, CellBuilder, CellBuilderError, CellSerialize},
+    Error, ResultExt, StringError,
+};
+use tlb_ton::{
+    action::{OutAction, SendMsgAction},
+    hashmap::{Hashmap, HashmapE},
+    MsgAddress,
+};
+
+/// Data of multisig wallet
+/// ```tlb
+/// storage#_ next_order_seqno:uint256 threshold:uint8
+///   signers:^(Hashmap 8 AddressInt) signers_num:uint8
+///   proposers:(HashmapE 8 AddressInt)
+///   allow_arbitrary_order_seqno:Bool = MultisigStorage;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultisigData {
+    pub next_order_seqno: BigUint,
+    /// Number of approvals required to execute an order
+    pub threshold: u8,
+    /// Addresses allowed to create and approve orders, by their index
+    pub signers: Vec<MsgAddress>,
+    /// Addresses allowed to only create orders, by their index
+    pub proposers: Vec<MsgAddress>,
+    pub allow_arbitrary_order_seqno: bool,
+}
+
+impl MultisigData {
+    /// Index of given address in [`signers`](Self::signers)
+    #[inline]
+    pub fn signer_index(&self, address: MsgAddress) -> Option<u8> {
+        index_of(&self.signers, address)
+    }
+
+    /// Index of given address in [`proposers`](Self::proposers)
+    #[inline]
+    pub fn proposer_index(&self, address: MsgAddress) -> Option<u8> {
+        index_of(&self.proposers, address)
+    }
+}
+
+impl CellSerialize for MultisigData {
+    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        builder
+            .pack_as::<_, &NBits<256>>(&self.next_order_seqno)?
+            .pack(self.threshold)?
+            .store_as::<_, Ref<&Signers>>(&self.signers)
+            .context("signers")?
+            .pack(self.signers.len() as u8)?
+            .store_as::<_, &Proposers>(&self.proposers)
+            .context("proposers")?
+            .pack(self.allow_arbitrary_order_seqno)?;
+        Ok(())
+    }
+}
+
+impl<'de> CellDeserialize<'de> for MultisigData {
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        let next_order_seqno = parser.unpack_as::<_, NBits<256>>()?;
+        let threshold = parser.unpack()?;
+        let signers: Vec<_> = parser.parse_as::<_, Ref<Signers>>().context("signers")?;
+        let signers_num: u8 = parser.unpack()?;
+        if signers_num as usize != signers.len() {
+            return Err(Error::custom(format!(
+                "signers_num mismatch: {signers_num} != {}",
+                signers.len()
+            )));
+        }
+        Ok(Self {
+            next_order_seqno,
+            threshold,
+            signers,
+            proposers: parser.parse_as::<_, Proposers>().context("proposers")?,
+            allow_arbitrary_order_seqno: parser.unpack()?,
+        })
+    }
+}
+
+/// Action to be executed by multisig once the order is approved
+/// ```tlb
+/// send_message#f1381e5b mode:uint8 message:^Cell = Action;
+/// update_multisig_params#1d0cfbd3 threshold:uint8
+///   signers:^(Hashmap 8 AddressInt)
+///   proposers:(HashmapE 8 AddressInt) = Action;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[allow(clippy::large_enum_variant)]
+pub enum MultisigAction {
+    /// ```tlb
+    /// send_message#f1381e5b mode:uint8 message:^Cell = Action;
+    /// ```
+    SendMessage(SendMsgAction),
+
+    /// ```tlb
+    /// update_multisig_params#1d0cfbd3 threshold:uint8
+    ///   signers:^(Hashmap 8 AddressInt)
+    ///   proposers:(HashmapE 8 AddressInt) = Action;
+    /// ```
+    UpdateParams {
+        threshold: u8,
+        signers: Vec<MsgAddress>,
+        proposers: Vec<MsgAddress>,
+    },
+}
+
+impl MultisigAction {
+    const SEND_MESSAGE_PREFIX: u32 = 0xf1381e5b;
+    const UPDATE_PARAMS_PREFIX: u32 = 0x1d0cfbd3;
+}
+
+impl From<SendMsgAction> for MultisigAction {
+    #[inline]
+    fn from(action: SendMsgAction) -> Self {
+        Self::SendMessage(action)
+    }
+}
+
+impl TryFrom<OutAction> for MultisigAction {
+    type Error = StringError;
+
+    /// Only [`OutAction::SendMsg`] can be executed by multisig
+    #[inline]
+    fn try_from(action: OutAction) -> Result<Self, Self::Error> {
+        match action {
+            OutAction::SendMsg(action) => Ok(Self::SendMessage(action)),
+            _ => Err(Error::custom("only send_msg actions are supported")),
+        }
+    }
+}
+
+impl CellSerialize for MultisigAction {
+    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        match self {
+            Self::SendMessage(action) => builder.pack(Self::SEND_MESSAGE_PREFIX)?.store(action)?,
+            Self::UpdateParams {
+                threshold,
+                signers,
+                proposers,
+            } => builder
+                .pack(Self::UPDATE_PARAMS_PREFIX)?
+                .pack(threshold)?
+                .store_as::<_, Ref<&Signers>>(signers)
+                .context("signers")?
+                .store_as::<_, &Proposers>(proposers)
+                .context("proposers")?,
+        };
+        Ok(())
+    }
+}
+
+impl<'de> CellDeserialize<'de> for MultisigAction {
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        Ok(match parser.unpack()? {
+            Self::SEND_MESSAGE_PREFIX => Self::SendMessage(parser.parse().context("send_message")?),
+            Self::UPDATE_PARAMS_PREFIX => Self::UpdateParams {
+                threshold: parser.unpack()?,
+                signers: parser.parse_as::<_, Ref<Signers>>().context("signers")?,
+                proposers: parser.parse_as::<_, Proposers>().context("proposers")?,
+            },
+            prefix => return Err(Error::custom(format!("unknown prefix: {prefix:#0x}"))),
+        })
+    }
+}
+
+/// Create new order, sent by signer or proposer to multisig. Sender
+/// approves the order on creation if it is a signer.
+/// ```tlb
+/// new_order#f718510f query_id:uint64 order_seqno:uint256 signer:(## 1)
+///   index:uint8 expiration_date:uint48 order:^Order = InternalMsgBody;
+///
+/// _ _:(Hashmap 8 ^Action) = Order;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultisigNewOrder {
+    pub query_id: u64,
+    /// Must be equal to [`next_order_seqno`](MultisigData::next_order_seqno)
+    /// unless [`allow_arbitrary_order_seqno`](MultisigData::allow_arbitrary_order_seqno)
+    /// is set
+    pub order_seqno: BigUint,
+    /// Whether [`index`](Self::index) refers to
+    /// [`signers`](MultisigData::signers) or
+    /// [`proposers`](MultisigData::proposers)
+    pub signer: bool,
+    pub index: u8,
+    pub expiration_date: DateTime<Utc>,
+    /// Actions executed in order
+    pub order: Vec<MultisigAction>,
+}
+
+impl MultisigNewOrder {
+    const PREFIX: u32 = 0xf718510f;
+}
+
+impl CellSerialize for MultisigNewOrder {
+    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        builder
+            .pack(Self::PREFIX)?
+            .pack(self.query_id)?
+            .pack_as::<_, &NBits<256>>(&self.order_seqno)?
+            .pack(self.signer)?
+            .pack(self.index)?
+            .pack_as::<_, UnixTimestamp48>(self.expiration_date)?
+            .store_as::<_, Ref<&Order>>(&self.order)
+            .context("order")?;
+        Ok(())
+    }
+}
+
+impl<'de> CellDeserialize<'de> for MultisigNewOrder {
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        parser.unpack::<ConstU32<{ Self::PREFIX }>>()?;
+        Ok(Self {
+            query_id: parser.unpack()?,
+            order_seqno: parser.unpack_as::<_, NBits<256>>()?,
+            signer: parser.unpack()?,
+            index: parser.unpack()?,
+            expiration_date: parser.unpack_as::<_, UnixTimestamp48>()?,
+            order: parser.parse_as::<_, Ref<Order>>().context("order")?,
+        })
+    }
+}
+
+/// Approve the order, sent by signer to the order contract
+/// ```tlb
+/// approve#a762230f query_id:uint64 signer_index:uint8 = InternalMsgBody;
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MultisigApprove {
+    pub query_id: u64,
+    pub signer_index: u8,
+}
+
+impl MultisigApprove {
+    const PREFIX: u32 = 0xa762230f;
+}
+
+impl CellSerialize for MultisigApprove {
+    #[inline]
+    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        builder
+            .pack(Self::PREFIX)?
+            .pack(self.query_id)?
+            .pack(self.signer_index)?;
+        Ok(())
+    }
+}
+
+impl<'de> CellDeserialize<'de> for MultisigApprove {
+    #[inline]
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        parser.unpack::<ConstU32<{ Self::PREFIX }>>()?;
+        Ok(Self {
+            query_id: parser.unpack()?,
+            signer_index: parser.unpack()?,
+        })
+    }
+}
+
+#[inline]
+fn index_of(addresses: &[MsgAddress], address: MsgAddress) -> Option<u8> {
+    addresses
+        .iter()
+        .position(|a| *a == address)
+        .map(|i| i as u8)
+}
+
+/// Builds dictionary keyed by 8-bit index of each value
+fn index_dict<T: Clone>(values: &[T]) -> Result<HashmapE<T>, CellBuilderError> {
+    if values.len() > u8::MAX as usize {
+        return Err(Error::custom(format!(
+            "too many entries: {} > {}",
+            values.len(),
+            u8::MAX
+        )));
+    }
+    let mut dict = HashmapE::new();
+    for (i, v) in values.iter().enumerate() {
+        dict.insert([i as u8].as_bits::<Msb0>(), v.clone())
+            .map_err(Error::custom)?;
+    }
+    Ok(dict)
+}
+
+/// Values sorted by their 8-bit keys, which must be sequential from 0 if
+/// `sequential` is set
+fn from_index_dict<'a, T, E>(
+    dict: impl IntoIterator<Item = (BitVec<u8, Msb0>, &'a T)>,
+    sequential: bool,
+) -> Result<Vec<T>, E>
+where
+    T: Clone + 'a,
+    E: Error,
+{
+    let mut values: Vec<_> = dict
+        .into_iter()
+        .map(|(key, v)| (key.load_be::<u8>(), v.clone()))
+        .collect();
+    values.sort_by_key(|(i, _)| *i);
+    if sequential {
+        if let Some((i, (key, _))) = values
+            .iter()
+            .enumerate()
+            .find(|(i, (key, _))| *i != *key as usize)
+        {
+            return Err(Error::custom(format!("expected key {i}, got {key}")));
+        }
+    }
+    Ok(values.into_iter().map(|(_, v)| v).collect())
+}
+
+/// Adapter for non-empty `Hashmap 8 AddressInt` with sequential keys
+struct Signers;
+
+impl CellSerializeAs<Vec<MsgAddress>> for Signers {
+    fn store_as(
+        source: &Vec<MsgAddress>,
+        builder: &mut CellBuilder,
+    ) -> Result<(), CellBuilderError> {
+        let HashmapE::Root(signers) = index_dict(source)? else {
+            return Err(Error::custom("signers must not be empty"));
+        };
+        builder.store_as_with::<_, &Hashmap<Data<NoArgs<_>>, NoArgs<_>>>(&signers, (8, (), ()))?;
+        Ok(())
+    }
+}
+
+impl<'de> CellDeserializeAs<'de, Vec<MsgAddress>> for Signers {
+    fn parse_as(parser: &mut CellParser<'de>) -> Result<Vec<MsgAddress>, CellParserError<'de>> {
+        let signers: Hashmap<MsgAddress> =
+            parser.parse_as_with::<_, Hashmap<Data<NoArgs<_>>, NoArgs<_>>>((8, (), ()))?;
+        from_index_dict(signers.iter(), true)
+    }
+}
+
+/// Adapter for `HashmapE 8 AddressInt` with sequential keys
+struct Proposers;
+
+impl CellSerializeAs<Vec<MsgAddress>> for Proposers {
+    fn store_as(
+        source: &Vec<MsgAddress>,
+        builder: &mut CellBuilder,
+    ) -> Result<(), CellBuilderError> {
+        builder.store_as_with::<_, &HashmapE<Data<NoArgs<_>>, NoArgs<_>>>(
+            &index_dict(source)?,
+            (8, (), ()),
+        )?;
+        Ok(())
+    }
+}
+
+impl<'de> CellDeserializeAs<'de, Vec<MsgAddress>> for Proposers {
+    fn parse_as(parser: &mut CellParser<'de>) -> Result<Vec<MsgAddress>, CellParserError<'de>> {
+        let proposers: HashmapE<MsgAddress> =
+            parser.parse_as_with::<_, HashmapE<Data<NoArgs<_>>, NoArgs<_>>>((8, (), ()))?;
+        from_index_dict(proposers.iter(), true)
+    }
+}
+
+/// Adapter for non-empty `Hashmap 8 ^Action`
+struct Order;
+
+impl CellSerializeAs<Vec<MultisigAction>> for Order {
+    fn store_as(
+        source: &Vec<MultisigAction>,
+        builder: &mut CellBuilder,
+    ) -> Result<(), CellBuilderError> {
+        let HashmapE::Root(order) = index_dict(source)? else {
+            return Err(Error::custom("order must not be empty"));
+        };
+        builder.store_as_with::<_, &Hashmap<Ref<NoArgs<_>>, NoArgs<_>>>(&order, (8, (), ()))?;
+        Ok(())
+    }
+}
+
+impl<'de> CellDeserializeAs<'de, Vec<MultisigAction>> for Order {
+    fn parse_as(parser: &mut CellParser<'de>) -> Result<Vec<MultisigAction>, CellParserError<'de>> {
+        let order: Hashmap<MultisigAction> =
+            parser.parse_as_with::<_, Hashmap<Ref<NoArgs<_>>, NoArgs<_>>>((8, (), ()))?;
+        // actions are executed in order of their keys
+        from_index_dict(order.iter(), false)
+    }
+}
+
+/// Adapter to **de**/**ser**ialize UNIX timestamp as `uint48` from
+/// [`DateTime`]
+struct UnixTimestamp48;
+
+impl BitPackAs<DateTime<Utc>> for UnixTimestamp48 {
+    #[inline]
+    fn pack_as<W>(source: &DateTime<Utc>, mut writer: W) -> Result<(), W::Error>
+    where
+        W: BitWriter,
+    {
+        let timestamp: u64 = source.timestamp().try_into().map_err(Error::custom)?;
+        writer.pack_as::<_, NBits<48>>(timestamp)?;
+        Ok(())
+    }
+}
+
+impl BitUnpackAs<DateTime<Utc>> for UnixTimestamp48 {
+    #[inline]
+    fn unpack_as<R>(mut reader: R) -> Result<DateTime<Utc>, R::Error>
+    where
+        R: BitReader,
+    {
+        let timestamp: u64 = reader.unpack_as::<_, NBits<48>>()?;
+        DateTime::from_timestamp(timestamp as i64, 0)
+            .ok_or_else(|| Error::custom(format!("invalid timestamp: {timestamp}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tlb::{ser::CellSerializeExt, Cell};
+    use tlb_ton::{action::SendMode, currency::ONE_TON, message::Message};
+
+    use super::*;
+
+    pub(super) fn address(i: u8) -> MsgAddress {
+        MsgAddress {
+            workchain_id: 0,
+            address: [i; 32],
+        }
+    }
+
+    pub(super) fn order() -> Vec<MultisigAction> {
+        vec![
+            SendMsgAction {
+                mode: SendMode::PAY_FEES_SEPARATELY,
+                message: Message::<()>::transfer(address(9), ONE_TON.clone(), false)
+                    .normalize()
+                    .unwrap(),
+            }
+            .into(),
+            MultisigAction::UpdateParams {
+                threshold: 1,
+                signers: vec![address(1)],
+                proposers: vec![],
+            },
+        ]
+    }
+
+    #[test]
+    fn data_serde() {
+        let data = MultisigData {
+            next_order_seqno: 5u32.into(),
+            threshold: 2,
+            signers: (0..3).map(address).collect(),
+            proposers: vec![address(10)],
+            allow_arbitrary_order_seqno: false,
+        };
+        let cell = data.to_cell().unwrap();
+        let got: MultisigData = cell.parse_fully().unwrap();
+        assert_eq!(got, data);
+        assert_eq!(got.signer_index(address(2)), Some(2));
+        assert_eq!(got.proposer_index(address(10)), Some(0));
+        assert_eq!(got.signer_index(address(10)), None);
+
+        // signers:^(Hashmap 8 AddressInt)
+        let signers: Hashmap<MsgAddress> = cell.references[0]
+            .parse_fully_as_with::<_, Hashmap<Data<NoArgs<_>>, NoArgs<_>>>((8, (), ()))
+            .unwrap();
+        assert_eq!(signers.get([1u8].as_bits::<Msb0>()), Some(&address(1)));
+
+        assert!(MultisigData {
+            signers: vec![],
+            ..data
+        }
+        .to_cell()
+        .is_err());
+    }
+
+    #[test]
+    fn sequential_signers() {
+        let mut signers = HashmapE::new();
+        for i in [0u8, 2] {
+            signers.insert([i].as_bits::<Msb0>(), address(i)).unwrap();
+        }
+        let HashmapE::Root(signers) = signers else {
+            unreachable!();
+        };
+        let mut builder = Cell::builder();
+        builder
+            .store_as_with::<_, &Hashmap<Data<NoArgs<_>>, NoArgs<_>>>(&signers, (8, (), ()))
+            .unwrap();
+        assert!(builder
+            .into_cell()
+            .parse_fully_as::<Vec<MsgAddress>, Signers>()
+            .is_err());
+    }
+
+    #[test]
+    fn new_order_serde() {
+        let msg = MultisigNewOrder {
+            query_id: 1,
+            order_seqno: 5u32.into(),
+            signer: true,
+            index: 1,
+            expiration_date: DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+            order: order(),
+        };
+        let cell = msg.to_cell().unwrap();
+        assert_eq!(cell.parser().unpack::<u32>().unwrap(), 0xf718510f);
+        let got: MultisigNewOrder = cell.parse_fully().unwrap();
+        assert_eq!(got, msg);
+    }
+
+    #[test]
+    fn approve_serde() {
+        let msg = MultisigApprove {
+            query_id: 1,
+            signer_index: 2,
+        };
+        let cell = msg.to_cell().unwrap();
+        assert_eq!(cell.parser().unpack::<u32>().unwrap(), 0xa762230f);
+        let got: MultisigApprove = cell.parse_fully().unwrap();
+        assert_eq!(got, msg);
+    }
+}
@@ -0,0 +1,224 @@
+use chrono::{DateTime, Utc};
+use num_bigint::BigUint;
+use tlb::{
+    bits::{de::BitReaderExt, r#as::NBits, ser::BitWriterExt},
+    de::{CellDeserialize, CellParser, CellParserError},
+    r#as::Ref,
+    ser::{CellBuilder, CellBuilderError, CellSerialize},
+    ResultExt,
+};
+use tlb_ton::{state_init::StateInit, MsgAddress};
+
+use super::{MultisigAction, Order, Signers, UnixTimestamp48};
+
+/// Data of order contract deployed by multisig for each
+/// [new order](super::MultisigNewOrder)
+/// ```tlb
+/// storage#_ multisig_address:MsgAddressInt order_seqno:uint256
+///   threshold:uint8 sent_for_execution:Bool
+///   signers:^(Hashmap 8 AddressInt) approvals_mask:uint256
+///   approvals_num:uint8 expiration_date:uint48
+///   order:^Order = OrderStorage;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultisigOrderData {
+    pub multisig_address: MsgAddress,
+    pub order_seqno: BigUint,
+    /// Set by multisig when the order is initialized, `None` in
+    /// [init data](MultisigOrderData::new)
+    pub state: Option<MultisigOrderState>,
+}
+
+impl MultisigOrderData {
+    /// Orders are always deployed to basechain
+    pub const WORKCHAIN_ID: i32 = 0;
+
+    /// Init data of the order
+    #[inline]
+    pub const fn new(multisig_address: MsgAddress, order_seqno: BigUint) -> Self {
+        Self {
+            multisig_address,
+            order_seqno,
+            state: None,
+        }
+    }
+
+    /// [`StateInit`] of order contract with given code, which is not stored
+    /// in [`MultisigData`](super::MultisigData), so it has to be obtained
+    /// from the multisig code or any of its deployed orders
+    #[inline]
+    pub fn state_init<C>(
+        order_code: C,
+        multisig_address: MsgAddress,
+        order_seqno: BigUint,
+    ) -> StateInit<C, Self> {
+        StateInit {
+            code: Some(order_code),
+            data: Some(Self::new(multisig_address, order_seqno)),
+            ..Default::default()
+        }
+    }
+
+    /// Address of order contract with given seqno, see
+    /// [`.state_init()`](MultisigOrderData::state_init)
+    #[inline]
+    pub fn derive_address<C>(
+        order_code: C,
+        multisig_address: MsgAddress,
+        order_seqno: BigUint,
+    ) -> Result<MsgAddress, CellBuilderError>
+    where
+        C: CellSerialize,
+    {
+        MsgAddress::derive(
+            Self::WORKCHAIN_ID,
+            Self::state_init(order_code, multisig_address, order_seqno),
+        )
+    }
+}
+
+impl CellSerialize for MultisigOrderData {
+    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        builder
+            .pack(self.multisig_address)?
+            .pack_as::<_, &NBits<256>>(&self.order_seqno)?;
+        if let Some(state) = &self.state {
+            builder.store(state)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'de> CellDeserialize<'de> for MultisigOrderData {
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        Ok(Self {
+            multisig_address: parser.unpack()?,
+            order_seqno: parser.unpack_as::<_, NBits<256>>()?,
+            state: if parser.bits_left() == 0 {
+                None
+            } else {
+                Some(parser.parse()?)
+            },
+        })
+    }
+}
+
+/// State of initialized order, see [`MultisigOrderData`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultisigOrderState {
+    /// Number of approvals required to execute the order
+    pub threshold: u8,
+    pub sent_for_execution: bool,
+    /// Signers of multisig at the moment of order creation
+    pub signers: Vec<MsgAddress>,
+    /// Bit `i` is set if signer with index `i` has approved the order
+    pub approvals_mask: BigUint,
+    pub approvals_num: u8,
+    pub expiration_date: DateTime<Utc>,
+    pub order: Vec<MultisigAction>,
+}
+
+impl MultisigOrderState {
+    /// Returns whether signer with given index has approved the order
+    #[inline]
+    pub fn is_approved_by(&self, signer_index: u8) -> bool {
+        self.approvals_mask.bit(signer_index.into())
+    }
+}
+
+impl CellSerialize for MultisigOrderState {
+    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        builder
+            .pack(self.threshold)?
+            .pack(self.sent_for_execution)?
+            .store_as::<_, Ref<&Signers>>(&self.signers)
+            .context("signers")?
+            .pack_as::<_, &NBits<256>>(&self.approvals_mask)?
+            .pack(self.approvals_num)?
+            .pack_as::<_, UnixTimestamp48>(self.expiration_date)?
+            .store_as::<_, Ref<&Order>>(&self.order)
+            .context("order")?;
+        Ok(())
+    }
+}
+
+impl<'de> CellDeserialize<'de> for MultisigOrderState {
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        Ok(Self {
+            threshold: parser.unpack()?,
+            sent_for_execution: parser.unpack()?,
+            signers: parser.parse_as::<_, Ref<Signers>>().context("signers")?,
+            approvals_mask: parser.unpack_as::<_, NBits<256>>()?,
+            approvals_num: parser.unpack()?,
+            expiration_date: parser.unpack_as::<_, UnixTimestamp48>()?,
+            order: parser.parse_as::<_, Ref<Order>>().context("order")?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tlb::{ser::CellSerializeExt, Cell};
+
+    use super::{
+        super::tests::{address, order},
+        *,
+    };
+
+    #[test]
+    fn data_serde() {
+        let mut data = MultisigOrderData::new(address(1), 5u32.into());
+        let got: MultisigOrderData = data.to_cell().unwrap().parse_fully().unwrap();
+        assert_eq!(got, data);
+
+        data.state = Some(MultisigOrderState {
+            threshold: 2,
+            sent_for_execution: false,
+            signers: (0..3).map(address).collect(),
+            approvals_mask: 0b101u32.into(),
+            approvals_num: 2,
+            expiration_date: DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+            order: order(),
+        });
+        let got: MultisigOrderData = data.to_cell().unwrap().parse_fully().unwrap();
+        assert_eq!(got, data);
+
+        let state = got.state.unwrap();
+        assert!(state.is_approved_by(0));
+        assert!(!state.is_approved_by(1));
+        assert!(state.is_approved_by(2));
+    }
+
+    #[test]
+    fn derive_address() {
+        let code = Cell::builder().into_cell();
+        let data = MultisigOrderData::new(address(1), 5u32.into());
+
+        // split_depth:(Maybe (## 5)) special:(Maybe TickTock)
+        // code:(Maybe ^Cell) data:(Maybe ^Cell) library:(HashmapE 256 SimpleLib)
+        let mut state_init = Cell::builder();
+        state_init
+            .pack(false)
+            .unwrap()
+            .pack(false)
+            .unwrap()
+            .pack(true)
+            .unwrap()
+            .store_as::<_, Ref>(&code)
+            .unwrap()
+            .pack(true)
+            .unwrap()
+            .store_as::<_, Ref>(&data)
+            .unwrap()
+            .pack(false)
+            .unwrap();
+
+        assert_eq!(
+            MultisigOrderData::derive_address(code, address(1), 5u32.into()).unwrap(),
+            MsgAddress {
+                workchain_id: 0,
+                address: state_init.into_cell().hash(),
+            },
+        );
+    }
+}
@@ -0,0 +1,483 @@
+use core::slice;
+
+use anyhow::{anyhow, bail, ensure};
+use chrono::{DateTime, Utc};
+use tlb::{de::CellParserError, Cell, Error};
+use tlb_ton::{
+    action::{OutAction, SendMsgAction},
+    message::{CommonMsgInfo, Message},
+    MsgAddress,
+};
+
+use super::{
+    highload_v2::HighloadV2ExternalBody,
+    highload_v3::{HighloadV3ExternalBody, HighloadV3InternalTransfer},
+    v3::WalletV3ExternalBody,
+    v4r2::{WalletV4R2ExternalBody, WalletV4R2Op},
+    v5r1::{WalletV5R1MsgBody, WalletV5R1SignedRequest},
+    verify, KnownWalletVersion, PUBLIC_KEY_LENGTH,
+};
+
+impl KnownWalletVersion {
+    /// Parse body of external message sent to the wallet of this version
+    pub fn parse_external_body(
+        self,
+        body: &Cell,
+    ) -> Result<KnownWalletExternalBody, CellParserError<'_>> {
+        Ok(match self {
+            Self::V3R1 | Self::V3R2 => KnownWalletExternalBody::V3(body.parse_fully()?),
+            Self::V4R1 | Self::V4R2 => KnownWalletExternalBody::V4(body.parse_fully()?),
+            Self::V5R1 => match body.parse_fully()? {
+                WalletV5R1MsgBody::ExternalSigned(request) => {
+                    KnownWalletExternalBody::V5R1(request)
+                }
+                _ => return Err(Error::custom("expected external_signed")),
+            },
+            Self::HighloadV2 => KnownWalletExternalBody::HighloadV2(body.parse_fully()?),
+            Self::HighloadV3 => KnownWalletExternalBody::HighloadV3(body.parse_fully()?),
+        })
+    }
+}
+
+/// Body of external message of [`KnownWalletVersion`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KnownWalletExternalBody {
+    /// [`V3R1`](super::v3::V3R1) and [`V3R2`](super::v3::V3R2)
+    V3(WalletV3ExternalBody),
+    /// [`V4R1`](super::v4r1::V4R1) and [`V4R2`](super::v4r2::V4R2)
+    V4(WalletV4R2ExternalBody),
+    /// [`WalletV5R1MsgBody::ExternalSigned`]
+    V5R1(WalletV5R1SignedRequest),
+    HighloadV2(HighloadV2ExternalBody),
+    HighloadV3(HighloadV3ExternalBody),
+}
+
+impl KnownWalletExternalBody {
+    /// Versions to try when decoding body of unknown wallet, one per
+    /// distinct body layout, starting with the most specific ones
+    const LAYOUTS: [KnownWalletVersion; 5] = [
+        KnownWalletVersion::V5R1,
+        KnownWalletVersion::HighloadV3,
+        KnownWalletVersion::V4R2,
+        KnownWalletVersion::V3R2,
+        KnownWalletVersion::HighloadV2,
+    ];
+
+    /// Versions sharing layout of this body, one of which is the version
+    /// of the wallet that has sent it
+    #[inline]
+    pub fn versions(&self) -> &'static [KnownWalletVersion] {
+        match self {
+            Self::V3(_) => &[KnownWalletVersion::V3R1, KnownWalletVersion::V3R2],
+            Self::V4(_) => &[KnownWalletVersion::V4R1, KnownWalletVersion::V4R2],
+            Self::V5R1(_) => &[KnownWalletVersion::V5R1],
+            Self::HighloadV2(_) => &[KnownWalletVersion::HighloadV2],
+            Self::HighloadV3(_) => &[KnownWalletVersion::HighloadV3],
+        }
+    }
+
+    /// Verify signature against given public key
+    pub fn verify(&self, public_key: &[u8; PUBLIC_KEY_LENGTH]) -> anyhow::Result<bool> {
+        match self {
+            Self::V3(body) => body.verify(public_key),
+            Self::V4(body) => body.verify(public_key),
+            Self::V5R1(request) => request.verify(public_key),
+            Self::HighloadV2(body) => body.verify(public_key),
+            Self::HighloadV3(body) => body.verify(public_key),
+        }
+    }
+
+    /// Same as [`.verify()`](KnownWalletExternalBody::verify), but hashes
+    /// signed part of the `body` cell this body was parsed from, so that it
+    /// does not need to be serializable back, e.g. with send modes rejected
+    /// by [`SendMode::is_valid()`](tlb_ton::action::SendMode::is_valid)
+    pub fn verify_cell(
+        &self,
+        body: &Cell,
+        public_key: &[u8; PUBLIC_KEY_LENGTH],
+    ) -> anyhow::Result<bool> {
+        let hash = match self {
+            // signature:bits512 body:SignBody
+            Self::V3(_) | Self::V4(_) | Self::HighloadV2(_) => Cell {
+                data: body.data.get(512..).ok_or_else(|| anyhow!("EOF"))?.into(),
+                ..body.clone()
+            }
+            .hash(),
+            // prefix:uint32 body:SignBody signature:bits512
+            Self::V5R1(_) => Cell {
+                data: body
+                    .data
+                    .get(..body.data.len().saturating_sub(512))
+                    .ok_or_else(|| anyhow!("EOF"))?
+                    .into(),
+                ..body.clone()
+            }
+            .hash(),
+            // message:^MsgInner signature:bits512
+            Self::HighloadV3(_) => Cell::hash(
+                body.references
+                    .first()
+                    .ok_or_else(|| anyhow!("no message reference"))?,
+            ),
+        };
+        verify(public_key, hash, self.signature())
+    }
+
+    #[inline]
+    pub fn signature(&self) -> &[u8; 64] {
+        match self {
+            Self::V3(body) => &body.signature,
+            Self::V4(body) => &body.signature,
+            Self::V5R1(request) => &request.signature,
+            Self::HighloadV2(body) => &body.signature,
+            Self::HighloadV3(body) => &body.signature,
+        }
+    }
+
+    #[inline]
+    pub fn wallet_id(&self) -> u32 {
+        match self {
+            Self::V3(body) => body.body.wallet_id,
+            Self::V4(body) => body.body.wallet_id,
+            Self::V5R1(request) => request.body.wallet_id,
+            Self::HighloadV2(body) => body.body.wallet_id,
+            Self::HighloadV3(body) => body.body.wallet_id,
+        }
+    }
+
+    /// Time after which the message can not be accepted by the wallet
+    #[inline]
+    pub fn expire_at(&self) -> DateTime<Utc> {
+        match self {
+            Self::V3(body) => body.body.expire_at,
+            Self::V4(body) => body.body.expire_at,
+            Self::V5R1(request) => request.body.valid_until,
+            Self::HighloadV2(body) => body
+                .body
+                .expire_at()
+                .expect("32-bit timestamp is always in range"),
+            Self::HighloadV3(body) => body.body.expire_at(),
+        }
+    }
+
+    /// Seqno, if the wallet uses one to protect against replays
+    #[inline]
+    pub fn seqno(&self) -> Option<u32> {
+        match self {
+            Self::V3(body) => Some(body.body.seqno),
+            Self::V4(body) => Some(body.body.seqno),
+            Self::V5R1(request) => Some(request.body.msg_seqno),
+            Self::HighloadV2(_) | Self::HighloadV3(_) => None,
+        }
+    }
+
+    /// Out actions of the wallet, i.e. sent messages for all versions
+    /// except v5, which can also set code, reserve currency or change
+    /// libraries. Requests which are not out actions, such as v4 plugin
+    /// management or v5 [extended actions](super::v5r1::ExtendedAction),
+    /// are only available in the body itself.
+    pub fn out_actions(&self) -> Vec<OutAction> {
+        match self {
+            Self::V3(body) => Self::send_msgs(&body.body.msgs),
+            Self::V4(body) => match &body.body.op {
+                WalletV4R2Op::Send(msgs) => Self::send_msgs(msgs),
+                _ => Vec::new(),
+            },
+            Self::V5R1(request) => request.body.inner.out_actions.clone(),
+            Self::HighloadV2(body) => Self::send_msgs(&body.body.msgs),
+            Self::HighloadV3(body) => [OutAction::SendMsg(body.body.action.clone())].into(),
+        }
+    }
+
+    fn send_msgs(msgs: &[SendMsgAction]) -> Vec<OutAction> {
+        msgs.iter().cloned().map(OutAction::SendMsg).collect()
+    }
+}
+
+/// External message to a [known](KnownWalletVersion) wallet, see
+/// [`decode_external_message()`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedExternalMessage {
+    /// Address of the wallet
+    pub address: MsgAddress,
+    /// Version detected by code of attached [`StateInit`](tlb_ton::state_init::StateInit),
+    /// if any. Otherwise, only the layout of the body is known, see
+    /// [`.versions()`](DecodedExternalMessage::versions).
+    pub version: Option<KnownWalletVersion>,
+    pub body: KnownWalletExternalBody,
+}
+
+impl DecodedExternalMessage {
+    /// Versions the wallet can be of: the detected [`version`](Self::version)
+    /// or, without it, all [versions](KnownWalletExternalBody::versions)
+    /// sharing layout of the body
+    #[inline]
+    pub fn versions(&self) -> &[KnownWalletVersion] {
+        match &self.version {
+            Some(version) => slice::from_ref(version),
+            None => self.body.versions(),
+        }
+    }
+
+    /// Out actions of the wallet, see
+    /// [`KnownWalletExternalBody::out_actions()`].
+    ///
+    /// Highload v3 `internal_transfer`s sent by the wallet to itself are
+    /// expanded into the actions they carry.
+    pub fn out_actions(&self) -> Vec<OutAction> {
+        let actions = self.body.out_actions();
+        if !matches!(self.body, KnownWalletExternalBody::HighloadV3(_)) {
+            return actions;
+        }
+        let mut expanded = Vec::with_capacity(actions.len());
+        self.expand_internal_transfers(actions, &mut expanded);
+        expanded
+    }
+
+    fn expand_internal_transfers(&self, actions: Vec<OutAction>, expanded: &mut Vec<OutAction>) {
+        for action in actions {
+            let transfer = match &action {
+                OutAction::SendMsg(msg) => self.as_internal_transfer(msg),
+                _ => None,
+            };
+            match transfer {
+                Some(transfer) => self.expand_internal_transfers(transfer.actions.0, expanded),
+                None => expanded.push(action),
+            }
+        }
+    }
+
+    fn as_internal_transfer(&self, msg: &SendMsgAction) -> Option<HighloadV3InternalTransfer> {
+        match &msg.message.info {
+            CommonMsgInfo::Internal(info) if info.dst == self.address => {
+                msg.message.body.parse_fully().ok()
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Inverse of
+/// [`Wallet::create_external_message()`](super::Wallet::create_external_message)
+/// for [known](KnownWalletVersion) wallets.
+///
+/// Version is detected by code of attached state init, otherwise the body
+/// is tried against every known layout. Only the body signed by given
+/// public key is accepted, which is checked against the original body cell.
+pub fn decode_external_message(
+    msg: &Cell,
+    public_key: &[u8; PUBLIC_KEY_LENGTH],
+) -> anyhow::Result<DecodedExternalMessage> {
+    let msg: Message = msg.parse_fully()?;
+    let CommonMsgInfo::ExternalIn(info) = &msg.info else {
+        bail!("not an inbound external message");
+    };
+
+    let version = msg
+        .init
+        .as_ref()
+        .and_then(|init| init.code.as_ref())
+        .map(|code| {
+            KnownWalletVersion::from_code(code).ok_or_else(|| anyhow!("unknown wallet code"))
+        })
+        .transpose()?;
+
+    let body = match version {
+        Some(version) => {
+            let body = version.parse_external_body(&msg.body)?;
+            ensure!(
+                body.verify_cell(&msg.body, public_key)?,
+                "invalid signature"
+            );
+            body
+        }
+        None => {
+            let mut parsed = false;
+            let mut verified = None;
+            for version in KnownWalletExternalBody::LAYOUTS {
+                let Ok(body) = version.parse_external_body(&msg.body) else {
+                    continue;
+                };
+                parsed = true;
+                if body.verify_cell(&msg.body, public_key)? {
+                    verified = Some(body);
+                    break;
+                }
+            }
+            ensure!(parsed, "unknown wallet message body");
+            verified.ok_or_else(|| anyhow!("invalid signature"))?
+        }
+    };
+
+    Ok(DecodedExternalMessage {
+        address: info.dst,
+        version,
+        body,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use bitvec::{order::Msb0, view::AsBits};
+    use tlb::ser::CellSerializeExt;
+    use tlb_ton::{
+        action::{ReserveCurrencyAction, ReserveMode},
+        currency::{CurrencyCollection, ONE_TON},
+    };
+
+    use super::{
+        super::{
+            highload_v2::HighloadV2,
+            highload_v3::HighloadV3,
+            tests::msgs,
+            v3::{V3R1, V3R2},
+            v4r1::V4R1,
+            v4r2::V4R2,
+            v5r1::V5R1,
+            KeyPair, Signer, Wallet, WalletVersion,
+        },
+        *,
+    };
+
+    fn out_actions(n: u8) -> Vec<OutAction> {
+        msgs(n).into_iter().map(OutAction::SendMsg).collect()
+    }
+
+    fn roundtrip<V: WalletVersion>(version: KnownWalletVersion, n: u8) {
+        let wallet = Wallet::<V>::derive_default(KeyPair::from_seed([7; 32])).unwrap();
+        let expire_at = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+
+        for state_init in [false, true] {
+            let msg = wallet
+                .create_external_message(expire_at, 3, msgs(n), state_init)
+                .unwrap()
+                .to_cell()
+                .unwrap();
+
+            let decoded = decode_external_message(&msg, &wallet.public_key()).unwrap();
+            assert_eq!(decoded.address, wallet.address());
+            assert_eq!(decoded.version, state_init.then_some(version));
+            assert!(decoded.versions().contains(&version));
+            assert_eq!(
+                decoded.body,
+                version
+                    .parse_external_body(&msg.parse_fully::<Message>().unwrap().body)
+                    .unwrap(),
+            );
+            assert_eq!(decoded.body.wallet_id(), wallet.wallet_id());
+            assert_eq!(decoded.body.expire_at(), expire_at);
+            assert_eq!(
+                decoded.body.seqno(),
+                (!matches!(
+                    version,
+                    KnownWalletVersion::HighloadV2 | KnownWalletVersion::HighloadV3
+                ))
+                .then_some(3),
+            );
+            assert_eq!(decoded.out_actions(), out_actions(n));
+
+            assert!(
+                decode_external_message(&msg, &KeyPair::from_seed([8; 32]).public_key).is_err()
+            );
+        }
+    }
+
+    #[test]
+    fn decode() {
+        roundtrip::<V3R1>(KnownWalletVersion::V3R1, 4);
+        roundtrip::<V4R1>(KnownWalletVersion::V4R1, 4);
+        roundtrip::<V4R2>(KnownWalletVersion::V4R2, 4);
+        roundtrip::<V5R1>(KnownWalletVersion::V5R1, 4);
+        roundtrip::<HighloadV2>(KnownWalletVersion::HighloadV2, 4);
+        roundtrip::<HighloadV3>(KnownWalletVersion::HighloadV3, 1);
+    }
+
+    #[test]
+    fn decode_v5r1_out_actions() {
+        let wallet = Wallet::<V5R1>::derive_default(KeyPair::from_seed([7; 32])).unwrap();
+        let mut actions = out_actions(2);
+        actions.push(OutAction::ReserveCurrency(ReserveCurrencyAction {
+            mode: ReserveMode::AT_MOST,
+            currency: CurrencyCollection {
+                grams: ONE_TON.clone(),
+                ..Default::default()
+            },
+        }));
+        let body = wallet.create_extended_sign_body(
+            DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+            3,
+            actions.clone(),
+            [],
+        );
+        let signature = wallet.sign_body(&body).unwrap();
+        let msg = wallet
+            .wrap_signed(body, signature, false)
+            .unwrap()
+            .to_cell()
+            .unwrap();
+
+        let decoded = decode_external_message(&msg, &wallet.public_key()).unwrap();
+        assert_eq!(decoded.version, None);
+        assert_eq!(decoded.versions(), [KnownWalletVersion::V5R1]);
+        assert_eq!(decoded.out_actions(), actions);
+    }
+
+    #[test]
+    fn decode_invalid_send_mode() {
+        let keypair = KeyPair::from_seed([7; 32]);
+        let wallet = Wallet::<V3R2>::derive_default(keypair.clone()).unwrap();
+        let expire_at = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let mut msg: Message = wallet
+            .create_external_message(expire_at, 3, msgs(1), false)
+            .unwrap()
+            .to_cell()
+            .unwrap()
+            .parse_fully()
+            .unwrap();
+
+        // signature:bits512 wallet_id:uint32 valid_until:uint32 seqno:uint32
+        // mode:uint8, set both CARRY_ALL_BALANCE and CARRY_ALL_REMAINING_VALUE
+        let mut body = msg.body.clone();
+        body.data.set(512 + 96, true);
+        body.data.set(512 + 97, true);
+        let signed = Cell {
+            data: body.data[512..].into(),
+            ..body.clone()
+        };
+        let signature = keypair.sign_hash(&signed.hash()).unwrap();
+        body.data[..512].copy_from_bitslice(signature.as_bits::<Msb0>());
+        msg.body = body;
+
+        let decoded =
+            decode_external_message(&msg.to_cell().unwrap(), &keypair.public_key).unwrap();
+        assert_eq!(
+            decoded.versions(),
+            [KnownWalletVersion::V3R1, KnownWalletVersion::V3R2]
+        );
+        let [OutAction::SendMsg(action)]: [_; 1] = decoded.out_actions().try_into().unwrap() else {
+            unreachable!()
+        };
+        assert!(!action.mode.is_valid());
+        assert!(decoded.body.verify(&keypair.public_key).is_err());
+    }
+
+    #[test]
+    fn decode_highload_v3_batch() {
+        let wallet = Wallet::<HighloadV3>::derive_default(KeyPair::from_seed([7; 32])).unwrap();
+        let msg = wallet
+            .create_highload_external_message(
+                Default::default(),
+                DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+                HighloadV3::DEFAULT_TIMEOUT,
+                msgs(255),
+                false,
+            )
+            .unwrap()
+            .to_cell()
+            .unwrap();
+
+        let decoded = decode_external_message(&msg, &wallet.public_key()).unwrap();
+        assert_eq!(decoded.address, wallet.address());
+        assert_eq!(decoded.out_actions(), out_actions(255));
+    }
+}
@@ -0,0 +1,277 @@
+use std::sync::Arc;
+
+use bitvec::{field::BitField, order::Msb0, view::AsBits};
+use chrono::{DateTime, Utc};
+use lazy_static::lazy_static;
+use nacl::sign::PUBLIC_KEY_LENGTH;
+use tlb::{
+    bits::{de::BitReaderExt, ser::BitWriterExt},
+    de::{CellDeserialize, CellParser, CellParserError},
+    r#as::NoArgs,
+    ser::{CellBuilder, CellBuilderError, CellSerialize, CellSerializeExt},
+    Cell, Error,
+};
+use tlb_ton::{action::SendMsgAction, boc::BagOfCells, hashmap::HashmapE};
+
+use super::{verify, WalletVersion};
+
+lazy_static! {
+    static ref HIGHLOAD_V2_CODE_CELL: Arc<Cell> = {
+        BagOfCells::parse_base64(include_str!("./highload_v2.code"))
+            .unwrap()
+            .single_root()
+            .expect("code BoC must be single root")
+            .clone()
+    };
+}
+
+/// [Highload wallet v2](https://github.com/ton-blockchain/ton/blob/master/crypto/smartcont/highload-wallet-v2-code.fc).
+///
+/// Instead of seqno, each external message is identified by its
+/// [`query_id`](HighloadV2SignBody::query_id), which holds expiration time
+/// in its higher 32 bits. So, there are no deadline-less messages for this
+/// wallet and `expire_at` given to
+/// [`Wallet::create_external_message()`](super::Wallet::create_external_message)
+/// must be in the future, while `seqno` is used as lower 32 bits of the
+/// query ID.
+pub struct HighloadV2;
+
+impl HighloadV2 {
+    /// Maximum number of messages in a single batch
+    pub const MAX_MSGS: usize = 254;
+
+    /// Compose query ID from expiration time and arbitrary `n`, so that
+    /// query IDs are unique among messages expiring at the same time
+    #[inline]
+    pub fn query_id(expire_at: DateTime<Utc>, n: u32) -> u64 {
+        (expire_at.timestamp() as u64) << 32 | n as u64
+    }
+}
+
+impl WalletVersion for HighloadV2 {
+    type Data = HighloadV2Data;
+    type SignBody = HighloadV2SignBody;
+    type ExternalMsgBody = HighloadV2ExternalBody;
+
+    fn code() -> Arc<Cell> {
+        HIGHLOAD_V2_CODE_CELL.clone()
+    }
+
+    fn init_data(wallet_id: u32, pubkey: [u8; PUBLIC_KEY_LENGTH]) -> Self::Data {
+        HighloadV2Data {
+            wallet_id,
+            last_cleaned: 0,
+            pubkey,
+            old_queries: HashmapE::Empty,
+        }
+    }
+
+    fn create_sign_body(
+        wallet_id: u32,
+        expire_at: DateTime<Utc>,
+        seqno: u32,
+        msgs: impl IntoIterator<Item = SendMsgAction>,
+    ) -> Result<Self::SignBody, CellBuilderError> {
+        Ok(HighloadV2SignBody {
+            wallet_id,
+            query_id: HighloadV2::query_id(expire_at, seqno),
+            msgs: msgs.into_iter().collect(),
+        })
+    }
+
+    fn wrap_signed_external(body: Self::SignBody, signature: [u8; 64]) -> Self::ExternalMsgBody {
+        HighloadV2ExternalBody { signature, body }
+    }
+}
+
+/// ```tlb
+/// highload_v2_data$_ wallet_id:uint32 last_cleaned:uint64 pubkey:bits256
+///   old_queries:(HashmapE 64 Unit) = HighloadV2Data;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HighloadV2Data {
+    pub wallet_id: u32,
+    pub last_cleaned: u64,
+    pub pubkey: [u8; PUBLIC_KEY_LENGTH],
+    /// query ID -> ()
+    pub old_queries: HashmapE<()>,
+}
+
+impl HighloadV2Data {
+    /// Returns whether query with given ID was already processed and not
+    /// yet cleaned up, so it can not be sent again
+    #[inline]
+    pub fn is_processed(&self, query_id: u64) -> bool {
+        self.old_queries
+            .contains_key(query_id.to_be_bytes().as_bits::<Msb0>())
+    }
+}
+
+impl CellSerialize for HighloadV2Data {
+    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        builder
+            .pack(self.wallet_id)?
+            .pack(self.last_cleaned)?
+            .pack(self.pubkey)?
+            .store_as_with::<_, &HashmapE<NoArgs<_>, NoArgs<_>>>(&self.old_queries, (64, (), ()))?;
+        Ok(())
+    }
+}
+
+impl<'de> CellDeserialize<'de> for HighloadV2Data {
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        Ok(Self {
+            wallet_id: parser.unpack()?,
+            last_cleaned: parser.unpack()?,
+            pubkey: parser.unpack()?,
+            old_queries: parser.parse_as_with::<_, HashmapE<NoArgs<_>, NoArgs<_>>>((64, (), ()))?,
+        })
+    }
+}
+
+/// ```tlb
+/// highload_v2_sign_body$_ wallet_id:uint32 query_id:uint64
+///   msgs:(HashmapE 16 SendMsgAction) = HighloadV2SignBody;
+/// ```
+/// Messages are stored in the dictionary by their index in
+/// [`msgs`](HighloadV2SignBody::msgs).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HighloadV2SignBody {
+    pub wallet_id: u32,
+    pub query_id: u64,
+    pub msgs: Vec<SendMsgAction>,
+}
+
+impl HighloadV2SignBody {
+    /// Expiration time encoded in [`query_id`](HighloadV2SignBody::query_id)
+    #[inline]
+    pub fn expire_at(&self) -> Option<DateTime<Utc>> {
+        DateTime::from_timestamp((self.query_id >> 32) as i64, 0)
+    }
+}
+
+impl CellSerialize for HighloadV2SignBody {
+    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        if self.msgs.len() > HighloadV2::MAX_MSGS {
+            return Err(Error::custom(format!(
+                "too many messages: {} > {}",
+                self.msgs.len(),
+                HighloadV2::MAX_MSGS
+            )));
+        }
+        let mut msgs = HashmapE::new();
+        for (i, msg) in self.msgs.iter().enumerate() {
+            msgs.insert((i as i16).to_be_bytes().as_bits::<Msb0>(), msg)
+                .map_err(Error::custom)?;
+        }
+        builder
+            .pack(self.wallet_id)?
+            .pack(self.query_id)?
+            .store_as_with::<_, &HashmapE<NoArgs<_>, NoArgs<_>>>(&msgs, (16, (), ()))?;
+        Ok(())
+    }
+}
+
+impl<'de> CellDeserialize<'de> for HighloadV2SignBody {
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        let wallet_id = parser.unpack()?;
+        let query_id = parser.unpack()?;
+        let msgs: HashmapE<SendMsgAction> =
+            parser.parse_as_with::<_, HashmapE<NoArgs<_>, NoArgs<_>>>((16, (), ()))?;
+        // messages are sent in order of signed keys
+        let mut msgs: Vec<_> = msgs
+            .iter()
+            .map(|(key, msg)| (key.load_be::<u16>() as i16, msg.clone()))
+            .collect();
+        msgs.sort_by_key(|(i, _)| *i);
+        Ok(Self {
+            wallet_id,
+            query_id,
+            msgs: msgs.into_iter().map(|(_, msg)| msg).collect(),
+        })
+    }
+}
+
+/// ```tlb
+/// highload_v2_external$_ signature:bits512 body:HighloadV2SignBody = HighloadV2ExternalBody;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HighloadV2ExternalBody {
+    pub signature: [u8; 64],
+    pub body: HighloadV2SignBody,
+}
+
+impl HighloadV2ExternalBody {
+    /// Verify [`signature`](Self::signature) against given public key.
+    /// It covers the inline [`HighloadV2SignBody`] following it, hashed as
+    /// a cell on its own, so `msgs` are signed through the dictionary root.
+    #[inline]
+    pub fn verify(&self, public_key: &[u8; PUBLIC_KEY_LENGTH]) -> anyhow::Result<bool> {
+        verify(public_key, self.body.to_cell()?.hash(), &self.signature)
+    }
+}
+
+impl CellSerialize for HighloadV2ExternalBody {
+    #[inline]
+    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        builder.pack(self.signature)?.store(&self.body)?;
+        Ok(())
+    }
+}
+
+impl<'de> CellDeserialize<'de> for HighloadV2ExternalBody {
+    #[inline]
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        Ok(Self {
+            signature: parser.unpack()?,
+            body: parser.parse()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hex_literal::hex;
+
+    use crate::wallet::{tests::msgs, KeyPair, Wallet};
+
+    use super::*;
+
+    #[test]
+    fn check_code() {
+        assert_eq!(
+            HIGHLOAD_V2_CODE_CELL.hash(),
+            hex!("9494d1cc8edf12f05671a1a9ba09921096eb50811e1924ec65c3c629fbb80812"),
+        );
+    }
+
+    #[test]
+    fn external_body_serde() {
+        let wallet = Wallet::<HighloadV2>::derive_default(KeyPair::from_seed([5; 32])).unwrap();
+        let expire_at = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let msgs = msgs(20);
+        let msg = wallet
+            .create_external_message(expire_at, 7, msgs.clone(), false)
+            .unwrap();
+        assert!(msg.body.verify(&wallet.public_key()).unwrap());
+        assert_eq!(msg.body.body.expire_at(), Some(expire_at));
+        assert_eq!(msg.body.body.query_id & 0xffff_ffff, 7);
+
+        let got: HighloadV2ExternalBody = msg.body.to_cell().unwrap().parse_fully().unwrap();
+        assert_eq!(got.body.msgs, msgs);
+        assert_eq!(got, msg.body);
+    }
+
+    #[test]
+    fn is_processed() {
+        let mut data = HighloadV2::init_data(0, [0; PUBLIC_KEY_LENGTH]);
+        assert!(!data.is_processed(42));
+
+        data.old_queries
+            .insert(42u64.to_be_bytes().as_bits::<Msb0>(), ())
+            .unwrap();
+        let data: HighloadV2Data = data.to_cell().unwrap().parse_fully().unwrap();
+        assert!(data.is_processed(42));
+        assert!(!data.is_processed(43));
+    }
+}
@@ -0,0 +1,704 @@
+use std::{
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::ensure;
+use bitvec::{order::Msb0, view::AsBits};
+use chrono::{DateTime, Duration, Utc};
+use lazy_static::lazy_static;
+use nacl::sign::PUBLIC_KEY_LENGTH;
+use num_bigint::BigUint;
+use tlb::{
+    bits::{
+        de::{BitReader, BitReaderExt, BitUnpack},
+        r#as::NBits,
+        ser::{BitPack, BitWriter, BitWriterExt},
+    },
+    de::{CellDeserialize, CellParser, CellParserError},
+    r#as::{NoArgs, Ref},
+    ser::{CellBuilder, CellBuilderError, CellSerialize, CellSerializeExt},
+    Cell, Error,
+};
+use tlb_ton::{
+    action::{OutAction, OutList, SendMode, SendMsgAction},
+    boc::BagOfCells,
+    hashmap::HashmapE,
+    message::{CommonMsgInfo, Message},
+    MsgAddress,
+};
+
+use super::{verify, HasPublicKey, Signer, Wallet, WalletVersion};
+
+lazy_static! {
+    static ref HIGHLOAD_V3_CODE_CELL: Arc<Cell> = {
+        BagOfCells::parse_base64(include_str!("./highload_v3.code"))
+            .unwrap()
+            .single_root()
+            .expect("code BoC must be single root")
+            .clone()
+    };
+}
+
+/// [Highload wallet v3](https://github.com/ton-blockchain/highload-wallet-contract-v3).
+///
+/// Each external message is identified by [`HighloadV3QueryId`] and is
+/// valid for [`timeout`](HighloadV3Data::timeout) seconds since its
+/// [`created_at`](HighloadV3MsgInner::created_at), which must not be in the
+/// future. Use [`Wallet::create_highload_sign_body()`] to set them
+/// explicitly and to send batches of messages via
+/// [`internal_transfer`](HighloadV3::internal_transfer) to the wallet
+/// itself.
+///
+/// Wallets derived with [`Wallet::derive()`] use
+/// [`DEFAULT_TIMEOUT`](HighloadV3::DEFAULT_TIMEOUT), and generic
+/// [`Wallet::create_external_message()`] maps its arguments as following:
+/// * `created_at` is `expire_at - DEFAULT_TIMEOUT`, so `expire_at` should
+///   not be later than `DEFAULT_TIMEOUT` from now,
+/// * `seqno` is converted with [`HighloadV3QueryId::from_seqno()`],
+/// * exactly one message must be given, which is sent directly, otherwise
+///   an error is returned.
+pub struct HighloadV3;
+
+impl HighloadV3 {
+    /// Timeout used in init data of derived wallets
+    pub const DEFAULT_TIMEOUT: u32 = 60 * 60;
+
+    /// Maximum number of actions sent by a single
+    /// [`HighloadV3InternalTransfer`]
+    pub const MAX_ACTIONS: usize = 254;
+
+    /// Create action sending `internal_transfer` with given `value` to the
+    /// wallet at `address`, which in turn sends given messages.
+    ///
+    /// Messages exceeding [`MAX_ACTIONS`](HighloadV3::MAX_ACTIONS) are chained
+    /// via nested `internal_transfer`s, which are sent as the last action of
+    /// previous one. Zero `value` makes each of them carry all remaining
+    /// balance of the wallet.
+    pub fn internal_transfer(
+        address: MsgAddress,
+        query_id: HighloadV3QueryId,
+        msgs: impl IntoIterator<Item = SendMsgAction>,
+        value: BigUint,
+    ) -> Result<SendMsgAction, CellBuilderError> {
+        let mut msgs: Vec<_> = msgs.into_iter().collect();
+        if msgs.len() > Self::MAX_ACTIONS {
+            let rest = msgs.split_off(Self::MAX_ACTIONS - 1);
+            msgs.push(Self::internal_transfer(
+                address,
+                query_id,
+                rest,
+                value.clone(),
+            )?);
+        }
+        Ok(SendMsgAction {
+            mode: if value == BigUint::ZERO {
+                SendMode::CARRY_ALL_BALANCE
+            } else {
+                SendMode::PAY_FEES_SEPARATELY
+            },
+            message: Message::<HighloadV3InternalTransfer> {
+                info: CommonMsgInfo::transfer(address, value, true),
+                init: None,
+                body: HighloadV3InternalTransfer {
+                    query_id: query_id.into(),
+                    actions: msgs.into_iter().map(OutAction::SendMsg).collect(),
+                },
+            }
+            .normalize()?,
+        })
+    }
+}
+
+impl WalletVersion for HighloadV3 {
+    type Data = HighloadV3Data;
+    type SignBody = HighloadV3MsgInner;
+    type ExternalMsgBody = HighloadV3ExternalBody;
+
+    fn code() -> Arc<Cell> {
+        HIGHLOAD_V3_CODE_CELL.clone()
+    }
+
+    fn init_data(wallet_id: u32, pubkey: [u8; PUBLIC_KEY_LENGTH]) -> Self::Data {
+        HighloadV3Data {
+            pubkey,
+            wallet_id,
+            old_queries: HashmapE::Empty,
+            queries: HashmapE::Empty,
+            last_clean_time: 0,
+            timeout: Self::DEFAULT_TIMEOUT,
+        }
+    }
+
+    /// Sends the only message directly, see [`HighloadV3`] for details.
+    ///
+    /// Any other number of messages is rejected, since batches need wallet's
+    /// own address, see [`Wallet::create_highload_sign_body()`].
+    fn create_sign_body(
+        wallet_id: u32,
+        expire_at: DateTime<Utc>,
+        seqno: u32,
+        msgs: impl IntoIterator<Item = SendMsgAction>,
+    ) -> Result<Self::SignBody, CellBuilderError> {
+        let msgs: Vec<_> = msgs.into_iter().collect();
+        let [action] = <[SendMsgAction; 1]>::try_from(msgs).map_err(|msgs| {
+            Error::custom(format!(
+                "expected exactly one message, got {}, use Wallet::create_highload_sign_body() for batches",
+                msgs.len(),
+            ))
+        })?;
+        Ok(HighloadV3MsgInner {
+            wallet_id,
+            action,
+            query_id: HighloadV3QueryId::from_seqno(seqno),
+            created_at: expire_at - Duration::seconds(Self::DEFAULT_TIMEOUT.into()),
+            timeout: Self::DEFAULT_TIMEOUT,
+        })
+    }
+
+    fn wrap_signed_external(body: Self::SignBody, signature: [u8; 64]) -> Self::ExternalMsgBody {
+        HighloadV3ExternalBody { signature, body }
+    }
+}
+
+impl<S> Wallet<HighloadV3, S>
+where
+    S: HasPublicKey,
+{
+    /// Create body sending given messages as query with given ID, which is
+    /// valid for `timeout` seconds since `created_at`.
+    ///
+    /// `timeout` must match [`timeout`](HighloadV3Data::timeout) in data of
+    /// the deployed wallet, see [`WalletVersion::parse_data()`]. Since the
+    /// wallet rejects messages created in the future, `created_at` can not
+    /// be later than now and is better set slightly in the past.
+    ///
+    /// A single message is sent directly, while any other number of
+    /// messages is sent via [`internal_transfer`](HighloadV3::internal_transfer)
+    /// to the wallet itself, carrying all of its remaining balance.
+    pub fn create_highload_sign_body(
+        &self,
+        query_id: HighloadV3QueryId,
+        created_at: DateTime<Utc>,
+        timeout: u32,
+        msgs: impl IntoIterator<Item = SendMsgAction>,
+    ) -> anyhow::Result<HighloadV3MsgInner> {
+        ensure!(
+            created_at <= now(),
+            "created_at is in the future: {created_at}"
+        );
+        let msgs: Vec<_> = msgs.into_iter().collect();
+        let action = match <[SendMsgAction; 1]>::try_from(msgs) {
+            Ok([action]) => action,
+            Err(msgs) => {
+                HighloadV3::internal_transfer(self.address(), query_id, msgs, BigUint::ZERO)?
+            }
+        };
+        Ok(HighloadV3MsgInner {
+            wallet_id: self.wallet_id(),
+            action,
+            query_id,
+            created_at,
+            timeout,
+        })
+    }
+}
+
+impl<S> Wallet<HighloadV3, S>
+where
+    S: Signer,
+{
+    /// Shortcut to [create](Wallet::create_highload_sign_body),
+    /// [sign](Wallet::sign_body) and [wrap](Wallet::wrap_external_msg)
+    /// external message ready for sending to TON blockchain.
+    pub fn create_highload_external_message(
+        &self,
+        query_id: HighloadV3QueryId,
+        created_at: DateTime<Utc>,
+        timeout: u32,
+        msgs: impl IntoIterator<Item = SendMsgAction>,
+        state_init: bool,
+    ) -> anyhow::Result<Message<HighloadV3ExternalBody, Arc<Cell>, HighloadV3Data>> {
+        let sign_body = self.create_highload_sign_body(query_id, created_at, timeout, msgs)?;
+        let signature = self.sign_body(&sign_body)?;
+        let body = HighloadV3::wrap_signed_external(sign_body, signature);
+        Ok(self.wrap_external_msg(body, state_init))
+    }
+}
+
+/// Current time, which highload v3 compares `created_at` with
+fn now() -> DateTime<Utc> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time must be after UNIX epoch");
+    DateTime::from_timestamp(now.as_secs() as i64, 0).expect("system time must be in range")
+}
+
+/// ```tlb
+/// query_id$_ shift:uint13 bit_number:(## 10) { bit_number >= 0 } { bit_number < 1023 } = QueryId;
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct HighloadV3QueryId {
+    shift: u16,
+    bit_number: u16,
+}
+
+impl HighloadV3QueryId {
+    pub const MAX_SHIFT: u16 = (1 << 13) - 1;
+    pub const MAX_BIT_NUMBER: u16 = 1022;
+
+    /// Number of distinct query IDs
+    pub const COUNT: u32 = (Self::MAX_SHIFT as u32 + 1) * (Self::MAX_BIT_NUMBER as u32 + 1);
+
+    /// Returns `None` if `shift` or `bit_number` are out of bounds
+    #[inline]
+    pub const fn new(shift: u16, bit_number: u16) -> Option<Self> {
+        if shift > Self::MAX_SHIFT || bit_number > Self::MAX_BIT_NUMBER {
+            return None;
+        }
+        Some(Self { shift, bit_number })
+    }
+
+    /// Query ID with given sequential number, wrapping around after
+    /// [`COUNT`](HighloadV3QueryId::COUNT)
+    #[inline]
+    pub const fn from_seqno(seqno: u32) -> Self {
+        let seqno = seqno % Self::COUNT;
+        Self {
+            shift: (seqno / (Self::MAX_BIT_NUMBER as u32 + 1)) as u16,
+            bit_number: (seqno % (Self::MAX_BIT_NUMBER as u32 + 1)) as u16,
+        }
+    }
+
+    /// Sequential number of this query ID, inverse of
+    /// [`from_seqno()`](HighloadV3QueryId::from_seqno)
+    #[inline]
+    pub const fn seqno(&self) -> u32 {
+        self.shift as u32 * (Self::MAX_BIT_NUMBER as u32 + 1) + self.bit_number as u32
+    }
+
+    #[inline]
+    pub const fn shift(&self) -> u16 {
+        self.shift
+    }
+
+    #[inline]
+    pub const fn bit_number(&self) -> u16 {
+        self.bit_number
+    }
+
+    /// Next query ID, or `None` if all of them are exhausted
+    #[inline]
+    pub const fn next(&self) -> Option<Self> {
+        if self.bit_number < Self::MAX_BIT_NUMBER {
+            return Some(Self {
+                shift: self.shift,
+                bit_number: self.bit_number + 1,
+            });
+        }
+        if self.shift < Self::MAX_SHIFT {
+            return Some(Self {
+                shift: self.shift + 1,
+                bit_number: 0,
+            });
+        }
+        None
+    }
+}
+
+impl From<HighloadV3QueryId> for u64 {
+    #[inline]
+    fn from(query_id: HighloadV3QueryId) -> Self {
+        (query_id.shift as u64) << 10 | query_id.bit_number as u64
+    }
+}
+
+impl BitPack for HighloadV3QueryId {
+    #[inline]
+    fn pack<W>(&self, mut writer: W) -> Result<(), W::Error>
+    where
+        W: BitWriter,
+    {
+        writer
+            .pack_as::<_, NBits<13>>(self.shift)?
+            .pack_as::<_, NBits<10>>(self.bit_number)?;
+        Ok(())
+    }
+}
+
+impl BitUnpack for HighloadV3QueryId {
+    #[inline]
+    fn unpack<R>(mut reader: R) -> Result<Self, R::Error>
+    where
+        R: BitReader,
+    {
+        let shift = reader.unpack_as::<_, NBits<13>>()?;
+        let bit_number = reader.unpack_as::<_, NBits<10>>()?;
+        Self::new(shift, bit_number)
+            .ok_or_else(|| Error::custom(format!("invalid bit_number: {bit_number}")))
+    }
+}
+
+/// ```tlb
+/// highload_v3_data$_ pubkey:bits256 wallet_id:uint32
+///   old_queries:(HashmapE 13 ^Cell) queries:(HashmapE 13 ^Cell)
+///   last_clean_time:uint64 timeout:uint22 = HighloadV3Data;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HighloadV3Data {
+    pub pubkey: [u8; PUBLIC_KEY_LENGTH],
+    pub wallet_id: u32,
+    /// shift -> bitmap of processed bit numbers
+    pub old_queries: HashmapE<Cell>,
+    /// shift -> bitmap of processed bit numbers
+    pub queries: HashmapE<Cell>,
+    pub last_clean_time: u64,
+    pub timeout: u32,
+}
+
+impl HighloadV3Data {
+    /// Returns whether query with given ID is marked as processed in
+    /// [`queries`](HighloadV3Data::queries) or
+    /// [`old_queries`](HighloadV3Data::old_queries), so it can not be sent
+    /// again until cleaned up
+    pub fn is_processed(&self, query_id: HighloadV3QueryId) -> bool {
+        let shift = query_id.shift.to_be_bytes();
+        let key = &shift.as_bits::<Msb0>()[3..];
+        [&self.queries, &self.old_queries]
+            .into_iter()
+            .any(|queries| {
+                queries
+                    .get(key)
+                    .and_then(|bitmap| bitmap.data.get(query_id.bit_number as usize))
+                    .is_some_and(|bit| *bit)
+            })
+    }
+}
+
+impl CellSerialize for HighloadV3Data {
+    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        builder
+            .pack(self.pubkey)?
+            .pack(self.wallet_id)?
+            .store_as_with::<_, &HashmapE<Ref<NoArgs<_>>, NoArgs<_>>>(
+                &self.old_queries,
+                (13, (), ()),
+            )?
+            .store_as_with::<_, &HashmapE<Ref<NoArgs<_>>, NoArgs<_>>>(&self.queries, (13, (), ()))?
+            .pack(self.last_clean_time)?
+            .pack_as::<_, NBits<22>>(self.timeout)?;
+        Ok(())
+    }
+}
+
+impl<'de> CellDeserialize<'de> for HighloadV3Data {
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        Ok(Self {
+            pubkey: parser.unpack()?,
+            wallet_id: parser.unpack()?,
+            old_queries: parser.parse_as_with::<_, HashmapE<Ref<NoArgs<_>>, NoArgs<_>>>((
+                13,
+                (),
+                (),
+            ))?,
+            queries: parser.parse_as_with::<_, HashmapE<Ref<NoArgs<_>>, NoArgs<_>>>((
+                13,
+                (),
+                (),
+            ))?,
+            last_clean_time: parser.unpack()?,
+            timeout: parser.unpack_as::<_, NBits<22>>()?,
+        })
+    }
+}
+
+/// ```tlb
+/// _ subwallet_id:uint32 message_to_send:^Cell send_mode:uint8 query_id:QueryId
+///   created_at:uint64 timeout:uint22 = MsgInner;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HighloadV3MsgInner {
+    pub wallet_id: u32,
+    /// `message_to_send` with its `send_mode`
+    pub action: SendMsgAction,
+    pub query_id: HighloadV3QueryId,
+    pub created_at: DateTime<Utc>,
+    pub timeout: u32,
+}
+
+impl HighloadV3MsgInner {
+    /// Time after which the message can not be accepted anymore
+    #[inline]
+    pub fn expire_at(&self) -> DateTime<Utc> {
+        self.created_at + Duration::seconds(self.timeout.into())
+    }
+}
+
+impl CellSerialize for HighloadV3MsgInner {
+    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        builder
+            .pack(self.wallet_id)?
+            // message_to_send:^Cell send_mode:uint8
+            .store(&self.action)?
+            .pack(self.query_id)?
+            .pack(u64::try_from(self.created_at.timestamp()).map_err(Error::custom)?)?
+            .pack_as::<_, NBits<22>>(self.timeout)?;
+        Ok(())
+    }
+}
+
+impl<'de> CellDeserialize<'de> for HighloadV3MsgInner {
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        Ok(Self {
+            wallet_id: parser.unpack()?,
+            action: parser.parse()?,
+            query_id: parser.unpack()?,
+            created_at: {
+                let created_at: u64 = parser.unpack()?;
+                i64::try_from(created_at)
+                    .ok()
+                    .and_then(|created_at| DateTime::from_timestamp(created_at, 0))
+                    .ok_or_else(|| Error::custom(format!("invalid created_at: {created_at}")))?
+            },
+            timeout: parser.unpack_as::<_, NBits<22>>()?,
+        })
+    }
+}
+
+/// ```tlb
+/// msg_body$_ message:^MsgInner signature:bits512 = ExternalInMsgBody;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HighloadV3ExternalBody {
+    pub signature: [u8; 64],
+    pub body: HighloadV3MsgInner,
+}
+
+impl HighloadV3ExternalBody {
+    /// Verify [`signature`](Self::signature) against given public key.
+    /// Unlike in other wallets, signature follows the body here and covers
+    /// the hash of `message:^MsgInner` reference cell.
+    #[inline]
+    pub fn verify(&self, public_key: &[u8; PUBLIC_KEY_LENGTH]) -> anyhow::Result<bool> {
+        verify(public_key, self.body.to_cell()?.hash(), &self.signature)
+    }
+}
+
+impl CellSerialize for HighloadV3ExternalBody {
+    #[inline]
+    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        builder
+            .store_as::<_, Ref>(&self.body)?
+            .pack(self.signature)?;
+        Ok(())
+    }
+}
+
+impl<'de> CellDeserialize<'de> for HighloadV3ExternalBody {
+    #[inline]
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        Ok(Self {
+            body: parser.parse_as::<_, Ref>()?,
+            signature: parser.unpack()?,
+        })
+    }
+}
+
+/// ```tlb
+/// internal_transfer#ae42e5a4 query_id:uint64 actions:^OutList = InternalMsgBody;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HighloadV3InternalTransfer {
+    pub query_id: u64,
+    pub actions: OutList,
+}
+
+impl HighloadV3InternalTransfer {
+    const PREFIX: u32 = 0xae42e5a4;
+}
+
+impl CellSerialize for HighloadV3InternalTransfer {
+    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        builder
+            .pack(Self::PREFIX)?
+            .pack(self.query_id)?
+            .store_as::<_, Ref>(&self.actions)?;
+        Ok(())
+    }
+}
+
+impl<'de> CellDeserialize<'de> for HighloadV3InternalTransfer {
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        let prefix: u32 = parser.unpack()?;
+        if prefix != Self::PREFIX {
+            return Err(Error::custom(format!("unknown prefix: {prefix:#0x}")));
+        }
+        Ok(Self {
+            query_id: parser.unpack()?,
+            actions: parser.parse_as::<_, Ref>()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hex_literal::hex;
+    use tlb::bits::{de::unpack_fully, ser::pack};
+
+    use crate::wallet::{tests::msgs, KeyPair, Wallet};
+
+    use super::*;
+
+    #[test]
+    fn check_code() {
+        assert_eq!(
+            HIGHLOAD_V3_CODE_CELL.hash(),
+            hex!("11acad7955844090f283bf238bc1449871f783e7cc0979408d3f4859483e8525"),
+        );
+    }
+
+    #[test]
+    fn query_id() {
+        let first = HighloadV3QueryId::default();
+        assert_eq!(first.next(), HighloadV3QueryId::new(0, 1));
+        assert_eq!(
+            HighloadV3QueryId::new(0, HighloadV3QueryId::MAX_BIT_NUMBER)
+                .unwrap()
+                .next(),
+            HighloadV3QueryId::new(1, 0),
+        );
+        assert_eq!(
+            HighloadV3QueryId::new(
+                HighloadV3QueryId::MAX_SHIFT,
+                HighloadV3QueryId::MAX_BIT_NUMBER
+            )
+            .unwrap()
+            .next(),
+            None,
+        );
+        assert_eq!(HighloadV3QueryId::new(0, 1023), None);
+
+        let query_id = HighloadV3QueryId::from_seqno(5000);
+        assert_eq!(query_id, HighloadV3QueryId::new(4, 908).unwrap());
+        assert_eq!(query_id.seqno(), 5000);
+        assert_eq!(u64::from(query_id), 4 << 10 | 908);
+        assert_eq!(
+            HighloadV3QueryId::from_seqno(HighloadV3QueryId::COUNT),
+            first
+        );
+
+        let packed = pack(query_id).unwrap();
+        assert_eq!(packed.len(), 23);
+        assert_eq!(unpack_fully::<HighloadV3QueryId>(packed).unwrap(), query_id);
+    }
+
+    #[test]
+    fn single_message() {
+        let wallet = Wallet::<HighloadV3>::derive_default(KeyPair::from_seed([9; 32])).unwrap();
+        let expire_at = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let msg = wallet
+            .create_external_message(expire_at, 1, msgs(1), false)
+            .unwrap();
+
+        assert!(msg.body.verify(&wallet.public_key()).unwrap());
+        assert_eq!(msg.body.body.action, msgs(1)[0]);
+        assert_eq!(
+            msg.body.body.created_at,
+            expire_at - Duration::seconds(HighloadV3::DEFAULT_TIMEOUT.into()),
+        );
+        assert_eq!(msg.body.body.expire_at(), expire_at);
+
+        let got: HighloadV3ExternalBody = msg.body.to_cell().unwrap().parse_fully().unwrap();
+        assert_eq!(got, msg.body);
+    }
+
+    #[test]
+    fn not_single_message() {
+        let wallet = Wallet::<HighloadV3>::derive_default(KeyPair::from_seed([9; 32])).unwrap();
+        let expire_at = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+
+        // batches can only be sent via create_highload_external_message()
+        for n in [0, 2] {
+            assert!(wallet.create_sign_body(expire_at, 1, msgs(n)).is_err());
+            assert!(wallet
+                .create_external_message(expire_at, 1, msgs(n), false)
+                .is_err());
+        }
+    }
+
+    #[test]
+    fn chained_internal_transfers() {
+        let wallet = Wallet::<HighloadV3>::derive_default(KeyPair::from_seed([9; 32])).unwrap();
+        let msgs: Vec<_> = (0..3).flat_map(|_| msgs(200)).collect();
+        let created_at = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let msg = wallet
+            .create_highload_external_message(
+                HighloadV3QueryId::from_seqno(1),
+                created_at,
+                600,
+                msgs.clone(),
+                false,
+            )
+            .unwrap();
+        assert!(msg.body.verify(&wallet.public_key()).unwrap());
+        let body = msg.body.body;
+        assert_eq!(body.created_at, created_at);
+        assert_eq!(body.timeout, 600);
+
+        let mut sent = Vec::new();
+        let mut action = body.action;
+        loop {
+            assert_eq!(action.mode, SendMode::CARRY_ALL_BALANCE);
+            let CommonMsgInfo::Internal(info) = &action.message.info else {
+                unreachable!()
+            };
+            assert_eq!(info.dst, wallet.address());
+
+            let transfer: HighloadV3InternalTransfer = action.message.body.parse_fully().unwrap();
+            assert_eq!(transfer.query_id, u64::from(body.query_id));
+            assert!(transfer.actions.0.len() <= HighloadV3::MAX_ACTIONS);
+
+            let mut actions = transfer.actions.0.into_iter().map(|action| match action {
+                OutAction::SendMsg(action) => action,
+                _ => unreachable!(),
+            });
+            if sent.len() + HighloadV3::MAX_ACTIONS >= msgs.len() {
+                sent.extend(actions);
+                break;
+            }
+            sent.extend(actions.by_ref().take(HighloadV3::MAX_ACTIONS - 1));
+            action = actions.next().unwrap();
+            assert!(actions.next().is_none());
+        }
+        assert_eq!(sent, msgs);
+    }
+
+    #[test]
+    fn created_at_in_future() {
+        let wallet = Wallet::<HighloadV3>::derive_default(KeyPair::from_seed([9; 32])).unwrap();
+        let query_id = HighloadV3QueryId::default();
+        let created_at = now() + Duration::seconds(60);
+        assert!(wallet
+            .create_highload_sign_body(query_id, created_at, HighloadV3::DEFAULT_TIMEOUT, msgs(1))
+            .is_err());
+    }
+
+    #[test]
+    fn is_processed() {
+        let mut data = HighloadV3::init_data(0, [0; PUBLIC_KEY_LENGTH]);
+        let query_id = HighloadV3QueryId::new(5, 100).unwrap();
+        assert!(!data.is_processed(query_id));
+
+        let mut bitmap = Cell::default();
+        bitmap.data.resize(1023, false);
+        bitmap.data.set(100, true);
+        data.old_queries
+            .insert(&5u16.to_be_bytes().as_bits::<Msb0>()[3..], bitmap)
+            .unwrap();
+
+        let data: HighloadV3Data = data.to_cell().unwrap().parse_fully().unwrap();
+        assert!(data.is_processed(query_id));
+        assert!(!data.is_processed(query_id.next().unwrap()));
+        assert!(!data.is_processed(HighloadV3QueryId::new(6, 100).unwrap()));
+    }
+}
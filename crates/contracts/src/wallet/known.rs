@@ -0,0 +1,181 @@
+use std::sync::Arc;
+
+use lazy_static::lazy_static;
+use tlb::{de::CellParserError, Cell};
+
+use super::{
+    highload_v2::{HighloadV2, HighloadV2Data},
+    highload_v3::{HighloadV3, HighloadV3Data},
+    v3::{WalletV3Data, V3R1, V3R2},
+    v4r1::V4R1,
+    v4r2::{WalletV4R2Data, V4R2},
+    v5r1::{WalletV5R1Data, V5R1},
+    WalletVersion, PUBLIC_KEY_LENGTH,
+};
+
+lazy_static! {
+    static ref CODE_HASHES: [([u8; 32], KnownWalletVersion); KnownWalletVersion::ALL.len()] =
+        KnownWalletVersion::ALL.map(|version| (version.code().hash(), version));
+}
+
+/// Wallet version detected by code of the account.
+///
+/// ```rust
+/// # use ton_contracts::wallet::{KnownWalletVersion, WalletVersion, v4r2::V4R2};
+/// assert_eq!(
+///     KnownWalletVersion::from_code(&V4R2::code()),
+///     Some(KnownWalletVersion::V4R2),
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KnownWalletVersion {
+    V3R1,
+    V3R2,
+    V4R1,
+    V4R2,
+    V5R1,
+    HighloadV2,
+    HighloadV3,
+}
+
+impl KnownWalletVersion {
+    pub const ALL: [Self; 7] = [
+        Self::V3R1,
+        Self::V3R2,
+        Self::V4R1,
+        Self::V4R2,
+        Self::V5R1,
+        Self::HighloadV2,
+        Self::HighloadV3,
+    ];
+
+    /// Detect version by hash of the account code
+    #[inline]
+    pub fn from_code_hash(hash: &[u8; 32]) -> Option<Self> {
+        CODE_HASHES
+            .iter()
+            .find_map(|(h, version)| (h == hash).then_some(*version))
+    }
+
+    /// Detect version by the account code
+    #[inline]
+    pub fn from_code(code: &Cell) -> Option<Self> {
+        Self::from_code_hash(&code.hash())
+    }
+
+    /// Code of the wallet, see [`WalletVersion::code()`]
+    pub fn code(self) -> Arc<Cell> {
+        match self {
+            Self::V3R1 => V3R1::code(),
+            Self::V3R2 => V3R2::code(),
+            Self::V4R1 => V4R1::code(),
+            Self::V4R2 => V4R2::code(),
+            Self::V5R1 => V5R1::code(),
+            Self::HighloadV2 => HighloadV2::code(),
+            Self::HighloadV3 => HighloadV3::code(),
+        }
+    }
+
+    /// Parse data of deployed wallet of this version, see
+    /// [`WalletVersion::parse_data()`]
+    pub fn parse_data(self, data: &Cell) -> Result<KnownWalletData, CellParserError<'_>> {
+        Ok(match self {
+            Self::V3R1 => KnownWalletData::V3(V3R1::parse_data(data)?),
+            Self::V3R2 => KnownWalletData::V3(V3R2::parse_data(data)?),
+            Self::V4R1 => KnownWalletData::V4(V4R1::parse_data(data)?),
+            Self::V4R2 => KnownWalletData::V4(V4R2::parse_data(data)?),
+            Self::V5R1 => KnownWalletData::V5R1(V5R1::parse_data(data)?),
+            Self::HighloadV2 => KnownWalletData::HighloadV2(HighloadV2::parse_data(data)?),
+            Self::HighloadV3 => KnownWalletData::HighloadV3(HighloadV3::parse_data(data)?),
+        })
+    }
+}
+
+/// Data of [`KnownWalletVersion`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KnownWalletData {
+    /// [`V3R1`] and [`V3R2`]
+    V3(WalletV3Data),
+    /// [`V4R1`] and [`V4R2`]
+    V4(WalletV4R2Data),
+    V5R1(WalletV5R1Data),
+    HighloadV2(HighloadV2Data),
+    HighloadV3(HighloadV3Data),
+}
+
+impl KnownWalletData {
+    /// Current seqno, if the wallet uses one to protect against replays
+    #[inline]
+    pub fn seqno(&self) -> Option<u32> {
+        match self {
+            Self::V3(data) => Some(data.seqno),
+            Self::V4(data) => Some(data.seqno),
+            Self::V5R1(data) => Some(data.seqno),
+            Self::HighloadV2(_) | Self::HighloadV3(_) => None,
+        }
+    }
+
+    #[inline]
+    pub fn wallet_id(&self) -> u32 {
+        match self {
+            Self::V3(data) => data.wallet_id,
+            Self::V4(data) => data.wallet_id,
+            Self::V5R1(data) => data.wallet_id,
+            Self::HighloadV2(data) => data.wallet_id,
+            Self::HighloadV3(data) => data.wallet_id,
+        }
+    }
+
+    #[inline]
+    pub fn pubkey(&self) -> [u8; PUBLIC_KEY_LENGTH] {
+        match self {
+            Self::V3(data) => data.pubkey,
+            Self::V4(data) => data.pubkey,
+            Self::V5R1(data) => data.pubkey,
+            Self::HighloadV2(data) => data.pubkey,
+            Self::HighloadV3(data) => data.pubkey,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tlb::ser::CellSerializeExt;
+
+    use super::*;
+
+    #[test]
+    fn detect() {
+        let pubkey = [1; PUBLIC_KEY_LENGTH];
+        for version in KnownWalletVersion::ALL {
+            assert_eq!(
+                KnownWalletVersion::from_code(&version.code()),
+                Some(version)
+            );
+
+            let data = match version {
+                KnownWalletVersion::V3R1 => V3R1::init_data(42, pubkey).to_cell(),
+                KnownWalletVersion::V3R2 => V3R2::init_data(42, pubkey).to_cell(),
+                KnownWalletVersion::V4R1 => V4R1::init_data(42, pubkey).to_cell(),
+                KnownWalletVersion::V4R2 => V4R2::init_data(42, pubkey).to_cell(),
+                KnownWalletVersion::V5R1 => V5R1::init_data(42, pubkey).to_cell(),
+                KnownWalletVersion::HighloadV2 => HighloadV2::init_data(42, pubkey).to_cell(),
+                KnownWalletVersion::HighloadV3 => HighloadV3::init_data(42, pubkey).to_cell(),
+            }
+            .unwrap();
+            let data = version.parse_data(&data).unwrap();
+            assert_eq!(data.wallet_id(), 42);
+            assert_eq!(data.pubkey(), pubkey);
+            assert_eq!(
+                data.seqno(),
+                (!matches!(
+                    version,
+                    KnownWalletVersion::HighloadV2 | KnownWalletVersion::HighloadV3
+                ))
+                .then_some(0),
+            );
+        }
+
+        assert_eq!(KnownWalletVersion::from_code(&Cell::default()), None);
+    }
+}
@@ -1,16 +1,19 @@
-use std::{collections::HashSet, str::FromStr};
+use core::fmt::{self, Display};
+use std::str::FromStr;
 
-use anyhow::anyhow;
+use anyhow::{anyhow, ensure};
 use hmac::{Hmac, Mac};
 use lazy_static::lazy_static;
 use nacl::sign::generate_keypair;
 use pbkdf2::{password_hash::Output, pbkdf2_hmac};
-use sha2::Sha512;
+use rand::{seq::SliceRandom, thread_rng, Rng};
+use sha2::{Digest, Sha256, Sha512};
 
 use super::KeyPair;
 
 lazy_static! {
-    static ref WORDLIST_EN: HashSet<&'static str> = include_str!("./wordlist_en.txt")
+    /// Sorted list of allowed words
+    static ref WORDLIST_EN: Vec<&'static str> = include_str!("./wordlist_en.txt")
         .split_whitespace()
         .filter(|w| !w.is_empty())
         .collect();
@@ -28,11 +31,100 @@ lazy_static! {
 /// let kp: KeyPair = mnemonic.generate_keypair(None).unwrap();
 /// # assert_eq!(kp.secret_key, hex!("119dcf2840a3d56521d260b2f125eedc0d4f3795b9e627269a4b5a6dca8257bdc04ad1885c127fe863abb00752fa844e6439bb04f264d70de7cea580b32637ab"));
 /// ```
-#[derive(Debug, Clone)]
+///
+/// New mnemonics can be created with [`Mnemonic::generate()`]:
+/// ```rust
+/// # use ton_contracts::wallet::mnemonic::Mnemonic;
+/// let mnemonic = Mnemonic::generate(None).unwrap();
+/// assert!(mnemonic.is_basic_seed(None).unwrap());
+/// let restored: Mnemonic = mnemonic.to_string().parse().unwrap();
+/// # assert_eq!(restored, mnemonic);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Mnemonic([&'static str; 24]);
 
 impl Mnemonic {
     const PBKDF_ITERATIONS: u32 = 100000;
+    const BASIC_SEED_ITERATIONS: u32 = Self::PBKDF_ITERATIONS / 256;
+
+    /// Generate a new random mnemonic, optionally protected by password.
+    ///
+    /// Words are picked until the result satisfies the same rules as in
+    /// tonlib: it must be a [basic seed](Mnemonic::is_basic_seed) for given
+    /// password, and if the password is set, then the mnemonic should
+    /// [require a password](Mnemonic::is_password_needed).
+    #[inline]
+    pub fn generate(password: impl Into<Option<String>>) -> anyhow::Result<Self> {
+        Self::generate_with_rng(&mut thread_rng(), password)
+    }
+
+    /// Same as [`Mnemonic::generate()`], but uses given source of randomness
+    pub fn generate_with_rng<R>(
+        rng: &mut R,
+        password: impl Into<Option<String>>,
+    ) -> anyhow::Result<Self>
+    where
+        R: Rng + ?Sized,
+    {
+        let password = password.into().filter(|p| !p.is_empty());
+        loop {
+            let mnemonic = Self(core::array::from_fn(|_| {
+                *WORDLIST_EN.choose(rng).expect("wordlist is not empty")
+            }));
+            if password.is_some() && !mnemonic.is_password_needed()? {
+                continue;
+            }
+            if !mnemonic.is_basic_seed(password.clone())? {
+                continue;
+            }
+            return Ok(mnemonic);
+        }
+    }
+
+    /// Returns whether the mnemonic with given password is a valid TON seed.
+    ///
+    /// Random words satisfy this with probability of 1/256, so it helps to
+    /// detect typos and mnemonics of other formats (e.g. BIP-39).
+    pub fn is_basic_seed(&self, password: impl Into<Option<String>>) -> anyhow::Result<bool> {
+        let entropy = self.entropy(password)?;
+        let seed = Self::pbkdf2_sha512(
+            entropy.as_slice(),
+            "TON seed version",
+            Self::BASIC_SEED_ITERATIONS,
+            64,
+        )?;
+        Ok(seed[0] == 0)
+    }
+
+    /// Returns whether the mnemonic was generated with a password, i.e.
+    /// it is not a [basic seed](Mnemonic::is_basic_seed) without password
+    pub fn is_password_needed(&self) -> anyhow::Result<bool> {
+        let entropy = self.entropy(None)?;
+        let seed = Self::pbkdf2_sha512(entropy.as_slice(), "TON fast seed version", 1, 64)?;
+        Ok(seed[0] == 1 && !self.is_basic_seed(None)?)
+    }
+
+    /// Checks that the mnemonic can be used with given password
+    pub fn validate(&self, password: impl Into<Option<String>>) -> anyhow::Result<()> {
+        let password = password.into().filter(|p| !p.is_empty());
+        if password.is_some() {
+            ensure!(
+                self.is_password_needed()?,
+                "mnemonic is not protected by password"
+            );
+        }
+        ensure!(
+            self.is_basic_seed(password)?,
+            "invalid mnemonic or password"
+        );
+        Ok(())
+    }
+
+    /// Returns words of this mnemonic
+    #[inline]
+    pub const fn words(&self) -> &[&'static str; 24] {
+        &self.0
+    }
 
     /// Generate [`Keypair`] with optional password
     pub fn generate_keypair(&self, password: impl Into<Option<String>>) -> anyhow::Result<KeyPair> {
@@ -75,10 +167,10 @@ impl FromStr for Mnemonic {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut words = Vec::new();
         for w in s.split_whitespace() {
-            let Some(word) = WORDLIST_EN.get(w.to_lowercase().as_str()) else {
+            let Ok(i) = WORDLIST_EN.binary_search(&w.to_lowercase().as_str()) else {
                 return Err(anyhow!("word '{w}' is not in the allowed list"));
             };
-            words.push(*word);
+            words.push(WORDLIST_EN[i]);
         }
         Ok(Self(words.try_into().map_err(|words: Vec<_>| {
             anyhow!(
@@ -88,3 +180,269 @@ impl FromStr for Mnemonic {
         })?))
     }
 }
+
+impl Display for Mnemonic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0.join(" "))
+    }
+}
+
+/// [BIP-39](https://github.com/bitcoin/bips/blob/master/bip-0039.mediawiki)
+/// mnemonic of 12, 15, 18, 21 or 24 words with checksum, as used by
+/// Ledger-style and multi-chain wallets.
+///
+/// Unlike [`Mnemonic`], keys are derived from it using
+/// [SLIP-0010](https://github.com/satoshilabs/slips/blob/master/slip-0010.md)
+/// for ed25519 along `m/44'/607'/account'`.
+///
+/// Passphrase is not NFKD-normalized, so it should consist of ASCII
+/// characters to be compatible with other implementations.
+/// ```rust
+/// # use hex_literal::hex;
+/// # use ton_contracts::wallet::mnemonic::Bip39Mnemonic;
+/// let mnemonic: Bip39Mnemonic = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about"
+///     .parse()
+///     .unwrap();
+/// let kp = mnemonic.derive_keypair(None, 0).unwrap();
+/// # assert_eq!(kp.public_key, hex!("7952e94118f34607c75e23258dd9220d66ccac5a3ee074125c25068e8107bfbf"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bip39Mnemonic(Vec<&'static str>);
+
+impl Bip39Mnemonic {
+    const PBKDF_ITERATIONS: u32 = 2048;
+    /// [SLIP-0044](https://github.com/satoshilabs/slips/blob/master/slip-0044.md)
+    /// coin type of TON
+    pub const TON_COIN_TYPE: u32 = 607;
+    const HARDENED: u32 = 0x80000000;
+
+    /// Returns words of this mnemonic
+    #[inline]
+    pub fn words(&self) -> &[&'static str] {
+        &self.0
+    }
+
+    /// 64-byte seed with optional passphrase
+    pub fn to_seed(&self, passphrase: impl Into<Option<String>>) -> anyhow::Result<[u8; 64]> {
+        let salt = format!("mnemonic{}", passphrase.into().unwrap_or_default());
+        let seed = Mnemonic::pbkdf2_sha512(
+            self.0.join(" ").as_bytes(),
+            &salt,
+            Self::PBKDF_ITERATIONS,
+            64,
+        )?;
+        Ok(seed.try_into().expect("64 bytes"))
+    }
+
+    /// Derive [`KeyPair`] for given account index along `m/44'/607'/account'`
+    #[inline]
+    pub fn derive_keypair(
+        &self,
+        passphrase: impl Into<Option<String>>,
+        account: u32,
+    ) -> anyhow::Result<KeyPair> {
+        self.derive_keypair_path(passphrase, &[44, Self::TON_COIN_TYPE, account])
+    }
+
+    /// Derive [`KeyPair`] along arbitrary path. Only hardened derivation is
+    /// defined for ed25519, so all indices are hardened, i.e. `[44, 607, 0]`
+    /// stands for `m/44'/607'/0'`.
+    pub fn derive_keypair_path(
+        &self,
+        passphrase: impl Into<Option<String>>,
+        path: &[u32],
+    ) -> anyhow::Result<KeyPair> {
+        let secret = slip10_ed25519(&self.to_seed(passphrase)?, path)?;
+        Ok(generate_keypair(&secret).into())
+    }
+}
+
+/// [SLIP-0010](https://github.com/satoshilabs/slips/blob/master/slip-0010.md)
+/// hardened derivation of ed25519 private key
+fn slip10_ed25519(seed: &[u8], path: &[u32]) -> anyhow::Result<[u8; 32]> {
+    let mut mac = Hmac::<Sha512>::new_from_slice(b"ed25519 seed")?;
+    mac.update(seed);
+    let mut i: [u8; 64] = mac.finalize().into_bytes().into();
+    for &index in path {
+        ensure!(
+            index < Bip39Mnemonic::HARDENED,
+            "index is too big to be hardened: {index}"
+        );
+        let (key, chain_code) = i.split_at(32);
+        let mut mac = Hmac::<Sha512>::new_from_slice(chain_code)?;
+        mac.update(&[0]);
+        mac.update(key);
+        mac.update(&(index | Bip39Mnemonic::HARDENED).to_be_bytes());
+        i = mac.finalize().into_bytes().into();
+    }
+    Ok(i[..32].try_into().expect("32 bytes"))
+}
+
+impl FromStr for Bip39Mnemonic {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut words = Vec::new();
+        let mut bits = Vec::new();
+        for w in s.split_whitespace() {
+            let Ok(i) = WORDLIST_EN.binary_search(&w.to_lowercase().as_str()) else {
+                return Err(anyhow!("word '{w}' is not in the allowed list"));
+            };
+            words.push(WORDLIST_EN[i]);
+            bits.extend((0..11).rev().map(|b| (i >> b) & 1 == 1));
+        }
+        ensure!(
+            matches!(words.len(), 12 | 15 | 18 | 21 | 24),
+            "mnemonic must consist from 12, 15, 18, 21 or 24 words, got: {}",
+            words.len()
+        );
+
+        // ENT + ENT / 32 bits
+        let (entropy, checksum) = bits.split_at(bits.len() * 32 / 33);
+        let entropy: Vec<u8> = entropy
+            .chunks(8)
+            .map(|byte| byte.iter().fold(0, |b, &bit| b << 1 | bit as u8))
+            .collect();
+        let hash = Sha256::digest(&entropy);
+        ensure!(
+            checksum
+                .iter()
+                .enumerate()
+                .all(|(i, &bit)| (hash[i / 8] >> (7 - i % 8)) & 1 == bit as u8),
+            "invalid checksum"
+        );
+        Ok(Self(words))
+    }
+}
+
+impl Display for Bip39Mnemonic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hex_literal::hex;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::{
+        super::{v4r2::V4R2, Wallet},
+        *,
+    };
+
+    const BASIC: &str = "dose ice enrich trigger test dove century still betray gas diet dune use other base gym mad law immense village world example praise game";
+    const PASSWORD_PROTECTED: &str = "actress dress hundred shuffle seed salt lamp swim jazz cook guilt cable winner sign invest pretty share where grunt bench spot one hover imitate";
+    const INVALID: &str = "clarify ritual innocent bachelor myth hope blood school trick audit slam glare dilemma salute hold motor grab knife badge witness eagle slice maple truly";
+
+    #[test]
+    fn basic_seed() {
+        let mnemonic: Mnemonic = BASIC.parse().unwrap();
+        assert!(mnemonic.is_basic_seed(None).unwrap());
+        assert!(!mnemonic.is_password_needed().unwrap());
+        mnemonic.validate(None).unwrap();
+        assert!(mnemonic.validate("secret".to_string()).is_err());
+        assert_eq!(mnemonic.to_string(), BASIC);
+    }
+
+    #[test]
+    fn password_protected() {
+        let mnemonic: Mnemonic = PASSWORD_PROTECTED.parse().unwrap();
+        assert!(!mnemonic.is_basic_seed(None).unwrap());
+        assert!(mnemonic.is_password_needed().unwrap());
+        assert!(mnemonic.is_basic_seed("secret".to_string()).unwrap());
+        mnemonic.validate("secret".to_string()).unwrap();
+        assert!(mnemonic.validate(None).is_err());
+    }
+
+    /// Mnemonic and wallet address from the [`Wallet`](super::super::Wallet)
+    /// example, generated by a wallet app independently of this crate
+    #[test]
+    fn wallet_app_vector() {
+        let mnemonic: Mnemonic = "jewel loop vast intact snack drip fatigue lunch erode green indoor balance together scrub hen monster hour narrow banner warfare increase panel sound spell"
+            .parse()
+            .unwrap();
+        assert!(mnemonic.is_basic_seed(None).unwrap());
+        assert!(!mnemonic.is_password_needed().unwrap());
+        mnemonic.validate(None).unwrap();
+
+        let keypair = mnemonic.generate_keypair(None).unwrap();
+        assert_eq!(
+            Wallet::<V4R2>::derive_default(keypair).unwrap().address(),
+            "UQA7RMTgzvcyxNNLmK2HdklOvFE8_KNMa-btKZ0dPU1UsqfC"
+                .parse()
+                .unwrap(),
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        let mnemonic: Mnemonic = INVALID.parse().unwrap();
+        assert!(!mnemonic.is_basic_seed(None).unwrap());
+        assert!(!mnemonic.is_password_needed().unwrap());
+        assert!(mnemonic.validate(None).is_err());
+    }
+
+    #[test]
+    fn bip39_checksum() {
+        assert!("abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about"
+            .parse::<Bip39Mnemonic>()
+            .is_ok());
+        assert!("abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon art"
+            .parse::<Bip39Mnemonic>()
+            .is_ok());
+        assert!("abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon"
+            .parse::<Bip39Mnemonic>()
+            .is_err());
+        assert!(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about"
+                .parse::<Bip39Mnemonic>()
+                .is_err()
+        );
+        assert!(BASIC.parse::<Bip39Mnemonic>().is_err());
+    }
+
+    #[test]
+    fn bip39_seed() {
+        let mnemonic: Bip39Mnemonic = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            mnemonic.to_seed("TREZOR".to_string()).unwrap(),
+            hex!("c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04"),
+        );
+
+        let kp = mnemonic.derive_keypair(None, 1).unwrap();
+        assert_eq!(
+            kp.secret_key[..32],
+            hex!("ee7d43cd863e47480496a7ab9e8ff8daaa220cb90d21747800b3e07fdf49152e"),
+        );
+        assert_eq!(
+            kp.public_key,
+            hex!("1d87da6f9190dddea5650e9156ff32ca359e61fb473642f0c76b7b79514f0d4d"),
+        );
+    }
+
+    #[test]
+    fn slip10_vector_1() {
+        let seed = hex!("000102030405060708090a0b0c0d0e0f");
+        assert_eq!(
+            slip10_ed25519(&seed, &[]).unwrap(),
+            hex!("2b4be7f19ee27bbf30c667b642d5f4aa69fd169872f8fc3059c08ebae2eb19e7"),
+        );
+        assert_eq!(
+            slip10_ed25519(&seed, &[0]).unwrap(),
+            hex!("68e0fe46dfb67e368c75379acec591dad19df3cde26e63b93a8e704f1dade7a3"),
+        );
+        assert!(slip10_ed25519(&seed, &[0x80000000]).is_err());
+    }
+
+    #[test]
+    fn generate() {
+        let mut rng = StdRng::seed_from_u64(0);
+
+        let mnemonic = Mnemonic::generate_with_rng(&mut rng, None).unwrap();
+        mnemonic.validate(None).unwrap();
+        assert_eq!(mnemonic.to_string().parse::<Mnemonic>().unwrap(), mnemonic);
+    }
+}
@@ -1,31 +1,42 @@
 //! TON [Wallet](https://docs.ton.org/participate/wallets/contracts)
+mod decode;
+pub mod highload_v2;
+pub mod highload_v3;
+mod known;
 pub mod mnemonic;
 mod signer;
+pub mod v3;
+pub mod v4r1;
 pub mod v4r2;
 pub mod v5r1;
 mod version;
 
-pub use self::{signer::*, version::*};
+pub use self::{decode::*, known::*, signer::*, version::*};
 
 use core::marker::PhantomData;
 use std::sync::Arc;
 
+use anyhow::{ensure, Context};
 use chrono::{DateTime, Utc};
 use num_bigint::BigUint;
-use tlb::{
-    ser::{CellBuilderError, CellSerializeExt},
-    Cell,
-};
+use tlb::{ser::CellBuilderError, Cell};
 use tlb_ton::{
     action::SendMsgAction,
     message::{CommonMsgInfo, ExternalInMsgInfo, Message},
     state_init::StateInit,
-    MsgAddress,
+    MsgAddress, MsgAddressExt,
 };
 
 pub const DEFAULT_WALLET_ID: u32 = 0x29a9a317;
 
-/// Generic wallet for signing messages
+/// Generic wallet for signing messages.
+///
+/// Signing is delegated to [`Signer`] or [`AsyncSigner`], which is
+/// [`KeyPair`] by default. Wallet with only a public key can be used to
+/// sign messages elsewhere:
+/// [create](Wallet::create_sign_body) body, [export](Wallet::sign_body_hash)
+/// its hash, sign it and [wrap](Wallet::wrap_signed) the signature into a
+/// message.
 ///
 /// ```rust
 /// # use ton_contracts::wallet::{
@@ -45,45 +56,43 @@ pub const DEFAULT_WALLET_ID: u32 = 0x29a9a317;
 ///     "UQA7RMTgzvcyxNNLmK2HdklOvFE8_KNMa-btKZ0dPU1UsqfC".parse().unwrap(),
 /// )
 /// ```
-pub struct Wallet<V> {
+pub struct Wallet<V, S = KeyPair> {
     address: MsgAddress,
     wallet_id: u32,
-    keypair: KeyPair,
+    signer: S,
     _phantom: PhantomData<V>,
 }
 
-impl<V> Wallet<V>
+impl<V, S> Wallet<V, S>
 where
     V: WalletVersion,
+    S: HasPublicKey,
 {
     #[inline]
-    pub const fn new(address: MsgAddress, keypair: KeyPair, wallet_id: u32) -> Self {
+    pub const fn new(address: MsgAddress, signer: S, wallet_id: u32) -> Self {
         Self {
             address,
             wallet_id,
-            keypair,
+            signer,
             _phantom: PhantomData,
         }
     }
 
-    /// Derive wallet from its workchain, keypair and id
+    /// Derive wallet from its workchain, signer and id
     #[inline]
-    pub fn derive(
-        workchain_id: i32,
-        keypair: KeyPair,
-        wallet_id: u32,
-    ) -> Result<Self, CellBuilderError> {
+    pub fn derive(workchain_id: i32, signer: S, wallet_id: u32) -> Result<Self, CellBuilderError> {
         Ok(Self::new(
-            MsgAddress::derive(workchain_id, V::state_init(wallet_id, keypair.public_key))?,
-            keypair,
+            MsgAddress::derive(workchain_id, V::state_init(wallet_id, signer.public_key()))?,
+            signer,
             wallet_id,
         ))
     }
 
-    /// Shortcut for [`Wallet::derive()`] with default workchain and wallet id
+    /// Shortcut for [`Wallet::derive()`] with default workchain and
+    /// [wallet id](WalletVersion::DEFAULT_WALLET_ID)
     #[inline]
-    pub fn derive_default(keypair: KeyPair) -> Result<Self, CellBuilderError> {
-        Self::derive(0, keypair, DEFAULT_WALLET_ID)
+    pub fn derive_default(signer: S) -> Result<Self, CellBuilderError> {
+        Self::derive(0, signer, V::DEFAULT_WALLET_ID)
     }
 
     /// Address of the wallet
@@ -99,8 +108,13 @@ where
     }
 
     #[inline]
-    pub const fn public_key(&self) -> &[u8; PUBLIC_KEY_LENGTH] {
-        &self.keypair.public_key
+    pub fn public_key(&self) -> [u8; PUBLIC_KEY_LENGTH] {
+        self.signer.public_key()
+    }
+
+    #[inline]
+    pub const fn signer(&self) -> &S {
+        &self.signer
     }
 
     /// Create external body for this wallet.
@@ -110,16 +124,65 @@ where
         expire_at: DateTime<Utc>,
         seqno: u32,
         msgs: impl IntoIterator<Item = SendMsgAction>,
-    ) -> V::SignBody {
+    ) -> Result<V::SignBody, CellBuilderError> {
         V::create_sign_body(self.wallet_id, expire_at, seqno, msgs)
     }
 
+    /// Hash of the body from [`.create_sign_body()`](Wallet::create_sign_body)
+    /// to be signed
     #[inline]
-    pub fn sign(&self, msg: impl AsRef<[u8]>) -> anyhow::Result<[u8; 64]> {
-        self.keypair.sign(msg)
+    pub fn sign_body_hash(&self, body: &V::SignBody) -> Result<[u8; 32], CellBuilderError> {
+        V::sign_body_hash(body)
+    }
+
+    /// Wrap body from [`.create_sign_body()`](Wallet::create_sign_body) and
+    /// its signature obtained elsewhere into a message ready for sending
+    /// to TON blockchain. The signature is verified against wallet's public
+    /// key.
+    pub fn wrap_signed(
+        &self,
+        body: V::SignBody,
+        signature: [u8; 64],
+        state_init: bool,
+    ) -> anyhow::Result<Message<V::ExternalMsgBody, Arc<Cell>, V::Data>> {
+        ensure!(
+            verify(&self.public_key(), self.sign_body_hash(&body)?, &signature)?,
+            "invalid signature"
+        );
+        Ok(self.wrap_external_msg(V::wrap_signed_external(body, signature), state_init))
+    }
+
+    /// Wrap signed body from [`.sign_body()`](Wallet::sign_body) in a message
+    /// ready for sending to TON blockchain.
+    #[inline]
+    pub fn wrap_external_msg(
+        &self,
+        body: V::ExternalMsgBody,
+        state_init: bool,
+    ) -> Message<V::ExternalMsgBody, Arc<Cell>, V::Data> {
+        Message {
+            info: CommonMsgInfo::ExternalIn(ExternalInMsgInfo {
+                src: MsgAddressExt::None,
+                dst: self.address(),
+                import_fee: BigUint::ZERO,
+            }),
+            init: state_init.then(|| self.state_init()),
+            body,
+        }
+    }
+
+    #[inline]
+    pub fn state_init(&self) -> StateInit<Arc<Cell>, V::Data> {
+        V::state_init(self.wallet_id(), self.public_key())
     }
+}
 
-    /// Shortcut to [create](Wallet::create_external_body),
+impl<V, S> Wallet<V, S>
+where
+    V: WalletVersion,
+    S: Signer,
+{
+    /// Shortcut to [create](Wallet::create_sign_body),
     /// [sign](Wallet::sign_body) and [wrap](Wallet::wrap_signed) external
     /// message ready for sending to TON blockchain.
     ///
@@ -129,7 +192,7 @@ where
     /// # use tlb_ton::{
     /// #   message::Message,
     /// #   currency::ONE_TON,
-    /// #   action::SendMsgAction,
+    /// #   action::{SendMode, SendMsgAction},
     /// # };
     /// # use ton_contracts::wallet::{
     /// #   mnemonic::Mnemonic,
@@ -147,7 +210,7 @@ where
     ///     Default::default(), // DateTime::UNIX_EPOCH means no deadline
     ///     0, // seqno
     ///     [SendMsgAction {
-    ///         mode: 3,
+    ///         mode: SendMode::PAY_FEES_SEPARATELY | SendMode::IGNORE_ERRORS,
     ///         message: Message::<()>::transfer(
     ///             "EQAWezezpqKTbO6xjCussXDdIeJ7XxTcErjA6uD3T3r7AwTk"
     ///                 .parse()
@@ -171,7 +234,7 @@ where
         msgs: impl IntoIterator<Item = SendMsgAction>,
         state_init: bool,
     ) -> anyhow::Result<Message<V::ExternalMsgBody, Arc<Cell>, V::Data>> {
-        let sign_body = self.create_sign_body(expire_at, seqno, msgs);
+        let sign_body = self.create_sign_body(expire_at, seqno, msgs)?;
         let signature = self.sign_body(&sign_body)?;
         let body = V::wrap_signed_external(sign_body, signature);
         let wrapped = self.wrap_external_msg(body, state_init);
@@ -179,33 +242,169 @@ where
     }
 
     /// Sign body from [`.create_sign_body()`](Wallet::create_sign_body)
-    /// using this wallet's private key
+    /// using this wallet's [`Signer`]
     #[inline]
     pub fn sign_body(&self, msg: &V::SignBody) -> anyhow::Result<[u8; 64]> {
-        self.sign(msg.to_cell()?.hash())
+        self.signer.sign_hash(&self.sign_body_hash(msg)?)
     }
 
-    /// Wrap signed body from [`.sign_body()`](Wallet::sign_body) in a message
-    /// ready for sending to TON blockchain.
+    /// Sign given hash using this wallet's [`Signer`].
+    /// Since [`Signer`] only signs hashes, `msg` must be exactly 32 bytes long.
+    #[deprecated(note = "use `.sign_body()` or `.signer().sign_hash()` instead")]
     #[inline]
-    pub fn wrap_external_msg(
+    pub fn sign(&self, msg: impl AsRef<[u8]>) -> anyhow::Result<[u8; 64]> {
+        let hash = msg
+            .as_ref()
+            .try_into()
+            .context("only 32-byte hashes can be signed")?;
+        self.signer.sign_hash(hash)
+    }
+}
+
+impl<V, S> Wallet<V, S>
+where
+    V: WalletVersion,
+    S: AsyncSigner,
+{
+    /// Same as [`.create_external_message()`](Wallet::create_external_message),
+    /// but signs with [`AsyncSigner`]
+    pub async fn create_external_message_async(
         &self,
-        body: V::ExternalMsgBody,
+        expire_at: DateTime<Utc>,
+        seqno: u32,
+        msgs: impl IntoIterator<Item = SendMsgAction>,
         state_init: bool,
-    ) -> Message<V::ExternalMsgBody, Arc<Cell>, V::Data> {
-        Message {
-            info: CommonMsgInfo::ExternalIn(ExternalInMsgInfo {
-                src: MsgAddress::NULL,
-                dst: self.address(),
-                import_fee: BigUint::ZERO,
-            }),
-            init: state_init.then(|| self.state_init()),
-            body,
-        }
+    ) -> anyhow::Result<Message<V::ExternalMsgBody, Arc<Cell>, V::Data>> {
+        let sign_body = self.create_sign_body(expire_at, seqno, msgs)?;
+        let signature = self.sign_body_async(&sign_body).await?;
+        let body = V::wrap_signed_external(sign_body, signature);
+        Ok(self.wrap_external_msg(body, state_init))
     }
 
+    /// Same as [`.sign_body()`](Wallet::sign_body), but signs with
+    /// [`AsyncSigner`]
     #[inline]
-    pub fn state_init(&self) -> StateInit<Arc<Cell>, V::Data> {
-        V::state_init(self.wallet_id(), *self.public_key())
+    pub async fn sign_body_async(&self, msg: &V::SignBody) -> anyhow::Result<[u8; 64]> {
+        let hash = self.sign_body_hash(msg)?;
+        self.signer.sign_hash_async(&hash).await
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use core::{
+        future::Future,
+        pin::pin,
+        task::{Context, Poll, Waker},
+    };
+
+    use tlb::ser::CellSerializeExt;
+    use tlb_ton::{action::SendMode, currency::ONE_TON};
+
+    use super::{mnemonic::Mnemonic, v4r2::V4R2, *};
+
+    /// Keypair of the well-known test mnemonic
+    pub(crate) fn keypair() -> KeyPair {
+        let mnemonic: Mnemonic = "jewel loop vast intact snack drip fatigue lunch erode green indoor balance together scrub hen monster hour narrow banner warfare increase panel sound spell"
+            .parse()
+            .unwrap();
+        mnemonic.generate_keypair(None).unwrap()
+    }
+
+    /// `n` transfers of 1 TON, `i`-th of them to `0:iii...i` address
+    pub(crate) fn msgs(n: u8) -> Vec<SendMsgAction> {
+        (0..n)
+            .map(|i| SendMsgAction {
+                mode: SendMode::PAY_FEES_SEPARATELY | SendMode::IGNORE_ERRORS,
+                message: Message::<()>::transfer(
+                    MsgAddress {
+                        workchain_id: 0,
+                        address: [i; 32],
+                    },
+                    ONE_TON.clone(),
+                    false,
+                )
+                .normalize()
+                .unwrap(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn detached_signing() {
+        let keypair = keypair();
+        let wallet = Wallet::<V4R2>::derive_default(keypair.clone()).unwrap();
+        let detached = Wallet::<V4R2, _>::derive_default(keypair.public_key).unwrap();
+        assert_eq!(detached.address(), wallet.address());
+
+        let expire_at = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let body = detached.create_sign_body(expire_at, 1, msgs(1)).unwrap();
+        let hash = detached.sign_body_hash(&body).unwrap();
+        let signature = keypair.sign_hash(&hash).unwrap();
+        let msg = detached.wrap_signed(body, signature, true).unwrap();
+
+        assert_eq!(
+            msg.to_cell().unwrap(),
+            wallet
+                .create_external_message(expire_at, 1, msgs(1), true)
+                .unwrap()
+                .to_cell()
+                .unwrap(),
+        );
+
+        let body = detached.create_sign_body(expire_at, 2, msgs(1)).unwrap();
+        assert!(detached.wrap_signed(body, signature, false).is_err());
+    }
+
+    #[test]
+    fn verify_external_body() {
+        let wallet = Wallet::<V4R2>::derive_default(keypair()).unwrap();
+        let expire_at = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let mut msg = wallet
+            .create_external_message(expire_at, 1, msgs(1), false)
+            .unwrap();
+
+        assert!(msg.body.verify(&wallet.public_key()).unwrap());
+        assert!(!msg
+            .body
+            .verify(&KeyPair::from_seed([0; 32]).public_key)
+            .unwrap());
+
+        msg.body.body.seqno += 1;
+        assert!(!msg.body.verify(&wallet.public_key()).unwrap());
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn sign_hash() {
+        let keypair = keypair();
+        let wallet = Wallet::<V4R2>::derive_default(keypair.clone()).unwrap();
+        let hash = [1; 32];
+
+        assert_eq!(
+            wallet.sign(hash).unwrap(),
+            keypair.sign_hash(&hash).unwrap()
+        );
+        assert!(wallet.sign([1; 31]).is_err());
+    }
+
+    #[test]
+    fn async_signing() {
+        let wallet = Wallet::<V4R2>::derive_default(keypair()).unwrap();
+        let expire_at = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+
+        let fut = pin!(wallet.create_external_message_async(expire_at, 1, msgs(1), false));
+        let Poll::Ready(msg) = fut.poll(&mut Context::from_waker(Waker::noop())) else {
+            unreachable!("signing with KeyPair is not pending");
+        };
+
+        assert_eq!(
+            msg.unwrap().to_cell().unwrap(),
+            wallet
+                .create_external_message(expire_at, 1, msgs(1), false)
+                .unwrap()
+                .to_cell()
+                .unwrap(),
+        );
     }
 }
@@ -1,9 +1,18 @@
-use anyhow::anyhow;
-use nacl::sign::{signature, Keypair};
+use core::{
+    fmt::{self, Debug},
+    future::{self, Future},
+};
+
+use anyhow::{anyhow, ensure};
+use nacl::sign::{generate_keypair, signature, Keypair};
+use zeroize::Zeroize;
 
 pub use nacl::sign::{PUBLIC_KEY_LENGTH, SECRET_KEY_LENGTH};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Ed25519 key pair.
+///
+/// Secret key is zeroized on drop and never printed by [`Debug`].
+#[derive(Clone, PartialEq, Eq)]
 pub struct KeyPair {
     /// Secret key of this pair.
     pub secret_key: [u8; SECRET_KEY_LENGTH],
@@ -13,11 +22,10 @@ pub struct KeyPair {
 }
 
 impl From<Keypair> for KeyPair {
-    fn from(Keypair { skey, pkey }: Keypair) -> Self {
-        Self {
-            secret_key: skey,
-            public_key: pkey,
-        }
+    fn from(mut kp: Keypair) -> Self {
+        let pair = Self::new(kp.skey, kp.pkey);
+        kp.skey.zeroize();
+        pair
     }
 }
 
@@ -33,6 +41,27 @@ impl KeyPair {
         }
     }
 
+    /// Restore key pair from its secret key, which is `seed || public_key`.
+    /// Public key is derived from the seed and checked against the one
+    /// embedded into secret key.
+    pub fn from_secret(secret_key: [u8; SECRET_KEY_LENGTH]) -> anyhow::Result<Self> {
+        let pair = Self::from_seed(secret_key[..32].try_into()?);
+        ensure!(
+            pair.secret_key == secret_key,
+            "public key does not match the secret key"
+        );
+        Ok(pair)
+    }
+
+    /// Derive key pair from 32-byte seed
+    #[inline]
+    pub fn from_seed(seed: [u8; 32]) -> Self {
+        let mut seed = seed;
+        let pair = generate_keypair(&seed).into();
+        seed.zeroize();
+        pair
+    }
+
     pub fn sign(&self, msg: impl AsRef<[u8]>) -> anyhow::Result<[u8; 64]> {
         signature(msg.as_ref(), self.secret_key.as_slice())
             .map_err(|e| anyhow!("{}", e.message))?
@@ -44,4 +73,141 @@ impl KeyPair {
                 )
             })
     }
+
+    /// Verify signature of `msg` made by this key pair
+    #[inline]
+    pub fn verify(&self, msg: impl AsRef<[u8]>, signature: &[u8; 64]) -> anyhow::Result<bool> {
+        verify(&self.public_key, msg, signature)
+    }
+}
+
+impl Debug for KeyPair {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeyPair")
+            .field("secret_key", &"<redacted>")
+            .field("public_key", &self.public_key)
+            .finish()
+    }
+}
+
+impl Drop for KeyPair {
+    #[inline]
+    fn drop(&mut self) {
+        self.secret_key.zeroize();
+    }
+}
+
+/// Holder of a public key that [`Wallet`](super::Wallet) belongs to.
+///
+/// Plain `[u8; 32]` public key can be used for a wallet that only builds
+/// messages and receives signatures from outside, see
+/// [`Wallet::wrap_signed()`](super::Wallet::wrap_signed).
+pub trait HasPublicKey {
+    fn public_key(&self) -> [u8; PUBLIC_KEY_LENGTH];
+}
+
+impl HasPublicKey for [u8; PUBLIC_KEY_LENGTH] {
+    #[inline]
+    fn public_key(&self) -> [u8; PUBLIC_KEY_LENGTH] {
+        *self
+    }
+}
+
+impl HasPublicKey for KeyPair {
+    #[inline]
+    fn public_key(&self) -> [u8; PUBLIC_KEY_LENGTH] {
+        self.public_key
+    }
+}
+
+/// Source of ed25519 signatures for [`Wallet`](super::Wallet), so that
+/// secret keys can be kept outside of the process, e.g. in HSM or KMS
+pub trait Signer: HasPublicKey {
+    /// Sign hash of a cell
+    fn sign_hash(&self, hash: &[u8; 32]) -> anyhow::Result<[u8; 64]>;
+}
+
+impl Signer for KeyPair {
+    #[inline]
+    fn sign_hash(&self, hash: &[u8; 32]) -> anyhow::Result<[u8; 64]> {
+        self.sign(hash)
+    }
+}
+
+/// Async version of [`Signer`].
+///
+/// It is implemented for every [`Signer`], so only remote signers need to
+/// implement it directly.
+pub trait AsyncSigner: HasPublicKey {
+    /// Sign hash of a cell
+    fn sign_hash_async(
+        &self,
+        hash: &[u8; 32],
+    ) -> impl Future<Output = anyhow::Result<[u8; 64]>> + Send;
+}
+
+impl<S> AsyncSigner for S
+where
+    S: Signer,
+{
+    #[inline]
+    fn sign_hash_async(
+        &self,
+        hash: &[u8; 32],
+    ) -> impl Future<Output = anyhow::Result<[u8; 64]>> + Send {
+        future::ready(self.sign_hash(hash))
+    }
+}
+
+/// Verify ed25519 `signature` of `msg` against `public_key`
+#[inline]
+pub fn verify(
+    public_key: &[u8; PUBLIC_KEY_LENGTH],
+    msg: impl AsRef<[u8]>,
+    signature: &[u8; 64],
+) -> anyhow::Result<bool> {
+    nacl::sign::verify(signature, msg.as_ref(), public_key).map_err(|e| anyhow!("{}", e.message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keypair() -> KeyPair {
+        KeyPair::from_seed([7; 32])
+    }
+
+    #[test]
+    fn from_secret() {
+        let keypair = keypair();
+        assert_eq!(KeyPair::from_secret(keypair.secret_key).unwrap(), keypair);
+
+        let mut secret_key = keypair.secret_key;
+        secret_key[63] ^= 1;
+        assert!(KeyPair::from_secret(secret_key).is_err());
+    }
+
+    #[test]
+    fn sign_verify() {
+        let keypair = keypair();
+        let signature = keypair.sign(b"message").unwrap();
+
+        assert!(keypair.verify(b"message", &signature).unwrap());
+        assert!(!keypair.verify(b"massage", &signature).unwrap());
+        assert!(!verify(
+            &KeyPair::from_seed([8; 32]).public_key,
+            b"message",
+            &signature
+        )
+        .unwrap());
+    }
+
+    #[test]
+    fn debug_redacts_secret_key() {
+        let keypair = keypair();
+        let debug = format!("{keypair:?}");
+
+        assert!(debug.contains("<redacted>"));
+        assert!(!debug.contains(&format!("{:?}", &keypair.secret_key[..32])));
+    }
 }
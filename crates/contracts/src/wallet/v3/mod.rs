@@ -0,0 +1,310 @@
+use core::iter;
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use lazy_static::lazy_static;
+use nacl::sign::PUBLIC_KEY_LENGTH;
+use tlb::{
+    bits::{de::BitReaderExt, ser::BitWriterExt},
+    de::{CellDeserialize, CellParser, CellParserError},
+    ser::{CellBuilder, CellBuilderError, CellSerialize, CellSerializeExt},
+    Cell,
+};
+use tlb_ton::{action::SendMsgAction, boc::BagOfCells, UnixTimestamp};
+
+use super::{verify, WalletVersion};
+
+lazy_static! {
+    static ref WALLET_V3R1_CODE_CELL: Arc<Cell> = {
+        BagOfCells::parse_base64(include_str!("./wallet_v3r1.code"))
+            .unwrap()
+            .single_root()
+            .expect("code BoC must be single root")
+            .clone()
+    };
+    static ref WALLET_V3R2_CODE_CELL: Arc<Cell> = {
+        BagOfCells::parse_base64(include_str!("./wallet_v3r2.code"))
+            .unwrap()
+            .single_root()
+            .expect("code BoC must be single root")
+            .clone()
+    };
+}
+
+/// Wallet [v3r1](https://github.com/ton-blockchain/ton/blob/master/crypto/smartcont/wallet3-code.fc).
+pub struct V3R1;
+
+impl WalletVersion for V3R1 {
+    type Data = WalletV3Data;
+    type SignBody = WalletV3SignBody;
+    type ExternalMsgBody = WalletV3ExternalBody;
+
+    fn code() -> Arc<Cell> {
+        WALLET_V3R1_CODE_CELL.clone()
+    }
+
+    fn init_data(wallet_id: u32, pubkey: [u8; PUBLIC_KEY_LENGTH]) -> Self::Data {
+        WalletV3Data::new(wallet_id, pubkey)
+    }
+
+    fn create_sign_body(
+        wallet_id: u32,
+        expire_at: DateTime<Utc>,
+        seqno: u32,
+        msgs: impl IntoIterator<Item = SendMsgAction>,
+    ) -> Result<Self::SignBody, CellBuilderError> {
+        Ok(WalletV3SignBody::new(wallet_id, expire_at, seqno, msgs))
+    }
+
+    fn wrap_signed_external(body: Self::SignBody, signature: [u8; 64]) -> Self::ExternalMsgBody {
+        WalletV3ExternalBody { signature, body }
+    }
+}
+
+/// Wallet [v3r2](https://github.com/ton-blockchain/ton/blob/master/crypto/smartcont/wallet3-code.fc).
+///
+/// Same as [`V3R1`], but additionally exposes `seqno` and `get_public_key`
+/// get-methods.
+pub struct V3R2;
+
+impl WalletVersion for V3R2 {
+    type Data = WalletV3Data;
+    type SignBody = WalletV3SignBody;
+    type ExternalMsgBody = WalletV3ExternalBody;
+
+    fn code() -> Arc<Cell> {
+        WALLET_V3R2_CODE_CELL.clone()
+    }
+
+    fn init_data(wallet_id: u32, pubkey: [u8; PUBLIC_KEY_LENGTH]) -> Self::Data {
+        WalletV3Data::new(wallet_id, pubkey)
+    }
+
+    fn create_sign_body(
+        wallet_id: u32,
+        expire_at: DateTime<Utc>,
+        seqno: u32,
+        msgs: impl IntoIterator<Item = SendMsgAction>,
+    ) -> Result<Self::SignBody, CellBuilderError> {
+        Ok(WalletV3SignBody::new(wallet_id, expire_at, seqno, msgs))
+    }
+
+    fn wrap_signed_external(body: Self::SignBody, signature: [u8; 64]) -> Self::ExternalMsgBody {
+        WalletV3ExternalBody { signature, body }
+    }
+}
+
+/// ```tlb
+/// wallet_v3_data$_ seqno:uint32 wallet_id:uint32 pubkey:bits256 = WalletV3Data;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WalletV3Data {
+    pub seqno: u32,
+    pub wallet_id: u32,
+    pub pubkey: [u8; PUBLIC_KEY_LENGTH],
+}
+
+impl WalletV3Data {
+    #[inline]
+    const fn new(wallet_id: u32, pubkey: [u8; PUBLIC_KEY_LENGTH]) -> Self {
+        Self {
+            seqno: 0,
+            wallet_id,
+            pubkey,
+        }
+    }
+}
+
+impl CellSerialize for WalletV3Data {
+    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        builder
+            .pack(self.seqno)?
+            .pack(self.wallet_id)?
+            .pack(self.pubkey)?;
+        Ok(())
+    }
+}
+
+impl<'de> CellDeserialize<'de> for WalletV3Data {
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        Ok(Self {
+            seqno: parser.unpack()?,
+            wallet_id: parser.unpack()?,
+            pubkey: parser.unpack()?,
+        })
+    }
+}
+
+/// ```tlb
+/// wallet_v3_sign_body$_ wallet_id:uint32 valid_until:uint32 seqno:uint32
+///   msgs:(SendMsgAction ... 4) = WalletV3SignBody;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WalletV3SignBody {
+    pub wallet_id: u32,
+    pub expire_at: DateTime<Utc>,
+    pub seqno: u32,
+    pub msgs: Vec<SendMsgAction>,
+}
+
+impl WalletV3SignBody {
+    #[inline]
+    fn new(
+        wallet_id: u32,
+        expire_at: DateTime<Utc>,
+        seqno: u32,
+        msgs: impl IntoIterator<Item = SendMsgAction>,
+    ) -> Self {
+        Self {
+            wallet_id,
+            expire_at,
+            seqno,
+            msgs: msgs.into_iter().collect(),
+        }
+    }
+}
+
+impl CellSerialize for WalletV3SignBody {
+    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        builder
+            .pack(self.wallet_id)?
+            .pack_as::<_, UnixTimestamp>(self.expire_at)?
+            .pack(self.seqno)?
+            .store_many(&self.msgs)?;
+        Ok(())
+    }
+}
+
+impl<'de> CellDeserialize<'de> for WalletV3SignBody {
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        Ok(Self {
+            wallet_id: parser.unpack()?,
+            expire_at: parser.unpack_as::<_, UnixTimestamp>()?,
+            seqno: parser.unpack()?,
+            msgs: iter::from_fn(|| {
+                if parser.no_references_left() {
+                    return None;
+                }
+                Some(parser.parse())
+            })
+            .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+/// ```tlb
+/// wallet_v3_external$_ signature:bits512 body:WalletV3SignBody = WalletV3ExternalBody;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WalletV3ExternalBody {
+    pub signature: [u8; 64],
+    pub body: WalletV3SignBody,
+}
+
+impl WalletV3ExternalBody {
+    /// Verify [`signature`](Self::signature) against given public key.
+    /// The contract checks it over the rest of the body following the
+    /// signature, i.e. the inline [`WalletV3SignBody`] hashed as a cell
+    /// on its own.
+    #[inline]
+    pub fn verify(&self, public_key: &[u8; PUBLIC_KEY_LENGTH]) -> anyhow::Result<bool> {
+        verify(public_key, self.body.to_cell()?.hash(), &self.signature)
+    }
+}
+
+impl CellSerialize for WalletV3ExternalBody {
+    #[inline]
+    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        builder.pack(self.signature)?.store(&self.body)?;
+        Ok(())
+    }
+}
+
+impl<'de> CellDeserialize<'de> for WalletV3ExternalBody {
+    #[inline]
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        Ok(Self {
+            signature: parser.unpack()?,
+            body: parser.parse()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hex_literal::hex;
+    use tlb_ton::{state_init::StateInit, MsgAddress};
+
+    use crate::wallet::{
+        tests::{keypair, msgs},
+        KeyPair, Wallet, DEFAULT_WALLET_ID,
+    };
+
+    use super::*;
+
+    #[test]
+    fn check_code() {
+        assert_eq!(
+            WALLET_V3R1_CODE_CELL.hash(),
+            hex!("b61041a58a7980b946e8fb9e198e3c904d24799ffa36574ea4251c41a566f581"),
+        );
+        assert_eq!(
+            WALLET_V3R2_CODE_CELL.hash(),
+            hex!("84dafa449f98a6987789ba232358072bc0f76dc4524002a5d0918b9a75d2d599"),
+        );
+    }
+
+    #[test]
+    fn external_body_serde() {
+        let wallet = Wallet::<V3R2>::derive_default(KeyPair::from_seed([3; 32])).unwrap();
+        let msg = wallet
+            .create_external_message(
+                DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+                5,
+                msgs(1),
+                false,
+            )
+            .unwrap();
+        assert!(msg.body.verify(&wallet.public_key()).unwrap());
+
+        let got: WalletV3ExternalBody = msg.body.to_cell().unwrap().parse_fully().unwrap();
+        assert_eq!(got, msg.body);
+    }
+
+    #[test]
+    fn derive_default_address() {
+        let keypair = keypair();
+        let address = |code: &Arc<Cell>| {
+            // seqno:uint32 wallet_id:uint32 public_key:bits256
+            let mut data = Cell::builder();
+            data.pack(0u32)
+                .unwrap()
+                .pack(DEFAULT_WALLET_ID)
+                .unwrap()
+                .pack(keypair.public_key)
+                .unwrap();
+            MsgAddress::derive(
+                0,
+                StateInit::<_, _> {
+                    code: Some(code.clone()),
+                    data: Some(data.into_cell()),
+                    ..Default::default()
+                },
+            )
+            .unwrap()
+        };
+
+        assert_eq!(
+            Wallet::<V3R1>::derive_default(keypair.clone())
+                .unwrap()
+                .address(),
+            address(&WALLET_V3R1_CODE_CELL),
+        );
+        assert_eq!(
+            Wallet::<V3R2>::derive_default(keypair.clone())
+                .unwrap()
+                .address(),
+            address(&WALLET_V3R2_CODE_CELL),
+        );
+    }
+}
@@ -0,0 +1,99 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use lazy_static::lazy_static;
+use nacl::sign::PUBLIC_KEY_LENGTH;
+use tlb::{ser::CellBuilderError, Cell};
+use tlb_ton::{action::SendMsgAction, boc::BagOfCells};
+
+use super::{
+    v4r2::{WalletV4R2Data, WalletV4R2ExternalBody, WalletV4R2SignBody, V4R2},
+    WalletVersion,
+};
+
+lazy_static! {
+    static ref WALLET_V4R1_CODE_CELL: Arc<Cell> = {
+        BagOfCells::parse_base64(include_str!("./wallet_v4r1.code"))
+            .unwrap()
+            .single_root()
+            .expect("code BoC must be single root")
+            .clone()
+    };
+}
+
+/// Wallet [v4r1](https://github.com/ton-blockchain/wallet-contract/blob/main/README.md).
+///
+/// Data and message layouts are the same as for [`V4R2`], only the code
+/// differs.
+pub struct V4R1;
+
+impl WalletVersion for V4R1 {
+    type Data = WalletV4R2Data;
+    type SignBody = WalletV4R2SignBody;
+    type ExternalMsgBody = WalletV4R2ExternalBody;
+
+    fn code() -> Arc<Cell> {
+        WALLET_V4R1_CODE_CELL.clone()
+    }
+
+    fn init_data(wallet_id: u32, pubkey: [u8; PUBLIC_KEY_LENGTH]) -> Self::Data {
+        V4R2::init_data(wallet_id, pubkey)
+    }
+
+    fn create_sign_body(
+        wallet_id: u32,
+        expire_at: DateTime<Utc>,
+        seqno: u32,
+        msgs: impl IntoIterator<Item = SendMsgAction>,
+    ) -> Result<Self::SignBody, CellBuilderError> {
+        V4R2::create_sign_body(wallet_id, expire_at, seqno, msgs)
+    }
+
+    fn wrap_signed_external(body: Self::SignBody, signature: [u8; 64]) -> Self::ExternalMsgBody {
+        V4R2::wrap_signed_external(body, signature)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hex_literal::hex;
+    use tlb::bits::ser::BitWriterExt;
+    use tlb_ton::{state_init::StateInit, MsgAddress};
+
+    use crate::wallet::{tests::keypair, Wallet, DEFAULT_WALLET_ID};
+
+    use super::*;
+
+    #[test]
+    fn check_code() {
+        assert_eq!(
+            WALLET_V4R1_CODE_CELL.hash(),
+            hex!("64dd54805522c5be8a9db59cea0105ccf0d08786ca79beb8cb79e880a8d7322d"),
+        );
+    }
+
+    #[test]
+    fn derive_default_address() {
+        let keypair = keypair();
+
+        // seqno:uint32 wallet_id:uint32 public_key:bits256
+        // plugins:(HashmapE 256 int1)
+        let mut data = Cell::builder();
+        data.pack(0u32)
+            .unwrap()
+            .pack(DEFAULT_WALLET_ID)
+            .unwrap()
+            .pack(keypair.public_key)
+            .unwrap()
+            .pack(false)
+            .unwrap();
+        let state_init = StateInit::<_, _> {
+            code: Some(WALLET_V4R1_CODE_CELL.clone()),
+            data: Some(data.into_cell()),
+            ..Default::default()
+        };
+
+        let wallet = Wallet::<V4R1>::derive_default(keypair).unwrap();
+        assert_eq!(wallet.address(), MsgAddress::derive(0, state_init).unwrap());
+    }
+}
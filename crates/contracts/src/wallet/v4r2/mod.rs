@@ -1,6 +1,11 @@
 use core::iter;
 use std::sync::Arc;
 
+use bitvec::{
+    field::BitField,
+    order::Msb0,
+    view::{AsBits, AsMutBits},
+};
 use chrono::{DateTime, Utc};
 use lazy_static::lazy_static;
 use nacl::sign::PUBLIC_KEY_LENGTH;
@@ -9,7 +14,7 @@ use tlb::{
     bits::{de::BitReaderExt, ser::BitWriterExt},
     de::{CellDeserialize, CellParser, CellParserError},
     r#as::{NoArgs, Ref},
-    ser::{CellBuilder, CellBuilderError, CellSerialize},
+    ser::{CellBuilder, CellBuilderError, CellSerialize, CellSerializeExt},
     Cell, Error,
 };
 use tlb_ton::{
@@ -17,7 +22,7 @@ use tlb_ton::{
     state_init::StateInit, MsgAddress, UnixTimestamp,
 };
 
-use super::WalletVersion;
+use super::{verify, WalletVersion};
 
 lazy_static! {
     static ref WALLET_V4R2_CODE_CELL: Arc<Cell> = {
@@ -55,13 +60,13 @@ impl WalletVersion for V4R2 {
         expire_at: DateTime<Utc>,
         seqno: u32,
         msgs: impl IntoIterator<Item = SendMsgAction>,
-    ) -> Self::SignBody {
-        WalletV4R2SignBody {
+    ) -> Result<Self::SignBody, CellBuilderError> {
+        Ok(WalletV4R2SignBody {
             wallet_id,
             expire_at,
             seqno,
             op: WalletV4R2Op::Send(msgs.into_iter().collect()),
-        }
+        })
     }
 
     fn wrap_signed_external(body: Self::SignBody, signature: [u8; 64]) -> Self::ExternalMsgBody {
@@ -78,6 +83,32 @@ pub struct WalletV4R2Data {
     pub plugins: HashmapE<()>,
 }
 
+impl WalletV4R2Data {
+    /// Returns whether plugin with given address is installed
+    #[inline]
+    pub fn has_plugin(&self, address: MsgAddress) -> bool {
+        let mut key = [0; 1 + 32];
+        key[0] = address.workchain_id as i8 as u8;
+        key[1..].copy_from_slice(&address.address);
+        self.plugins.contains_key(key.as_bits::<Msb0>())
+    }
+
+    /// Addresses of installed plugins
+    pub fn plugins(&self) -> impl Iterator<Item = MsgAddress> + '_ {
+        self.plugins.iter().map(|(key, _)| {
+            let mut address = MsgAddress {
+                workchain_id: key[..8].load_be::<u8>() as i8 as i32,
+                address: [0; 32],
+            };
+            address
+                .address
+                .as_mut_bits::<Msb0>()
+                .copy_from_bitslice(&key[8..]);
+            address
+        })
+    }
+}
+
 impl CellSerialize for WalletV4R2Data {
     fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
         builder
@@ -138,6 +169,7 @@ impl<'de> CellDeserialize<'de> for WalletV4R2SignBody {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[allow(clippy::large_enum_variant)]
 pub enum WalletV4R2Op {
     Send(Vec<SendMsgAction>),
     DeployAndInstall(WalletV4R2OpDeployAndInstallPlugin),
@@ -263,6 +295,16 @@ pub struct WalletV4R2ExternalBody {
     pub body: WalletV4R2SignBody,
 }
 
+impl WalletV4R2ExternalBody {
+    /// Verify [`signature`](Self::signature) against given public key.
+    /// It covers the inline [`WalletV4R2SignBody`] following it, hashed as
+    /// a cell on its own, so [`op`](WalletV4R2SignBody::op) refs are signed too.
+    #[inline]
+    pub fn verify(&self, public_key: &[u8; PUBLIC_KEY_LENGTH]) -> anyhow::Result<bool> {
+        verify(public_key, self.body.to_cell()?.hash(), &self.signature)
+    }
+}
+
 impl CellSerialize for WalletV4R2ExternalBody {
     #[inline]
     fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
@@ -288,6 +330,28 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn plugins() {
+        let plugin = MsgAddress {
+            workchain_id: -1,
+            address: [1; 32],
+        };
+        let mut data = V4R2::init_data(0, [0; PUBLIC_KEY_LENGTH]);
+        assert!(!data.has_plugin(plugin));
+
+        let mut key = vec![0xff];
+        key.extend(plugin.address);
+        data.plugins.insert(key.as_bits::<Msb0>(), ()).unwrap();
+        let data = V4R2::parse_data(&data.to_cell().unwrap()).unwrap();
+
+        assert!(data.has_plugin(plugin));
+        assert!(!data.has_plugin(MsgAddress {
+            workchain_id: 0,
+            ..plugin
+        }));
+        assert_eq!(data.plugins().collect::<Vec<_>>(), [plugin]);
+    }
+
     #[test]
     fn check_code() {
         let packed = pack_with(
@@ -1,12 +1,18 @@
 use std::sync::Arc;
 
+use anyhow::{anyhow, ensure};
+use bitvec::{
+    order::Msb0,
+    view::{AsBits, AsMutBits},
+};
+
 use chrono::{DateTime, Utc};
 use lazy_static::lazy_static;
 use nacl::sign::PUBLIC_KEY_LENGTH;
 use tlb::{
     bits::{de::BitReaderExt, ser::BitWriterExt},
     de::{CellDeserialize, CellParser, CellParserError},
-    r#as::{Data, NoArgs},
+    r#as::{Data, NoArgs, Ref},
     ser::{CellBuilder, CellBuilderError, CellSerialize},
     Cell, Error, ResultExt,
 };
@@ -18,7 +24,7 @@ use tlb_ton::{
     MsgAddress, UnixTimestamp,
 };
 
-use super::WalletVersion;
+use super::{verify, HasPublicKey, Signer, Wallet, WalletVersion};
 
 lazy_static! {
     static ref WALLET_V5R1_CODE_CELL: Arc<Cell> = {
@@ -31,6 +37,11 @@ lazy_static! {
 }
 
 /// Wallet [v5r1](https://github.com/ton-blockchain/wallet-contract-v5/blob/main/Specification.md).
+///
+/// Besides external messages, requests can be signed for
+/// [gasless](Wallet::create_internal_signed_body) delivery by internal
+/// message from any other contract, or sent by installed
+/// [extensions](InternalExtensionWalletV5R1MsgBody).
 pub struct V5R1;
 
 impl WalletVersion for V5R1 {
@@ -38,6 +49,11 @@ impl WalletVersion for V5R1 {
     type SignBody = WalletV5RSignBody;
     type ExternalMsgBody = WalletV5R1MsgBody;
 
+    /// `0x7FFFFF11`, i.e. subwallet 0 in mainnet basechain, as used by
+    /// wallet apps
+    const DEFAULT_WALLET_ID: u32 =
+        WalletV5R1Id::client(WalletV5R1Id::MAINNET_GLOBAL_ID, 0, 0).wallet_id();
+
     #[inline]
     fn code() -> Arc<Cell> {
         WALLET_V5R1_CODE_CELL.clone()
@@ -60,8 +76,8 @@ impl WalletVersion for V5R1 {
         valid_until: DateTime<Utc>,
         msg_seqno: u32,
         msgs: impl IntoIterator<Item = SendMsgAction>,
-    ) -> Self::SignBody {
-        WalletV5RSignBody {
+    ) -> Result<Self::SignBody, CellBuilderError> {
+        Ok(WalletV5RSignBody {
             wallet_id,
             valid_until,
             msg_seqno,
@@ -69,7 +85,12 @@ impl WalletVersion for V5R1 {
                 out_actions: msgs.into_iter().map(OutAction::SendMsg).collect(),
                 extended: [].into(),
             },
-        }
+        })
+    }
+
+    #[inline]
+    fn sign_body_hash(body: &Self::SignBody) -> Result<[u8; 32], CellBuilderError> {
+        body.external_signed_hash()
     }
 
     #[inline]
@@ -78,15 +99,217 @@ impl WalletVersion for V5R1 {
     }
 }
 
+impl<S> Wallet<V5R1, S>
+where
+    S: HasPublicKey,
+{
+    /// Create body with both out actions and [`ExtendedAction`]s for
+    /// further signing, e.g. to add or delete extensions
+    #[inline]
+    pub fn create_extended_sign_body(
+        &self,
+        valid_until: DateTime<Utc>,
+        msg_seqno: u32,
+        out_actions: impl IntoIterator<Item = OutAction>,
+        extended: impl IntoIterator<Item = ExtendedAction>,
+    ) -> WalletV5RSignBody {
+        WalletV5RSignBody {
+            wallet_id: self.wallet_id,
+            valid_until,
+            msg_seqno,
+            inner: WalletV5R1InnerRequest {
+                out_actions: out_actions.into_iter().collect(),
+                extended: extended.into_iter().collect(),
+            },
+        }
+    }
+
+    /// Wrap body and its signature obtained elsewhere into
+    /// [`WalletV5R1MsgBody::InternalSigned`]. The signature is verified
+    /// against [internal hash](WalletV5RSignBody::internal_signed_hash).
+    pub fn wrap_signed_internal(
+        &self,
+        body: WalletV5RSignBody,
+        signature: [u8; 64],
+    ) -> anyhow::Result<WalletV5R1MsgBody> {
+        ensure!(
+            verify(&self.public_key(), body.internal_signed_hash()?, &signature)?,
+            "invalid signature"
+        );
+        Ok(WalletV5R1MsgBody::InternalSigned(WalletV5R1SignedRequest {
+            body,
+            signature,
+        }))
+    }
+}
+
+impl<S> Wallet<V5R1, S>
+where
+    S: Signer,
+{
+    /// Create and sign body of internal message to this wallet, so that
+    /// the request can be delivered by any other contract which pays for
+    /// gas instead of the wallet itself.
+    #[inline]
+    pub fn create_internal_signed_body(
+        &self,
+        valid_until: DateTime<Utc>,
+        msg_seqno: u32,
+        msgs: impl IntoIterator<Item = SendMsgAction>,
+    ) -> anyhow::Result<WalletV5R1MsgBody> {
+        let body = self.create_sign_body(valid_until, msg_seqno, msgs)?;
+        let signature = self.sign_body_internal(&body)?;
+        Ok(WalletV5R1MsgBody::InternalSigned(WalletV5R1SignedRequest {
+            body,
+            signature,
+        }))
+    }
+
+    /// Sign body for [`WalletV5R1MsgBody::InternalSigned`]
+    #[inline]
+    pub fn sign_body_internal(&self, body: &WalletV5RSignBody) -> anyhow::Result<[u8; 64]> {
+        self.signer.sign_hash(&body.internal_signed_hash()?)
+    }
+}
+
+/// Wallet ID of v5r1, which depends on the network and workchain
+/// ```tlb
+/// wallet_id$_ global_id:int32 ^ context:WalletIdContext = WalletId;
+///
+/// client_context$1 workchain:int8 version:uint8 subwallet:uint15 = WalletIdContext;
+/// custom_context$0 context:uint31 = WalletIdContext;
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WalletV5R1Id {
+    pub global_id: i32,
+    pub context: WalletV5R1IdContext,
+}
+
+impl WalletV5R1Id {
+    pub const MAINNET_GLOBAL_ID: i32 = -239;
+    pub const TESTNET_GLOBAL_ID: i32 = -3;
+
+    /// Version of v5r1 in [`WalletV5R1IdContext::Client`]
+    pub const VERSION: u8 = 0;
+
+    /// Client context for given network, workchain and subwallet number
+    #[inline]
+    pub const fn client(global_id: i32, workchain_id: i8, subwallet: u16) -> Self {
+        Self {
+            global_id,
+            context: WalletV5R1IdContext::Client {
+                workchain_id,
+                version: Self::VERSION,
+                subwallet,
+            },
+        }
+    }
+
+    /// Encoded wallet ID. Bits not fitting into context fields are
+    /// discarded.
+    #[inline]
+    pub const fn wallet_id(self) -> u32 {
+        let context = match self.context {
+            WalletV5R1IdContext::Client {
+                workchain_id,
+                version,
+                subwallet,
+            } => {
+                1 << 31
+                    | (workchain_id as u8 as u32) << 23
+                    | (version as u32) << 15
+                    | (subwallet as u32 & 0x7fff)
+            }
+            WalletV5R1IdContext::Custom(context) => context & 0x7fff_ffff,
+        };
+        self.global_id as u32 ^ context
+    }
+
+    /// Decode wallet ID for network with given global ID
+    #[inline]
+    pub const fn from_wallet_id(global_id: i32, wallet_id: u32) -> Self {
+        let context = global_id as u32 ^ wallet_id;
+        Self {
+            global_id,
+            context: if context >> 31 == 1 {
+                WalletV5R1IdContext::Client {
+                    workchain_id: (context >> 23) as u8 as i8,
+                    version: (context >> 15) as u8,
+                    subwallet: (context & 0x7fff) as u16,
+                }
+            } else {
+                WalletV5R1IdContext::Custom(context)
+            },
+        }
+    }
+}
+
+impl From<WalletV5R1Id> for u32 {
+    #[inline]
+    fn from(id: WalletV5R1Id) -> Self {
+        id.wallet_id()
+    }
+}
+
+/// Context of [`WalletV5R1Id`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WalletV5R1IdContext {
+    /// ```tlb
+    /// client_context$1 workchain:int8 version:uint8 subwallet:uint15 = WalletIdContext;
+    /// ```
+    Client {
+        workchain_id: i8,
+        version: u8,
+        /// 15 bits
+        subwallet: u16,
+    },
+
+    /// ```tlb
+    /// custom_context$0 context:uint31 = WalletIdContext;
+    /// ```
+    Custom(u32),
+}
+
+/// ```tlb
+/// contract_state$_ is_signature_allowed:(## 1) seqno:# wallet_id:(## 32)
+///   public_key:(## 256) extensions_dict:(HashmapE 256 int1) = ContractState;
+/// ```
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct WalletV5R1Data {
     pub is_signature_allowed: bool,
     pub seqno: u32,
     pub wallet_id: u32,
     pub pubkey: [u8; PUBLIC_KEY_LENGTH],
+    /// extension address hash -> true
     pub extensions: HashmapE<bool>,
 }
 
+impl WalletV5R1Data {
+    /// Returns whether extension with given address is installed.
+    /// Extensions reside in the same workchain as the wallet, so only
+    /// address hash is compared.
+    #[inline]
+    pub fn has_extension(&self, address: MsgAddress) -> bool {
+        self.extensions
+            .contains_key(address.address.as_bits::<Msb0>())
+    }
+
+    /// Addresses of installed extensions in wallet's workchain
+    pub fn extensions(&self, workchain_id: i32) -> impl Iterator<Item = MsgAddress> + '_ {
+        self.extensions.iter().map(move |(key, _)| {
+            let mut address = MsgAddress {
+                workchain_id,
+                address: [0; 32],
+            };
+            address
+                .address
+                .as_mut_bits::<Msb0>()
+                .copy_from_bitslice(&key);
+            address
+        })
+    }
+}
+
 impl CellSerialize for WalletV5R1Data {
     #[inline]
     fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
@@ -112,7 +335,7 @@ impl<'de> CellDeserialize<'de> for WalletV5R1Data {
             wallet_id: parser.unpack()?,
             pubkey: parser.unpack()?,
             extensions: parser.parse_as_with::<_, HashmapE<Data<NoArgs<_>>, NoArgs<_>>>((
-                258,
+                256,
                 (),
                 (),
             ))?,
@@ -121,8 +344,13 @@ impl<'de> CellDeserialize<'de> for WalletV5R1Data {
 }
 
 /// ```tlb
-/// actions$_ out_actions:(Maybe OutList) has_other_actions:(## 1) {m:#} {n:#} other_actions:(ActionList n m) = InnerRequest;
+/// actions$_ out_actions:(Maybe ^OutList) has_other_actions:(## 1) {m:#} {n:#} other_actions:(ActionList n m) = InnerRequest;
+///
+/// action_list_basic$_ {n:#} actions:^(OutList n) = ActionList n 0;
+/// action_list_extended$_ {m:#} {n:#} action:ExtendedAction prev:^(ActionList n m) = ActionList n (m+1);
 /// ```
+/// Extended actions are executed in order: the first one is stored inline,
+/// while the rest follow in a chain of references.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct WalletV5R1InnerRequest {
     pub out_actions: Vec<OutAction>,
@@ -132,29 +360,63 @@ pub struct WalletV5R1InnerRequest {
 impl CellSerialize for WalletV5R1InnerRequest {
     fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
         builder
-            .store_as::<_, Option<&List>>(
+            .store_as::<_, Option<Ref<&List>>>(
                 Some(&self.out_actions).filter(|actions| !actions.is_empty()),
             )?
-            .store_as::<_, Option<&List>>(Some(&self.extended).filter(|other| !other.is_empty()))?;
+            .pack(!self.extended.is_empty())?;
+        if !self.extended.is_empty() {
+            builder.store(ExtendedActionList(&self.extended))?;
+        }
         Ok(())
     }
 }
 
 impl<'de> CellDeserialize<'de> for WalletV5R1InnerRequest {
     fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        let out_actions = parser
+            .parse_as::<_, Option<Ref<List>>>()
+            .context("out_actions")?
+            .unwrap_or_default();
+        let mut extended = Vec::new();
+        if parser.unpack()? {
+            extended.push(parser.parse().context("extended[0]")?);
+            if !parser.no_references_left() {
+                let mut p: CellParser<'de> = parser.parse_as::<_, Ref>()?;
+                loop {
+                    extended.push(
+                        p.parse()
+                            .with_context(|| format!("extended[{}]", extended.len()))?,
+                    );
+                    if p.no_references_left() {
+                        break;
+                    }
+                    p = p.parse_as::<_, Ref>()?;
+                }
+            }
+        }
         Ok(Self {
-            out_actions: parser
-                .parse_as::<_, Option<List>>()
-                .context("out_actions")?
-                .unwrap_or_default(),
-            extended: parser
-                .parse_as::<_, Option<List>>()
-                .context("extended")?
-                .unwrap_or_default(),
+            out_actions,
+            extended,
         })
     }
 }
 
+/// Non-empty chain of [`ExtendedAction`]s
+struct ExtendedActionList<'a>(&'a [ExtendedAction]);
+
+impl CellSerialize for ExtendedActionList<'_> {
+    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        let Some((action, rest)) = self.0.split_first() else {
+            return Err(Error::custom("empty extended actions"));
+        };
+        builder.store(action)?;
+        if !rest.is_empty() {
+            builder.store_as::<_, Ref>(ExtendedActionList(rest))?;
+        }
+        Ok(())
+    }
+}
+
 /// ```tlb
 /// action_add_ext#02 addr:MsgAddressInt = ExtendedAction;
 /// action_delete_ext#03 addr:MsgAddressInt = ExtendedAction;
@@ -172,6 +434,9 @@ pub enum ExtendedAction {
     /// ```
     DeleteExtension(MsgAddress),
 
+    /// Can only be sent by an extension. Signature auth can not be
+    /// disallowed while there are no extensions installed.
+    ///
     /// ```tlb
     /// action_set_signature_auth_allowed#04 allowed:(## 1) = ExtendedAction;
     /// ```
@@ -229,6 +494,28 @@ pub struct WalletV5RSignBody {
     pub inner: WalletV5R1InnerRequest,
 }
 
+impl WalletV5RSignBody {
+    /// Hash to be signed for [`WalletV5R1MsgBody::ExternalSigned`]
+    #[inline]
+    pub fn external_signed_hash(&self) -> Result<[u8; 32], CellBuilderError> {
+        self.signed_hash(WalletV5R1MsgBody::EXTERNAL_SIGNED_PREFIX)
+    }
+
+    /// Hash to be signed for [`WalletV5R1MsgBody::InternalSigned`]
+    #[inline]
+    pub fn internal_signed_hash(&self) -> Result<[u8; 32], CellBuilderError> {
+        self.signed_hash(WalletV5R1MsgBody::INTERNAL_SIGNED_PREFIX)
+    }
+
+    /// Signature covers opcode of the message, so that requests signed for
+    /// external messages can not be replayed in internal ones and vice versa
+    fn signed_hash(&self, prefix: u32) -> Result<[u8; 32], CellBuilderError> {
+        let mut builder = Cell::builder();
+        builder.pack(prefix)?.store(self)?;
+        Ok(builder.into_cell().hash())
+    }
+}
+
 impl CellSerialize for WalletV5RSignBody {
     fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
         builder
@@ -266,6 +553,32 @@ pub struct WalletV5R1SignedRequest {
     pub signature: [u8; 64],
 }
 
+impl WalletV5R1SignedRequest {
+    /// Verify [`signature`](Self::signature) of
+    /// [`external_signed`](WalletV5R1MsgBody::ExternalSigned) request
+    /// against given public key. Signature covers the opcode as well, see
+    /// [`WalletV5RSignBody::external_signed_hash()`].
+    #[inline]
+    pub fn verify(&self, public_key: &[u8; PUBLIC_KEY_LENGTH]) -> anyhow::Result<bool> {
+        verify(
+            public_key,
+            self.body.external_signed_hash()?,
+            &self.signature,
+        )
+    }
+
+    /// Same as [`.verify()`](Self::verify), but for
+    /// [`internal_signed`](WalletV5R1MsgBody::InternalSigned) request
+    #[inline]
+    pub fn verify_internal(&self, public_key: &[u8; PUBLIC_KEY_LENGTH]) -> anyhow::Result<bool> {
+        verify(
+            public_key,
+            self.body.internal_signed_hash()?,
+            &self.signature,
+        )
+    }
+}
+
 impl CellSerialize for WalletV5R1SignedRequest {
     fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
         builder.store(&self.body)?.pack(self.signature)?;
@@ -304,6 +617,19 @@ impl WalletV5R1MsgBody {
     const INTERNAL_SIGNED_PREFIX: u32 = 0x73696e74;
     const INTERNAL_EXTENSION_PREFIX: u32 = 0x6578746e;
     const EXTERNAL_SIGNED_PREFIX: u32 = 0x7369676e;
+
+    /// Verify signature of [`InternalSigned`](Self::InternalSigned) or
+    /// [`ExternalSigned`](Self::ExternalSigned) request against given
+    /// public key. [`InternalExtension`](Self::InternalExtension) is not
+    /// signed, as it is authorized by sender address.
+    #[inline]
+    pub fn verify(&self, public_key: &[u8; PUBLIC_KEY_LENGTH]) -> anyhow::Result<bool> {
+        match self {
+            Self::InternalSigned(request) => request.verify_internal(public_key),
+            Self::ExternalSigned(request) => request.verify(public_key),
+            Self::InternalExtension(_) => Err(anyhow!("internal_extension is not signed")),
+        }
+    }
 }
 
 impl CellSerialize for WalletV5R1MsgBody {
@@ -336,10 +662,40 @@ impl<'de> CellDeserialize<'de> for WalletV5R1MsgBody {
     }
 }
 
+/// Request sent by installed extension. Extension may also manage other
+/// extensions and [disallow](ExtendedAction::SetSignatureAuthAllowed)
+/// signature auth.
+/// ```tlb
+/// internal_extension#6578746e query_id:(## 64) inner:InnerRequest = InternalMsgBody;
+/// ```
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct InternalExtensionWalletV5R1MsgBody {
-    query_id: u64,
-    inner: WalletV5R1InnerRequest,
+    pub query_id: u64,
+    pub inner: WalletV5R1InnerRequest,
+}
+
+impl InternalExtensionWalletV5R1MsgBody {
+    #[inline]
+    pub fn new(
+        query_id: u64,
+        out_actions: impl IntoIterator<Item = OutAction>,
+        extended: impl IntoIterator<Item = ExtendedAction>,
+    ) -> Self {
+        Self {
+            query_id,
+            inner: WalletV5R1InnerRequest {
+                out_actions: out_actions.into_iter().collect(),
+                extended: extended.into_iter().collect(),
+            },
+        }
+    }
+}
+
+impl From<InternalExtensionWalletV5R1MsgBody> for WalletV5R1MsgBody {
+    #[inline]
+    fn from(body: InternalExtensionWalletV5R1MsgBody) -> Self {
+        Self::InternalExtension(body)
+    }
 }
 
 impl CellSerialize for InternalExtensionWalletV5R1MsgBody {
@@ -360,8 +716,21 @@ impl<'de> CellDeserialize<'de> for InternalExtensionWalletV5R1MsgBody {
 
 #[cfg(test)]
 mod tests {
-    use tlb::bits::{de::unpack_fully, ser::pack_with};
-    use tlb_ton::boc::{BagOfCellsArgs, BoC};
+    use hex_literal::hex;
+    use tlb::{
+        bits::{de::unpack_fully, ser::pack_with},
+        ser::CellSerializeExt,
+    };
+    use tlb_ton::{
+        action::OutList,
+        boc::{BagOfCellsArgs, BoC},
+        state_init::StateInit,
+    };
+
+    use crate::wallet::{
+        tests::{keypair, msgs},
+        KeyPair,
+    };
 
     use super::*;
 
@@ -380,5 +749,189 @@ mod tests {
 
         let got: Cell = unpacked.single_root().unwrap().parse_fully().unwrap();
         assert_eq!(&got, WALLET_V5R1_CODE_CELL.as_ref());
+        assert_eq!(
+            got.hash(),
+            hex!("20834b7b72b112147e1b2fb457b84e74d1a30f04f737d4f62a668e9552d2b72f"),
+        );
+    }
+
+    fn wallet() -> Wallet<V5R1> {
+        Wallet::derive_default(KeyPair::from_seed([1; 32])).unwrap()
+    }
+
+    #[test]
+    fn wallet_id() {
+        assert_eq!(V5R1::DEFAULT_WALLET_ID, 0x7FFFFF11);
+        assert_eq!(
+            WalletV5R1Id::client(WalletV5R1Id::TESTNET_GLOBAL_ID, 0, 0).wallet_id(),
+            0x7FFFFFFD,
+        );
+
+        for id in [
+            WalletV5R1Id::client(WalletV5R1Id::MAINNET_GLOBAL_ID, -1, 0),
+            WalletV5R1Id::client(WalletV5R1Id::TESTNET_GLOBAL_ID, 0, 0x7fff),
+            WalletV5R1Id {
+                global_id: WalletV5R1Id::MAINNET_GLOBAL_ID,
+                context: WalletV5R1IdContext::Custom(42),
+            },
+        ] {
+            assert_eq!(
+                WalletV5R1Id::from_wallet_id(id.global_id, id.wallet_id()),
+                id
+            );
+        }
+    }
+
+    #[test]
+    fn derive_default_address() {
+        let keypair = keypair();
+
+        // is_signature_allowed:Bool seqno:uint32 wallet_id:uint32
+        // public_key:bits256 extensions_dict:(HashmapE 256 int1)
+        let mut data = Cell::builder();
+        data.pack(true)
+            .unwrap()
+            .pack(0u32)
+            .unwrap()
+            .pack(0x7FFFFF11u32)
+            .unwrap()
+            .pack(keypair.public_key)
+            .unwrap()
+            .pack(false)
+            .unwrap();
+        let state_init = StateInit::<_, _> {
+            code: Some(WALLET_V5R1_CODE_CELL.clone()),
+            data: Some(data.into_cell()),
+            ..Default::default()
+        };
+
+        let wallet = Wallet::<V5R1>::derive_default(keypair).unwrap();
+        assert_eq!(wallet.address(), MsgAddress::derive(0, state_init).unwrap());
+        // regression guard for derived addresses, computed by this crate
+        // rather than taken from a wallet app
+        assert_eq!(
+            wallet.address(),
+            "EQDfdCNzXtNAVHoQxM08TDoZf3pNhgJqoK9NPF6NBwSL3E9y"
+                .parse()
+                .unwrap(),
+        );
+    }
+
+    #[test]
+    fn verify_signed_request() {
+        let wallet = wallet();
+        let expire_at = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let body = wallet.create_sign_body(expire_at, 1, msgs(1)).unwrap();
+
+        let mut b = Cell::builder();
+        b.pack(0x7369676e_u32).unwrap().store(&body).unwrap();
+        assert_eq!(wallet.sign_body_hash(&body).unwrap(), b.into_cell().hash());
+
+        let mut msg = wallet
+            .create_external_message(expire_at, 1, msgs(1), false)
+            .unwrap();
+        assert!(msg.body.verify(&wallet.public_key()).unwrap());
+
+        let WalletV5R1MsgBody::ExternalSigned(request) = &mut msg.body else {
+            unreachable!();
+        };
+        assert!(request.verify(&wallet.public_key()).unwrap());
+        assert!(!request.verify_internal(&wallet.public_key()).unwrap());
+
+        request.body.msg_seqno += 1;
+        assert!(!request.verify(&wallet.public_key()).unwrap());
+        assert!(!msg.body.verify(&wallet.public_key()).unwrap());
+    }
+
+    #[test]
+    fn internal_signed() {
+        let wallet = wallet();
+        let expire_at = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let body = wallet
+            .create_internal_signed_body(expire_at, 1, msgs(1))
+            .unwrap();
+        assert!(body.verify(&wallet.public_key()).unwrap());
+
+        let cell = body.to_cell().unwrap();
+        let got: WalletV5R1MsgBody = cell.parse_fully().unwrap();
+        assert_eq!(got, body);
+
+        // signed for internal message, so can not be replayed as external
+        let WalletV5R1MsgBody::InternalSigned(request) = body else {
+            unreachable!();
+        };
+        assert!(!WalletV5R1MsgBody::ExternalSigned(request.clone())
+            .verify(&wallet.public_key())
+            .unwrap());
+        assert!(wallet
+            .wrap_signed_internal(request.body.clone(), request.signature)
+            .is_ok());
+        assert!(wallet
+            .wrap_signed(request.body, request.signature, false)
+            .is_err());
+    }
+
+    #[test]
+    fn extended_actions() {
+        let wallet = wallet();
+        let extension = MsgAddress {
+            workchain_id: 0,
+            address: [2; 32],
+        };
+        let extended = [
+            ExtendedAction::AddExtension(extension),
+            ExtendedAction::DeleteExtension(MsgAddress::NULL),
+            ExtendedAction::SetSignatureAuthAllowed(false),
+        ];
+        let body = wallet.create_extended_sign_body(
+            DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+            1,
+            msgs(1).into_iter().map(OutAction::SendMsg),
+            extended.clone(),
+        );
+
+        let cell = body.to_cell().unwrap();
+        // ^OutList goes first, then chain of extended actions in order
+        assert_eq!(cell.references.len(), 2);
+        assert_eq!(
+            cell.references[0].as_ref(),
+            &OutList(body.inner.out_actions.clone()).to_c5().unwrap(),
+        );
+        let mut next = cell.references[1].parser();
+        assert_eq!(next.parse::<ExtendedAction>().unwrap(), extended[1]);
+        assert_eq!(
+            next.parse_as::<Cell, Ref>()
+                .unwrap()
+                .parse_fully::<ExtendedAction>()
+                .unwrap(),
+            extended[2],
+        );
+
+        let got: WalletV5RSignBody = cell.parse_fully().unwrap();
+        assert_eq!(got, body);
+
+        let ext = InternalExtensionWalletV5R1MsgBody::new(7, [], extended);
+        let body = WalletV5R1MsgBody::from(ext);
+        assert!(body.verify(&wallet.public_key()).is_err());
+        let got: WalletV5R1MsgBody = body.to_cell().unwrap().parse_fully().unwrap();
+        assert_eq!(got, body);
+    }
+
+    #[test]
+    fn data_serde() {
+        let extension = MsgAddress {
+            workchain_id: 0,
+            address: [4; 32],
+        };
+        let mut data = V5R1::init_data(V5R1::DEFAULT_WALLET_ID, [3; PUBLIC_KEY_LENGTH]);
+        assert!(!data.has_extension(extension));
+        data.extensions
+            .insert(extension.address.as_bits::<Msb0>(), true)
+            .unwrap();
+
+        let got = V5R1::parse_data(&data.to_cell().unwrap()).unwrap();
+        assert_eq!(got, data);
+        assert!(got.has_extension(extension));
+        assert_eq!(got.extensions(0).collect::<Vec<_>>(), [extension]);
     }
 }
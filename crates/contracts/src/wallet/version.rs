@@ -1,31 +1,53 @@
 use std::sync::Arc;
 
 use chrono::{DateTime, Utc};
-use tlb::{ser::CellSerialize, Cell};
+use tlb::{
+    de::{CellDeserialize, CellParserError},
+    ser::{CellBuilderError, CellSerialize, CellSerializeExt},
+    Cell,
+};
 use tlb_ton::{action::SendMsgAction, state_init::StateInit};
 
-use super::PUBLIC_KEY_LENGTH;
+use super::{DEFAULT_WALLET_ID, PUBLIC_KEY_LENGTH};
 
 /// Version of [`Wallet`]
 pub trait WalletVersion {
-    type Data: CellSerialize;
+    type Data: CellSerialize + for<'de> CellDeserialize<'de>;
     type SignBody: CellSerialize;
     type ExternalMsgBody: CellSerialize;
 
+    /// Wallet ID used by [`Wallet::derive_default()`](super::Wallet::derive_default)
+    const DEFAULT_WALLET_ID: u32 = DEFAULT_WALLET_ID;
+
     /// Code of the wallet for use with [`StateInit`]
     fn code() -> Arc<Cell>;
 
     /// Init data for use with [`StateInit`]
     fn init_data(wallet_id: u32, pubkey: [u8; PUBLIC_KEY_LENGTH]) -> Self::Data;
 
+    /// Parse data of deployed wallet, e.g. to get its current seqno
+    #[inline]
+    fn parse_data(data: &Cell) -> Result<Self::Data, CellParserError<'_>> {
+        data.parse_fully()
+    }
+
     /// Creates body for further signing with
-    /// [`.wrap_signed_external()`](WalletVersion::wrap_signed_external)
+    /// [`.wrap_signed_external()`](WalletVersion::wrap_signed_external).
+    /// Fails if given messages can not be sent by this version in a single
+    /// body.
     fn create_sign_body(
         wallet_id: u32,
         expire_at: DateTime<Utc>,
         seqno: u32,
         msgs: impl IntoIterator<Item = SendMsgAction>,
-    ) -> Self::SignBody;
+    ) -> Result<Self::SignBody, CellBuilderError>;
+
+    /// Hash of the body from
+    /// [`.create_sign_body()`](WalletVersion::create_sign_body) to be signed
+    #[inline]
+    fn sign_body_hash(body: &Self::SignBody) -> Result<[u8; 32], CellBuilderError> {
+        Ok(body.to_cell()?.hash())
+    }
 
     /// Wraps signed body into external [`Message::body`]
     fn wrap_signed_external(body: Self::SignBody, signature: [u8; 64]) -> Self::ExternalMsgBody;
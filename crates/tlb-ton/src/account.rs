@@ -0,0 +1,611 @@
+//! Collection of types related to [Account](https://docs.ton.org/develop/data-formats/tl-b-types#account)
+use chrono::{DateTime, Utc};
+use num_bigint::BigUint;
+use tlb::{
+    bits::{
+        de::{BitReader, BitReaderExt, BitUnpack},
+        r#as::{NBits, VarInt},
+        ser::{BitPack, BitWriter, BitWriterExt},
+    },
+    de::{CellDeserialize, CellParser, CellParserError},
+    r#as::Ref,
+    ser::{CellBuilder, CellBuilderError, CellSerialize},
+    Cell, Error, ResultExt,
+};
+
+use crate::{
+    currency::{CurrencyCollection, Grams},
+    state_init::StateInit,
+    MsgAddress, UnixTimestamp,
+};
+
+/// Account descriptor stored in shard state
+/// ```tlb
+/// account_descr$_ account:^Account last_trans_hash:bits256
+/// last_trans_lt:uint64 = ShardAccount;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShardAccount {
+    /// `None` stands for `account_none`
+    pub account: Option<Account>,
+    pub last_trans_hash: [u8; 32],
+    pub last_trans_lt: u64,
+}
+
+impl CellSerialize for ShardAccount {
+    #[inline]
+    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        builder
+            // account:^Account
+            .store_as::<_, Ref>(&self.account)
+            .context("account")?
+            // last_trans_hash:bits256
+            .pack(self.last_trans_hash)?
+            // last_trans_lt:uint64
+            .pack(self.last_trans_lt)?;
+        Ok(())
+    }
+}
+
+impl<'de> CellDeserialize<'de> for ShardAccount {
+    #[inline]
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        Ok(Self {
+            // account:^Account
+            account: parser.parse_as::<_, Ref>().context("account")?,
+            // last_trans_hash:bits256
+            last_trans_hash: parser.unpack()?,
+            // last_trans_lt:uint64
+            last_trans_lt: parser.unpack()?,
+        })
+    }
+}
+
+/// [Account](https://docs.ton.org/develop/data-formats/tl-b-types#account)
+///
+/// `account_none$0` shares its layout with `Maybe`, so full `Account` type
+/// is represented as `Option<Account>`, while this struct stands only for
+/// its `account$1` constructor, without the leading tag bit.
+/// ```tlb
+/// account_none$0 = Account;
+/// account$1 addr:MsgAddressInt storage_stat:StorageInfo
+/// storage:AccountStorage = Account;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Account {
+    pub addr: MsgAddress,
+    pub storage_stat: StorageInfo,
+    pub storage: AccountStorage,
+}
+
+impl Account {
+    /// Returns balance of the account
+    #[inline]
+    pub fn balance(&self) -> &CurrencyCollection {
+        &self.storage.balance
+    }
+
+    /// Returns [`StateInit`] of an active account
+    #[inline]
+    pub fn state_init(&self) -> Option<&StateInit> {
+        self.storage.state.state_init()
+    }
+
+    /// Returns [`AccountStatus`] corresponding to the state
+    #[inline]
+    pub fn status(&self) -> AccountStatus {
+        self.storage.state.status()
+    }
+}
+
+impl CellSerialize for Account {
+    #[inline]
+    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        builder
+            // addr:MsgAddressInt
+            .pack(self.addr)
+            .context("addr")?
+            // storage_stat:StorageInfo
+            .pack(&self.storage_stat)
+            .context("storage_stat")?
+            // storage:AccountStorage
+            .store(&self.storage)
+            .context("storage")?;
+        Ok(())
+    }
+}
+
+impl<'de> CellDeserialize<'de> for Account {
+    #[inline]
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        Ok(Self {
+            // addr:MsgAddressInt
+            addr: parser.unpack().context("addr")?,
+            // storage_stat:StorageInfo
+            storage_stat: parser.unpack().context("storage_stat")?,
+            // storage:AccountStorage
+            storage: parser.parse().context("storage")?,
+        })
+    }
+}
+
+/// ```tlb
+/// account_storage$_ last_trans_lt:uint64
+/// balance:CurrencyCollection state:AccountState
+/// = AccountStorage;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountStorage {
+    pub last_trans_lt: u64,
+    pub balance: CurrencyCollection,
+    pub state: AccountState,
+}
+
+impl CellSerialize for AccountStorage {
+    #[inline]
+    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        builder
+            // last_trans_lt:uint64
+            .pack(self.last_trans_lt)?
+            // balance:CurrencyCollection
+            .store(&self.balance)
+            .context("balance")?
+            // state:AccountState
+            .store(&self.state)
+            .context("state")?;
+        Ok(())
+    }
+}
+
+impl<'de> CellDeserialize<'de> for AccountStorage {
+    #[inline]
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        Ok(Self {
+            // last_trans_lt:uint64
+            last_trans_lt: parser.unpack()?,
+            // balance:CurrencyCollection
+            balance: parser.parse().context("balance")?,
+            // state:AccountState
+            state: parser.parse().context("state")?,
+        })
+    }
+}
+
+/// ```tlb
+/// account_uninit$00 = AccountState;
+/// account_active$1 _:StateInit = AccountState;
+/// account_frozen$01 state_hash:bits256 = AccountState;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AccountState {
+    /// ```tlb
+    /// account_uninit$00 = AccountState;
+    /// ```
+    Uninit,
+    /// ```tlb
+    /// account_active$1 _:StateInit = AccountState;
+    /// ```
+    Active(StateInit),
+    /// ```tlb
+    /// account_frozen$01 state_hash:bits256 = AccountState;
+    /// ```
+    Frozen { state_hash: [u8; 32] },
+}
+
+impl AccountState {
+    /// Returns [`StateInit`] if the account is active
+    #[inline]
+    pub fn state_init(&self) -> Option<&StateInit> {
+        match self {
+            Self::Active(state_init) => Some(state_init),
+            _ => None,
+        }
+    }
+
+    /// Returns code of an active account
+    #[inline]
+    pub fn code(&self) -> Option<&Cell> {
+        self.state_init()?.code.as_ref()
+    }
+
+    /// Returns data of an active account
+    #[inline]
+    pub fn data(&self) -> Option<&Cell> {
+        self.state_init()?.data.as_ref()
+    }
+
+    /// Returns [`AccountStatus`] corresponding to the state
+    #[inline]
+    pub fn status(&self) -> AccountStatus {
+        match self {
+            Self::Uninit => AccountStatus::Uninit,
+            Self::Active(_) => AccountStatus::Active,
+            Self::Frozen { .. } => AccountStatus::Frozen,
+        }
+    }
+}
+
+impl CellSerialize for AccountState {
+    #[inline]
+    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        match self {
+            Self::Uninit => {
+                // account_uninit$00
+                builder.pack_as::<_, NBits<2>>(0b00)?;
+            }
+            Self::Active(state_init) => {
+                builder
+                    // account_active$1
+                    .pack(true)?
+                    // _:StateInit
+                    .store(state_init)?;
+            }
+            Self::Frozen { state_hash } => {
+                builder
+                    // account_frozen$01
+                    .pack_as::<_, NBits<2>>(0b01)?
+                    // state_hash:bits256
+                    .pack(state_hash)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'de> CellDeserialize<'de> for AccountState {
+    #[inline]
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        // account_active$1
+        if parser.unpack()? {
+            return Ok(Self::Active(parser.parse().context("state_init")?));
+        }
+        Ok(match parser.unpack()? {
+            // account_uninit$00
+            false => Self::Uninit,
+            // account_frozen$01
+            true => Self::Frozen {
+                state_hash: parser.unpack()?,
+            },
+        })
+    }
+}
+
+/// ```tlb
+/// acc_state_uninit$00 = AccountStatus;
+/// acc_state_frozen$01 = AccountStatus;
+/// acc_state_active$10 = AccountStatus;
+/// acc_state_nonexist$11 = AccountStatus;
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AccountStatus {
+    Uninit,
+    Frozen,
+    Active,
+    NonExist,
+}
+
+impl BitPack for AccountStatus {
+    #[inline]
+    fn pack<W>(&self, mut writer: W) -> Result<(), W::Error>
+    where
+        W: BitWriter,
+    {
+        writer.pack_as::<_, NBits<2>>(match self {
+            Self::Uninit => 0b00,
+            Self::Frozen => 0b01,
+            Self::Active => 0b10,
+            Self::NonExist => 0b11,
+        })?;
+        Ok(())
+    }
+}
+
+impl BitUnpack for AccountStatus {
+    #[inline]
+    fn unpack<R>(mut reader: R) -> Result<Self, R::Error>
+    where
+        R: BitReader,
+    {
+        Ok(match reader.unpack_as::<u8, NBits<2>>()? {
+            0b00 => Self::Uninit,
+            0b01 => Self::Frozen,
+            0b10 => Self::Active,
+            0b11 => Self::NonExist,
+            _ => unreachable!(),
+        })
+    }
+}
+
+/// ```tlb
+/// storage_info$_ used:StorageUsed storage_extra:StorageExtraInfo
+/// last_paid:uint32 due_payment:(Maybe Grams) = StorageInfo;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StorageInfo {
+    pub used: StorageUsed,
+    pub storage_extra: StorageExtraInfo,
+    pub last_paid: DateTime<Utc>,
+    pub due_payment: Option<BigUint>,
+}
+
+impl BitPack for StorageInfo {
+    #[inline]
+    fn pack<W>(&self, mut writer: W) -> Result<(), W::Error>
+    where
+        W: BitWriter,
+    {
+        writer
+            // used:StorageUsed
+            .pack(&self.used)?
+            // storage_extra:StorageExtraInfo
+            .pack(self.storage_extra)?
+            // last_paid:uint32
+            .pack_as::<_, UnixTimestamp>(self.last_paid)?
+            // due_payment:(Maybe Grams)
+            .pack_as::<_, &Option<Grams>>(&self.due_payment)?;
+        Ok(())
+    }
+}
+
+impl BitUnpack for StorageInfo {
+    #[inline]
+    fn unpack<R>(mut reader: R) -> Result<Self, R::Error>
+    where
+        R: BitReader,
+    {
+        Ok(Self {
+            // used:StorageUsed
+            used: reader.unpack()?,
+            // storage_extra:StorageExtraInfo
+            storage_extra: reader.unpack()?,
+            // last_paid:uint32
+            last_paid: reader.unpack_as::<_, UnixTimestamp>()?,
+            // due_payment:(Maybe Grams)
+            due_payment: reader.unpack_as::<_, Option<Grams>>()?,
+        })
+    }
+}
+
+/// ```tlb
+/// storage_used$_ cells:(VarUInteger 7) bits:(VarUInteger 7) = StorageUsed;
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StorageUsed {
+    pub cells: BigUint,
+    pub bits: BigUint,
+}
+
+impl BitPack for StorageUsed {
+    #[inline]
+    fn pack<W>(&self, mut writer: W) -> Result<(), W::Error>
+    where
+        W: BitWriter,
+    {
+        writer
+            // cells:(VarUInteger 7)
+            .pack_as::<_, &VarInt<3>>(&self.cells)?
+            // bits:(VarUInteger 7)
+            .pack_as::<_, &VarInt<3>>(&self.bits)?;
+        Ok(())
+    }
+}
+
+impl BitUnpack for StorageUsed {
+    #[inline]
+    fn unpack<R>(mut reader: R) -> Result<Self, R::Error>
+    where
+        R: BitReader,
+    {
+        Ok(Self {
+            // cells:(VarUInteger 7)
+            cells: reader.unpack_as::<_, VarInt<3>>()?,
+            // bits:(VarUInteger 7)
+            bits: reader.unpack_as::<_, VarInt<3>>()?,
+        })
+    }
+}
+
+/// Before the introduction of `storage_extra`, this place was occupied by
+/// `public_cells:(VarUInteger 7)`, which was always zero, so both layouts
+/// are parsed as [`StorageExtraInfo::None`].
+/// ```tlb
+/// storage_extra_none$000 = StorageExtraInfo;
+/// storage_extra_info$001 dict_hash:uint256 = StorageExtraInfo;
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum StorageExtraInfo {
+    /// ```tlb
+    /// storage_extra_none$000 = StorageExtraInfo;
+    /// ```
+    #[default]
+    None,
+    /// ```tlb
+    /// storage_extra_info$001 dict_hash:uint256 = StorageExtraInfo;
+    /// ```
+    Info { dict_hash: [u8; 32] },
+}
+
+impl BitPack for StorageExtraInfo {
+    #[inline]
+    fn pack<W>(&self, mut writer: W) -> Result<(), W::Error>
+    where
+        W: BitWriter,
+    {
+        match self {
+            // storage_extra_none$000
+            Self::None => writer.pack_as::<_, NBits<3>>(0b000)?,
+            Self::Info { dict_hash } => writer
+                // storage_extra_info$001
+                .pack_as::<_, NBits<3>>(0b001)?
+                // dict_hash:uint256
+                .pack(dict_hash)?,
+        };
+        Ok(())
+    }
+}
+
+impl BitUnpack for StorageExtraInfo {
+    #[inline]
+    fn unpack<R>(mut reader: R) -> Result<Self, R::Error>
+    where
+        R: BitReader,
+    {
+        Ok(match reader.unpack_as::<u8, NBits<3>>()? {
+            // storage_extra_none$000
+            0b000 => Self::None,
+            // storage_extra_info$001
+            0b001 => Self::Info {
+                dict_hash: reader.unpack()?,
+            },
+            tag => {
+                return Err(Error::custom(format!(
+                    "unknown storage_extra tag: {tag:#05b}"
+                )))
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tlb::{
+        bits::{bitvec::vec::BitVec, de::unpack_fully, ser::pack_with},
+        ser::CellSerializeExt,
+    };
+
+    use crate::boc::{BagOfCells, BagOfCellsArgs};
+
+    use super::*;
+
+    fn given_active_account() -> Account {
+        let code = Cell {
+            data: BitVec::from_slice(&[0xff; 4]),
+            ..Default::default()
+        };
+        let data = Cell {
+            data: BitVec::from_slice(&[0, 0, 0, 1]),
+            ..Default::default()
+        };
+        let state_init = StateInit {
+            code: Some(code.clone()),
+            data: Some(data.clone()),
+            ..Default::default()
+        };
+        Account {
+            addr: MsgAddress::derive(0, state_init.clone()).unwrap(),
+            storage_stat: StorageInfo {
+                used: StorageUsed {
+                    cells: 3u32.into(),
+                    bits: 1_000u32.into(),
+                },
+                storage_extra: StorageExtraInfo::None,
+                last_paid: DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+                due_payment: None,
+            },
+            storage: AccountStorage {
+                last_trans_lt: 47_000_000_000_001,
+                balance: CurrencyCollection {
+                    grams: 1_000_000_000u64.into(),
+                    ..Default::default()
+                },
+                state: AccountState::Active(state_init),
+            },
+        }
+    }
+
+    #[test]
+    fn account_boc_serde() {
+        let account = Some(given_active_account());
+
+        let packed = pack_with(
+            BagOfCells::from_root(account.to_cell().unwrap()),
+            BagOfCellsArgs {
+                has_idx: false,
+                has_crc32c: true,
+            },
+        )
+        .unwrap();
+        let got: Option<Account> = unpack_fully::<BagOfCells>(packed)
+            .unwrap()
+            .single_root()
+            .unwrap()
+            .parse_fully()
+            .unwrap();
+
+        assert_eq!(got, account);
+        let got = got.unwrap();
+        assert_eq!(got.status(), AccountStatus::Active);
+        assert_eq!(got.balance().grams, 1_000_000_000u64.into());
+        assert_eq!(
+            got.storage
+                .state
+                .data()
+                .map(|data| data.data.as_raw_slice()),
+            Some([0, 0, 0, 1].as_slice())
+        );
+    }
+
+    #[test]
+    fn shard_account_serde() {
+        for account in [
+            None,
+            Some(Account {
+                storage: AccountStorage {
+                    state: AccountState::Frozen {
+                        state_hash: [1; 32],
+                    },
+                    ..given_active_account().storage
+                },
+                ..given_active_account()
+            }),
+            Some(Account {
+                storage_stat: StorageInfo {
+                    storage_extra: StorageExtraInfo::Info { dict_hash: [2; 32] },
+                    due_payment: Some(100u32.into()),
+                    ..given_active_account().storage_stat
+                },
+                storage: AccountStorage {
+                    state: AccountState::Uninit,
+                    ..given_active_account().storage
+                },
+                ..given_active_account()
+            }),
+        ] {
+            let shard_account = ShardAccount {
+                account,
+                last_trans_hash: [3; 32],
+                last_trans_lt: 100,
+            };
+
+            let cell = shard_account.to_cell().unwrap();
+            let got: ShardAccount = cell.parse_fully().unwrap();
+            assert_eq!(got, shard_account);
+        }
+    }
+
+    #[test]
+    fn legacy_public_cells_storage_info() {
+        let mut builder = Cell::builder();
+        builder
+            // cells:(VarUInteger 7) bits:(VarUInteger 7)
+            .pack(StorageUsed {
+                cells: 1u32.into(),
+                bits: 8u32.into(),
+            })
+            .unwrap()
+            // public_cells:(VarUInteger 7)
+            .pack_as::<_, &VarInt<3>>(&BigUint::ZERO)
+            .unwrap()
+            // last_paid:uint32
+            .pack(1_700_000_000u32)
+            .unwrap()
+            // due_payment:(Maybe Grams)
+            .pack(false)
+            .unwrap();
+        let cell = builder.into_cell();
+
+        let got: StorageInfo = cell.parse_fully_as::<_, tlb::r#as::Data>().unwrap();
+        assert_eq!(got.storage_extra, StorageExtraInfo::None);
+        assert_eq!(got.used.bits, 8u32.into());
+    }
+}
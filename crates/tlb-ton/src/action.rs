@@ -1,14 +1,96 @@
+use bitflags::bitflags;
 use tlb::{
-    bits::{de::BitReaderExt, r#as::NBits, ser::BitWriterExt},
+    bits::{
+        de::{BitReader, BitReaderExt, BitUnpack},
+        r#as::NBits,
+        ser::{BitPack, BitWriter, BitWriterExt},
+    },
     de::{CellDeserialize, CellParser, CellParserError},
     r#as::Ref,
-    ser::{CellBuilder, CellBuilderError, CellSerialize},
+    ser::{CellBuilder, CellBuilderError, CellSerialize, CellSerializeExt},
     Cell, Error, ResultExt,
 };
 
-use crate::{currency::CurrencyCollection, library::LibRef, message::Message};
+use crate::{currency::CurrencyCollection, library::LibRef, list::List, message::Message};
+
+/// List of output actions, as stored in `c5` register
+/// ```tlb
+/// out_list_empty$_ = OutList 0;
+/// out_list$_ {n:#} prev:^(OutList n) action:OutAction = OutList (n + 1);
+/// ```
+/// At most [`MAX_LEN`](OutList::MAX_LEN) actions are allowed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OutList(pub Vec<OutAction>);
+
+impl OutList {
+    /// Maximum number of actions allowed in a single transaction
+    pub const MAX_LEN: usize = 255;
+
+    /// Returns whether no more actions can be added
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        self.0.len() >= Self::MAX_LEN
+    }
+
+    /// Serializes the list into a cell suitable for `c5` register
+    #[inline]
+    pub fn to_c5(&self) -> Result<Cell, CellBuilderError> {
+        self.to_cell()
+    }
+
+    /// Parses the list from the contents of `c5` register
+    #[inline]
+    pub fn from_c5(c5: &Cell) -> Result<Self, CellParserError<'_>> {
+        c5.parse_fully()
+    }
+}
+
+impl From<Vec<OutAction>> for OutList {
+    #[inline]
+    fn from(actions: Vec<OutAction>) -> Self {
+        Self(actions)
+    }
+}
+
+impl FromIterator<OutAction> for OutList {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = OutAction>>(iter: I) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl CellSerialize for OutList {
+    #[inline]
+    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        if self.0.len() > Self::MAX_LEN {
+            return Err(Error::custom(format!(
+                "too many actions: {} > {}",
+                self.0.len(),
+                Self::MAX_LEN
+            )));
+        }
+        builder.store_as::<_, &List>(&self.0)?;
+        Ok(())
+    }
+}
+
+impl<'de> CellDeserialize<'de> for OutList {
+    #[inline]
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        let actions: Vec<OutAction> = parser.parse_as::<_, List>()?;
+        if actions.len() > Self::MAX_LEN {
+            return Err(Error::custom(format!(
+                "too many actions: {} > {}",
+                actions.len(),
+                Self::MAX_LEN
+            )));
+        }
+        Ok(Self(actions))
+    }
+}
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[allow(clippy::large_enum_variant)]
 pub enum OutAction {
     /// ```tlb
     /// action_send_msg#0ec3c86d mode:(## 8) out_msg:^(MessageRelaxed Any) = OutAction;
@@ -81,8 +163,7 @@ impl<'de> CellDeserialize<'de> for OutAction {
 /// ```
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct SendMsgAction<T = Cell, IC = Cell, ID = Cell> {
-    /// See <https://docs.ton.org/develop/func/stdlib#send_raw_message>
-    pub mode: u8,
+    pub mode: SendMode,
     pub message: Message<T, IC, ID>,
 }
 
@@ -119,7 +200,7 @@ where
 /// ```
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ReserveCurrencyAction {
-    pub mode: u8,
+    pub mode: ReserveMode,
     pub currency: CurrencyCollection,
 }
 
@@ -141,6 +222,130 @@ impl<'de> CellDeserialize<'de> for ReserveCurrencyAction {
     }
 }
 
+bitflags! {
+    /// Mode of [`SendMsgAction`],
+    /// see <https://docs.ton.org/develop/func/stdlib#send_raw_message>
+    ///
+    /// [`CARRY_ALL_REMAINING_INCOMING_VALUE`](SendMode::CARRY_ALL_REMAINING_INCOMING_VALUE)
+    /// and [`CARRY_ALL_BALANCE`](SendMode::CARRY_ALL_BALANCE) are mutually
+    /// exclusive, such modes as well as unknown bits are rejected on
+    /// serialization. Any mode is accepted on deserialization, since
+    /// invalid ones can still be found in on-chain action lists.
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+    pub struct SendMode: u8 {
+        /// Pay transfer fees separately from the message value
+        const PAY_FEES_SEPARATELY = 1;
+        /// Ignore errors arising while processing this message
+        /// during the action phase
+        const IGNORE_ERRORS = 2;
+        /// Bounce transaction if the action fails
+        const BOUNCE_ON_ACTION_FAIL = 16;
+        /// Destroy the account if its resulting balance is zero,
+        /// usually combined with [`CARRY_ALL_BALANCE`](SendMode::CARRY_ALL_BALANCE)
+        const DESTROY_ACCOUNT_IF_ZERO = 32;
+        /// Carry all the remaining value of the inbound message
+        /// in addition to the value initially indicated in the new message
+        const CARRY_ALL_REMAINING_INCOMING_VALUE = 64;
+        /// Carry all the remaining balance of the account
+        /// instead of the value initially indicated in the message
+        const CARRY_ALL_BALANCE = 128;
+    }
+}
+
+impl SendMode {
+    /// Returns whether this combination of flags is accepted by the node
+    #[inline]
+    pub const fn is_valid(&self) -> bool {
+        self.difference(Self::all()).is_empty()
+            && !self
+                .contains(Self::CARRY_ALL_REMAINING_INCOMING_VALUE.union(Self::CARRY_ALL_BALANCE))
+    }
+}
+
+impl BitPack for SendMode {
+    #[inline]
+    fn pack<W>(&self, mut writer: W) -> Result<(), W::Error>
+    where
+        W: BitWriter,
+    {
+        if !self.is_valid() {
+            return Err(Error::custom(format!("invalid send mode: {self:?}")));
+        }
+        writer.pack(self.bits())?;
+        Ok(())
+    }
+}
+
+impl BitUnpack for SendMode {
+    #[inline]
+    fn unpack<R>(mut reader: R) -> Result<Self, R::Error>
+    where
+        R: BitReader,
+    {
+        reader.unpack().map(Self::from_bits_retain)
+    }
+}
+
+bitflags! {
+    /// Mode of [`ReserveCurrencyAction`],
+    /// see <https://docs.ton.org/develop/func/stdlib#raw_reserve>
+    ///
+    /// Empty mode reserves exactly the given amount.
+    /// [`NEGATE`](ReserveMode::NEGATE) is only allowed together with
+    /// [`ADD_ORIGINAL_BALANCE`](ReserveMode::ADD_ORIGINAL_BALANCE), such
+    /// modes as well as unknown bits are rejected on serialization. Any mode
+    /// is accepted on deserialization, since invalid ones can still be found
+    /// in on-chain action lists.
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+    pub struct ReserveMode: u8 {
+        /// Reserve all but the given amount
+        const ALL_BUT_AMOUNT = 1;
+        /// Reserve at most the given amount, without failing
+        /// if the balance is insufficient
+        const AT_MOST = 2;
+        /// Increase the amount by the original balance of the account
+        /// before the compute phase
+        const ADD_ORIGINAL_BALANCE = 4;
+        /// Negate the amount before performing the reservation
+        const NEGATE = 8;
+        /// Bounce transaction if the action fails
+        const BOUNCE_ON_ACTION_FAIL = 16;
+    }
+}
+
+impl ReserveMode {
+    /// Returns whether this combination of flags is accepted by the node
+    #[inline]
+    pub const fn is_valid(&self) -> bool {
+        self.difference(Self::all()).is_empty()
+            && (!self.contains(Self::NEGATE) || self.contains(Self::ADD_ORIGINAL_BALANCE))
+    }
+}
+
+impl BitPack for ReserveMode {
+    #[inline]
+    fn pack<W>(&self, mut writer: W) -> Result<(), W::Error>
+    where
+        W: BitWriter,
+    {
+        if !self.is_valid() {
+            return Err(Error::custom(format!("invalid reserve mode: {self:?}")));
+        }
+        writer.pack(self.bits())?;
+        Ok(())
+    }
+}
+
+impl BitUnpack for ReserveMode {
+    #[inline]
+    fn unpack<R>(mut reader: R) -> Result<Self, R::Error>
+    where
+        R: BitReader,
+    {
+        reader.unpack().map(Self::from_bits_retain)
+    }
+}
+
 /// ```tlb
 /// action_change_library#26fa1dd4 mode:(## 7) libref:LibRef = OutAction;
 /// ```
@@ -175,3 +380,113 @@ where
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use tlb::{
+        bits::bitvec::{bitvec, order::Msb0},
+        r#as::Data,
+        ser::r#as::CellSerializeWrapAsExt,
+    };
+
+    use core::iter;
+
+    use crate::{currency::ONE_TON, MsgAddress};
+
+    use super::*;
+
+    #[test]
+    fn out_action_serde() {
+        for action in [
+            OutAction::SetCode(0xdeadbeef_u32.wrap_as::<Data>().to_cell().unwrap()),
+            OutAction::ReserveCurrency(ReserveCurrencyAction {
+                mode: ReserveMode::ALL_BUT_AMOUNT | ReserveMode::BOUNCE_ON_ACTION_FAIL,
+                currency: CurrencyCollection {
+                    grams: ONE_TON.clone(),
+                    other: Default::default(),
+                },
+            }),
+        ] {
+            let cell = action.to_cell().unwrap();
+            let got: OutAction = cell.parse_fully().unwrap();
+            assert_eq!(got, action);
+        }
+    }
+
+    #[test]
+    fn out_list_serde() {
+        let list: OutList = [
+            OutAction::SendMsg(SendMsgAction {
+                mode: SendMode::PAY_FEES_SEPARATELY,
+                message: Message::<()>::transfer(MsgAddress::NULL, ONE_TON.clone(), false)
+                    .normalize()
+                    .unwrap(),
+            }),
+            OutAction::SetCode(0xdeadbeef_u32.wrap_as::<Data>().to_cell().unwrap()),
+        ]
+        .into_iter()
+        .collect();
+
+        let c5 = list.to_c5().unwrap();
+        // out_list$_ prev:^(OutList 1) action:OutAction
+        assert_eq!(c5.references.len(), 2);
+        assert_eq!(c5.references[0].references.len(), 2);
+        assert!(c5.references[0].references[0].data.is_empty());
+
+        assert_eq!(OutList::from_c5(&c5).unwrap(), list);
+        assert_eq!(OutList::default().to_c5().unwrap(), Cell::default(),);
+    }
+
+    #[test]
+    fn out_list_limit() {
+        let list: OutList =
+            iter::repeat_n(OutAction::SetCode(Cell::default()), OutList::MAX_LEN + 1).collect();
+        assert!(list.is_full());
+        assert!(list.to_c5().is_err());
+    }
+
+    #[test]
+    fn send_mode() {
+        let mode = SendMode::PAY_FEES_SEPARATELY | SendMode::IGNORE_ERRORS;
+        let cell = mode.wrap_as::<Data>().to_cell().unwrap();
+        assert_eq!(cell.data, bitvec![u8, Msb0; 0, 0, 0, 0, 0, 0, 1, 1]);
+        assert_eq!(cell.parse_fully_as::<SendMode, Data>().unwrap(), mode);
+
+        // mutually exclusive flags and unknown bits are rejected on
+        // serialization, but parsed as-is
+        for bits in [0b1100_0000u8, 0b0000_0100] {
+            let mode = bits
+                .wrap_as::<Data>()
+                .to_cell()
+                .unwrap()
+                .parse_fully_as::<SendMode, Data>()
+                .unwrap();
+            assert_eq!(mode.bits(), bits);
+            assert!(!mode.is_valid());
+            assert!(mode.wrap_as::<Data>().to_cell().is_err());
+        }
+    }
+
+    #[test]
+    fn reserve_mode() {
+        let mode = ReserveMode::ADD_ORIGINAL_BALANCE | ReserveMode::NEGATE;
+        assert!(mode.is_valid());
+        let cell = mode.wrap_as::<Data>().to_cell().unwrap();
+        assert_eq!(cell.data, bitvec![u8, Msb0; 0, 0, 0, 0, 1, 1, 0, 0]);
+        assert_eq!(cell.parse_fully_as::<ReserveMode, Data>().unwrap(), mode);
+
+        // negation without original balance and unknown bits are rejected
+        // on serialization, but parsed as-is
+        for bits in [0b0000_1000u8, 0b0010_0000] {
+            let mode = bits
+                .wrap_as::<Data>()
+                .to_cell()
+                .unwrap()
+                .parse_fully_as::<ReserveMode, Data>()
+                .unwrap();
+            assert_eq!(mode.bits(), bits);
+            assert!(!mode.is_valid());
+            assert!(mode.wrap_as::<Data>().to_cell().is_err());
+        }
+    }
+}
@@ -1,3 +1,7 @@
+mod friendly;
+
+pub use self::friendly::*;
+
 use core::{
     fmt::{self, Debug, Display},
     str::FromStr,
@@ -10,7 +14,7 @@ use crc::Crc;
 use strum::Display;
 use tlb::{
     bits::{
-        bitvec::{order::Msb0, vec::BitVec},
+        bitvec::{order::Msb0, slice::BitSlice, vec::BitVec},
         de::{BitReader, BitReaderExt, BitUnpack},
         r#as::{NBits, VarBits},
         ser::{BitPack, BitWriter, BitWriterExt},
@@ -344,9 +348,317 @@ impl BitUnpack for MsgAddressTag {
     }
 }
 
+/// [MsgAddressExt](https://docs.ton.org/develop/data-formats/msg-tlb#msgaddressext-tl-b)
+/// ```tlb
+/// addr_none$00 = MsgAddressExt;
+/// addr_extern$01 len:(## 9) external_address:(bits len) = MsgAddressExt;
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub enum MsgAddressExt {
+    /// ```tlb
+    /// addr_none$00 = MsgAddressExt;
+    /// ```
+    #[default]
+    None,
+    /// ```tlb
+    /// addr_extern$01 len:(## 9) external_address:(bits len) = MsgAddressExt;
+    /// ```
+    Extern(BitVec<u8, Msb0>),
+}
+
+impl BitPack for MsgAddressExt {
+    #[inline]
+    fn pack<W>(&self, mut writer: W) -> Result<(), W::Error>
+    where
+        W: BitWriter,
+    {
+        match self {
+            Self::None => writer.pack(MsgAddressTag::Null)?,
+            Self::Extern(external_address) => pack_extern(external_address, &mut writer)?,
+        };
+        Ok(())
+    }
+}
+
+/// ```tlb
+/// addr_extern$01 len:(## 9) external_address:(bits len) = MsgAddressExt;
+/// ```
+#[inline]
+fn pack_extern<W>(external_address: &BitSlice<u8, Msb0>, mut writer: W) -> Result<W, W::Error>
+where
+    W: BitWriter,
+{
+    writer
+        .pack(MsgAddressTag::Extern)?
+        // len:(## 9) external_address:(bits len)
+        .pack_as::<_, VarBits<9>>(external_address)?;
+    Ok(writer)
+}
+
+impl BitUnpack for MsgAddressExt {
+    #[inline]
+    fn unpack<R>(mut reader: R) -> Result<Self, R::Error>
+    where
+        R: BitReader,
+    {
+        match reader.unpack()? {
+            MsgAddressTag::Null => Ok(Self::None),
+            MsgAddressTag::Extern => Ok(Self::Extern(
+                // len:(## 9) external_address:(bits len)
+                reader.unpack_as::<_, VarBits<9>>()?,
+            )),
+            tag => Err(Error::custom(format!("expected MsgAddressExt, got: {tag}"))),
+        }
+    }
+}
+
+/// Lossless representation of any
+/// [MsgAddress](https://docs.ton.org/develop/data-formats/msg-tlb#msgaddressext-tl-b).
+///
+/// Unlike [`MsgAddress`], it preserves [`Anycast`] info, external addresses
+/// and variable-length addresses as-is, so that every variant is serialized
+/// back exactly as it was parsed.
+/// ```tlb
+/// addr_none$00 = MsgAddressExt;
+/// addr_extern$01 len:(## 9) external_address:(bits len) = MsgAddressExt;
+///
+/// addr_std$10 anycast:(Maybe Anycast)
+/// workchain_id:int8 address:bits256  = MsgAddressInt;
+/// addr_var$11 anycast:(Maybe Anycast) addr_len:(## 9)
+/// workchain_id:int32 address:(bits addr_len) = MsgAddressInt;
+///
+/// _ _:MsgAddressInt = MsgAddress;
+/// _ _:MsgAddressExt = MsgAddress;
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub enum AnyMsgAddress {
+    /// ```tlb
+    /// addr_none$00 = MsgAddressExt;
+    /// ```
+    #[default]
+    None,
+    /// ```tlb
+    /// addr_extern$01 len:(## 9) external_address:(bits len) = MsgAddressExt;
+    /// ```
+    Extern(BitVec<u8, Msb0>),
+    /// ```tlb
+    /// addr_std$10 anycast:(Maybe Anycast)
+    /// workchain_id:int8 address:bits256  = MsgAddressInt;
+    /// ```
+    Std {
+        anycast: Option<Anycast>,
+        workchain_id: i8,
+        address: [u8; 32],
+    },
+    /// ```tlb
+    /// addr_var$11 anycast:(Maybe Anycast) addr_len:(## 9)
+    /// workchain_id:int32 address:(bits addr_len) = MsgAddressInt;
+    /// ```
+    Var {
+        anycast: Option<Anycast>,
+        workchain_id: i32,
+        address: BitVec<u8, Msb0>,
+    },
+}
+
+impl AnyMsgAddress {
+    /// Maximum length of `addr_var$11` address fitting into `addr_len:(## 9)`
+    pub const MAX_VAR_ADDRESS_LEN: usize = (1 << 9) - 1;
+
+    /// Returns whether this is an internal address, i.e. `MsgAddressInt`
+    #[inline]
+    pub fn is_internal(&self) -> bool {
+        matches!(self, Self::Std { .. } | Self::Var { .. })
+    }
+
+    /// Returns workchain of internal address
+    #[inline]
+    pub fn workchain_id(&self) -> Option<i32> {
+        match self {
+            Self::Std { workchain_id, .. } => Some(*workchain_id as i32),
+            Self::Var { workchain_id, .. } => Some(*workchain_id),
+            _ => None,
+        }
+    }
+
+    /// Returns [`Anycast`] info of internal address, if any
+    #[inline]
+    pub fn anycast(&self) -> Option<&Anycast> {
+        match self {
+            Self::Std { anycast, .. } | Self::Var { anycast, .. } => anycast.as_ref(),
+            _ => None,
+        }
+    }
+}
+
+impl From<MsgAddress> for AnyMsgAddress {
+    #[inline]
+    fn from(addr: MsgAddress) -> Self {
+        if addr.is_null() {
+            return Self::None;
+        }
+        match i8::try_from(addr.workchain_id) {
+            Ok(workchain_id) => Self::Std {
+                anycast: None,
+                workchain_id,
+                address: addr.address,
+            },
+            Err(_) => Self::Var {
+                anycast: None,
+                workchain_id: addr.workchain_id,
+                address: BitVec::from_slice(&addr.address),
+            },
+        }
+    }
+}
+
+impl From<MsgAddressExt> for AnyMsgAddress {
+    #[inline]
+    fn from(addr: MsgAddressExt) -> Self {
+        match addr {
+            MsgAddressExt::None => Self::None,
+            MsgAddressExt::Extern(external_address) => Self::Extern(external_address),
+        }
+    }
+}
+
+impl TryFrom<AnyMsgAddress> for MsgAddress {
+    type Error = StringError;
+
+    /// Fails if conversion would lose information, i.e. for external
+    /// addresses, addresses with [`Anycast`] or non-256-bit `addr_var$11`
+    fn try_from(addr: AnyMsgAddress) -> Result<Self, Self::Error> {
+        match addr {
+            AnyMsgAddress::None => Ok(Self::NULL),
+            AnyMsgAddress::Std {
+                anycast: None,
+                workchain_id,
+                address,
+            } => Ok(Self {
+                workchain_id: workchain_id as i32,
+                address,
+            }),
+            AnyMsgAddress::Var {
+                anycast: None,
+                workchain_id,
+                address,
+            } => Ok(Self {
+                workchain_id,
+                address: address.as_raw_slice().try_into().map_err(|_| {
+                    Error::custom(format!(
+                        "only 256-bit addresses can be converted, got {} bits",
+                        address.len()
+                    ))
+                })?,
+            }),
+            AnyMsgAddress::Extern(_) => Err(Error::custom("external address")),
+            AnyMsgAddress::Std { .. } | AnyMsgAddress::Var { .. } => {
+                Err(Error::custom("anycast is not supported"))
+            }
+        }
+    }
+}
+
+impl TryFrom<AnyMsgAddress> for MsgAddressExt {
+    type Error = StringError;
+
+    fn try_from(addr: AnyMsgAddress) -> Result<Self, Self::Error> {
+        match addr {
+            AnyMsgAddress::None => Ok(Self::None),
+            AnyMsgAddress::Extern(external_address) => Ok(Self::Extern(external_address)),
+            _ => Err(Error::custom("internal address")),
+        }
+    }
+}
+
+impl BitPack for AnyMsgAddress {
+    fn pack<W>(&self, mut writer: W) -> Result<(), W::Error>
+    where
+        W: BitWriter,
+    {
+        match self {
+            Self::None => writer.pack(MsgAddressExt::None)?,
+            Self::Extern(external_address) => pack_extern(external_address, &mut writer)?,
+            Self::Std {
+                anycast,
+                workchain_id,
+                address,
+            } => writer
+                .pack(MsgAddressTag::Std)?
+                // anycast:(Maybe Anycast)
+                .pack(anycast)?
+                // workchain_id:int8
+                .pack(workchain_id)?
+                // address:bits256
+                .pack(address)?,
+            Self::Var {
+                anycast,
+                workchain_id,
+                address,
+            } => {
+                if address.len() > Self::MAX_VAR_ADDRESS_LEN {
+                    return Err(Error::custom(format!(
+                        "addr_var: address is too long: {} > {} bits",
+                        address.len(),
+                        Self::MAX_VAR_ADDRESS_LEN,
+                    )));
+                }
+                writer
+                    .pack(MsgAddressTag::Var)?
+                    // anycast:(Maybe Anycast)
+                    .pack(anycast)?
+                    // addr_len:(## 9)
+                    .pack_as::<_, NBits<9>>(address.len())?
+                    // workchain_id:int32
+                    .pack(workchain_id)?
+                    // address:(bits addr_len)
+                    .pack(address)?
+            }
+        };
+        Ok(())
+    }
+}
+
+impl BitUnpack for AnyMsgAddress {
+    fn unpack<R>(mut reader: R) -> Result<Self, R::Error>
+    where
+        R: BitReader,
+    {
+        Ok(match reader.unpack()? {
+            MsgAddressTag::Null => Self::None,
+            MsgAddressTag::Extern => Self::Extern(
+                // len:(## 9) external_address:(bits len)
+                reader.unpack_as::<_, VarBits<9>>()?,
+            ),
+            MsgAddressTag::Std => Self::Std {
+                // anycast:(Maybe Anycast)
+                anycast: reader.unpack()?,
+                // workchain_id:int8
+                workchain_id: reader.unpack()?,
+                // address:bits256
+                address: reader.unpack()?,
+            },
+            MsgAddressTag::Var => {
+                // anycast:(Maybe Anycast)
+                let anycast = reader.unpack()?;
+                // addr_len:(## 9)
+                let addr_len: usize = reader.unpack_as::<_, NBits<9>>()?;
+                Self::Var {
+                    anycast,
+                    // workchain_id:int32
+                    workchain_id: reader.unpack()?,
+                    // address:(bits addr_len)
+                    address: reader.unpack_with(addr_len)?,
+                }
+            }
+        })
+    }
+}
+
 /// ```tlb
 /// anycast_info$_ depth:(#<= 30) { depth >= 1 } rewrite_pfx:(bits depth) = Anycast;
 /// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Anycast {
     pub rewrite_pfx: BitVec<u8, Msb0>,
 }
@@ -379,6 +691,8 @@ impl BitUnpack for Anycast {
 
 #[cfg(test)]
 mod tests {
+    use tlb::bits::{bitvec::bitvec, de::unpack_fully, ser::pack};
+
     use super::*;
 
     #[test]
@@ -388,6 +702,65 @@ mod tests {
             .unwrap();
     }
 
+    #[test]
+    fn any_address_serde() {
+        for addr in [
+            AnyMsgAddress::None,
+            AnyMsgAddress::Extern(bitvec![u8, Msb0; 1, 0, 1, 1]),
+            AnyMsgAddress::Std {
+                anycast: None,
+                workchain_id: -1,
+                address: [0xab; 32],
+            },
+            AnyMsgAddress::Std {
+                anycast: Some(Anycast {
+                    rewrite_pfx: bitvec![u8, Msb0; 1, 1, 0],
+                }),
+                workchain_id: 0,
+                address: [0xcd; 32],
+            },
+            AnyMsgAddress::Var {
+                anycast: Some(Anycast {
+                    rewrite_pfx: bitvec![u8, Msb0; 0, 1],
+                }),
+                workchain_id: 1 << 20,
+                address: bitvec![u8, Msb0; 1; 300],
+            },
+        ] {
+            let packed = pack(&addr).unwrap();
+            let got: AnyMsgAddress = unpack_fully(packed).unwrap();
+            assert_eq!(got, addr);
+        }
+    }
+
+    #[test]
+    fn any_address_too_long() {
+        let addr = |len| AnyMsgAddress::Var {
+            anycast: None,
+            workchain_id: 0,
+            address: bitvec![u8, Msb0; 1; len],
+        };
+        assert!(pack(addr(AnyMsgAddress::MAX_VAR_ADDRESS_LEN)).is_ok());
+        assert!(pack(addr(AnyMsgAddress::MAX_VAR_ADDRESS_LEN + 1)).is_err());
+        assert!(pack(AnyMsgAddress::Extern(bitvec![u8, Msb0; 1; 512])).is_err());
+    }
+
+    #[test]
+    fn any_address_conversions() {
+        let addr: MsgAddress = "EQBGXZ9ddZeWypx8EkJieHJX75ct0bpkmu0Y4YoYr3NM0Z9e"
+            .parse()
+            .unwrap();
+        let any = AnyMsgAddress::from(addr);
+        assert_eq!(pack(&any).unwrap(), pack(addr).unwrap());
+        assert_eq!(MsgAddress::try_from(any).unwrap(), addr);
+
+        assert!(MsgAddress::try_from(AnyMsgAddress::Extern(bitvec![u8, Msb0; 1])).is_err());
+        assert_eq!(
+            MsgAddressExt::try_from(AnyMsgAddress::Extern(bitvec![u8, Msb0; 1])).unwrap(),
+            MsgAddressExt::Extern(bitvec![u8, Msb0; 1]),
+        );
+    }
+
     #[cfg(feature = "serde")]
     #[test]
     fn serde() {
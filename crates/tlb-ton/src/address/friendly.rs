@@ -0,0 +1,209 @@
+use core::{
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+use tlb::{Error, StringError};
+
+use super::MsgAddress;
+
+/// Textual representation of [`FriendlyAddress`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum AddressFormat {
+    /// [Raw Address](https://docs.ton.org/learn/overviews/addresses#raw-address):
+    /// `<workchain_id>:<hex>`
+    Raw,
+    /// URL-safe base64
+    /// [user-friendly](https://docs.ton.org/learn/overviews/addresses#user-friendly-address)
+    /// representation
+    #[default]
+    Base64Url,
+    /// Standard base64
+    /// [user-friendly](https://docs.ton.org/learn/overviews/addresses#user-friendly-address)
+    /// representation
+    Base64Std,
+}
+
+/// [`MsgAddress`] together with its
+/// [user-friendly](https://docs.ton.org/learn/overviews/addresses#user-friendly-address)
+/// flags and textual format.
+///
+/// It is parsed from any of raw, URL-safe base64 or standard base64
+/// representations and is [displayed](Display) back in the same format
+/// with the same flags.
+/// ```rust
+/// # use tlb_ton::{AddressFormat, FriendlyAddress};
+/// let addr: FriendlyAddress = "UQBGXZ9ddZeWypx8EkJieHJX75ct0bpkmu0Y4YoYr3NM0cKb"
+///     .parse()
+///     .unwrap();
+/// assert!(!addr.bounceable);
+/// assert!(!addr.testnet);
+/// assert_eq!(addr.format, AddressFormat::Base64Url);
+/// assert_eq!(
+///     addr.to_string(),
+///     "UQBGXZ9ddZeWypx8EkJieHJX75ct0bpkmu0Y4YoYr3NM0cKb",
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(::serde_with::SerializeDisplay, ::serde_with::DeserializeFromStr)
+)]
+pub struct FriendlyAddress {
+    pub address: MsgAddress,
+    pub bounceable: bool,
+    pub testnet: bool,
+    pub format: AddressFormat,
+}
+
+impl FriendlyAddress {
+    /// Bounceable mainnet address in [URL-safe base64](AddressFormat::Base64Url)
+    #[inline]
+    pub const fn new(address: MsgAddress) -> Self {
+        Self {
+            address,
+            bounceable: true,
+            testnet: false,
+            format: AddressFormat::Base64Url,
+        }
+    }
+
+    #[inline]
+    pub const fn with_bounceable(mut self, bounceable: bool) -> Self {
+        self.bounceable = bounceable;
+        self
+    }
+
+    #[inline]
+    pub const fn with_testnet(mut self, testnet: bool) -> Self {
+        self.testnet = testnet;
+        self
+    }
+
+    #[inline]
+    pub const fn with_format(mut self, format: AddressFormat) -> Self {
+        self.format = format;
+        self
+    }
+}
+
+impl From<MsgAddress> for FriendlyAddress {
+    #[inline]
+    fn from(address: MsgAddress) -> Self {
+        Self::new(address)
+    }
+}
+
+impl From<FriendlyAddress> for MsgAddress {
+    #[inline]
+    fn from(addr: FriendlyAddress) -> Self {
+        addr.address
+    }
+}
+
+impl Display for FriendlyAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.format {
+            AddressFormat::Raw => f.write_str(&self.address.to_hex()),
+            AddressFormat::Base64Url => f.write_str(
+                &self
+                    .address
+                    .to_base64_url_flags(!self.bounceable, self.testnet),
+            ),
+            AddressFormat::Base64Std => f.write_str(
+                &self
+                    .address
+                    .to_base64_std_flags(!self.bounceable, self.testnet),
+            ),
+        }
+    }
+}
+
+impl FromStr for FriendlyAddress {
+    type Err = StringError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.len() != 48 {
+            let address = MsgAddress::from_hex(s)?;
+            if i8::try_from(address.workchain_id).is_err() {
+                return Err(Error::custom(format!(
+                    "workchain_id does not fit into int8: {}",
+                    address.workchain_id
+                )));
+            }
+            return Ok(Self::new(address).with_format(AddressFormat::Raw));
+        }
+
+        // both encodings coincide unless there are `+` or `/`
+        let (format, (address, non_bounceable, non_production)) = if s.contains(['+', '/']) {
+            (
+                AddressFormat::Base64Std,
+                MsgAddress::from_base64_std_flags(s)?,
+            )
+        } else {
+            (
+                AddressFormat::Base64Url,
+                MsgAddress::from_base64_url_flags(s)?,
+            )
+        };
+        Ok(Self {
+            address,
+            bounceable: !non_bounceable,
+            testnet: non_production,
+            format,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RAW: &str = "0:465d9f5d759796ca9c7c124262787257ef972dd1ba649aed18e18a18af734cd1";
+
+    #[test]
+    fn preserves_format() {
+        for s in [
+            RAW,
+            "EQBGXZ9ddZeWypx8EkJieHJX75ct0bpkmu0Y4YoYr3NM0Z9e",
+            "UQBGXZ9ddZeWypx8EkJieHJX75ct0bpkmu0Y4YoYr3NM0cKb",
+            "kQBGXZ9ddZeWypx8EkJieHJX75ct0bpkmu0Y4YoYr3NM0STU",
+            "0QBGXZ9ddZeWypx8EkJieHJX75ct0bpkmu0Y4YoYr3NM0XkR",
+        ] {
+            let addr: FriendlyAddress = s.parse().unwrap();
+            assert_eq!(addr.address, MsgAddress::from_hex(RAW).unwrap());
+            assert_eq!(addr.to_string(), s);
+        }
+    }
+
+    #[test]
+    fn flags() {
+        let addr: FriendlyAddress = "0QBGXZ9ddZeWypx8EkJieHJX75ct0bpkmu0Y4YoYr3NM0XkR"
+            .parse()
+            .unwrap();
+        assert!(!addr.bounceable);
+        assert!(addr.testnet);
+
+        let addr = FriendlyAddress::new(MsgAddress {
+            workchain_id: -1,
+            address: [0xff; 32],
+        })
+        .with_bounceable(false);
+        for format in [AddressFormat::Base64Url, AddressFormat::Base64Std] {
+            let addr = addr.with_format(format);
+            assert_eq!(addr.to_string().parse::<FriendlyAddress>().unwrap(), addr);
+        }
+    }
+
+    #[test]
+    fn invalid() {
+        // CRC mismatch
+        assert!("EQBGXZ9ddZeWypx8EkJieHJX75ct0bpkmu0Y4YoYr3NM0Z9f"
+            .parse::<FriendlyAddress>()
+            .is_err());
+        // workchain_id out of int8 range
+        assert!(format!("1000{}", &RAW[1..])
+            .parse::<FriendlyAddress>()
+            .is_err());
+    }
+}
@@ -1,8 +1,15 @@
+use std::iter;
+
 use tlb::{
-    bits::{de::BitReaderExt, ser::BitWriterExt},
+    bits::{
+        bitvec::{order::Msb0, slice::BitSlice, vec::BitVec},
+        de::BitReaderExt,
+        ser::BitWriterExt,
+    },
     de::{args::r#as::CellDeserializeAsWithArgs, CellParser, CellParserError},
     r#as::{ParseFully, Ref},
     ser::{args::r#as::CellSerializeAsWithArgs, CellBuilder, CellBuilderError},
+    Error, ResultExt, StringError,
 };
 
 /// [`BinTreeAug X Y`](https://docs.ton.org/develop/data-formats/tl-b-types#bintree)  
@@ -11,6 +18,7 @@ use tlb::{
 /// bta_fork$1 {X:Type} {Y:Type} left:^(BinTreeAug X Y)
 /// right:^(BinTreeAug X Y) extra:Y = BinTreeAug X Y;
 /// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct BinTreeAug<T, E = ()> {
     pub node: BinTreeNode<T, E>,
     pub extra: E,
@@ -31,9 +39,27 @@ where
         builder: &mut CellBuilder,
         (args, extra_args): Self::Args,
     ) -> Result<(), CellBuilderError> {
-        builder
-            .store_as_with::<_, &AsE>(&source.extra, extra_args.clone())?
-            .store_as_with::<_, &BinTreeNode<AsT, AsE>>(&source.node, (args, extra_args))?;
+        match &source.node {
+            BinTreeNode::Leaf(leaf) => builder
+                // bta_leaf$0
+                .pack(false)?
+                // extra:Y
+                .store_as_with::<_, &AsE>(&source.extra, extra_args)
+                .context("extra")?
+                // leaf:X
+                .store_as_with::<_, &AsT>(leaf, args)?,
+            BinTreeNode::Fork(fork) => builder
+                // bta_fork$1
+                .pack(true)?
+                // left:^(BinTreeAug X Y) right:^(BinTreeAug X Y)
+                .store_as_with::<_, &[Box<Ref<BinTreeAug<AsT, AsE>>>; 2]>(
+                    fork,
+                    (args, extra_args.clone()),
+                )?
+                // extra:Y
+                .store_as_with::<_, &AsE>(&source.extra, extra_args)
+                .context("extra")?,
+        };
         Ok(())
     }
 }
@@ -52,10 +78,29 @@ where
         parser: &mut CellParser<'de>,
         (args, extra_args): Self::Args,
     ) -> Result<BinTreeAug<T, E>, CellParserError<'de>> {
-        Ok(BinTreeAug {
-            extra: parser.parse_as_with::<_, AsE>(extra_args.clone())?,
-            node: parser
-                .parse_as_with::<_, ParseFully<BinTreeNode<AsT, AsE>>>((args, extra_args))?,
+        Ok(match parser.unpack()? {
+            // bta_leaf$0
+            false => BinTreeAug {
+                // extra:Y
+                extra: parser
+                    .parse_as_with::<_, AsE>(extra_args)
+                    .context("extra")?,
+                // leaf:X
+                node: BinTreeNode::Leaf(parser.parse_as_with::<_, AsT>(args)?),
+            },
+            // bta_fork$1
+            true => BinTreeAug {
+                // left:^(BinTreeAug X Y) right:^(BinTreeAug X Y)
+                node: BinTreeNode::Fork(
+                    parser.parse_as_with::<_, [Box<Ref<ParseFully<BinTreeAug<AsT, AsE>>>>; 2]>(
+                        (args, extra_args.clone()),
+                    )?,
+                ),
+                // extra:Y
+                extra: parser
+                    .parse_as_with::<_, AsE>(extra_args)
+                    .context("extra")?,
+            },
         })
     }
 }
@@ -68,11 +113,130 @@ where
 /// bta_fork$1 {X:Type} {Y:Type} left:^(BinTreeAug X Y)
 /// right:^(BinTreeAug X Y) extra:Y = BinTreeAug X Y;
 /// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum BinTreeNode<T, E = ()> {
     Leaf(T),
+    /// [left, right]
     Fork([Box<BinTreeAug<T, E>>; 2]),
 }
 
+impl<T, E> BinTreeAug<T, E> {
+    /// Returns number of leaves in this tree
+    #[inline]
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> usize {
+        match &self.node {
+            BinTreeNode::Leaf(_) => 1,
+            BinTreeNode::Fork([left, right]) => left.len() + right.len(),
+        }
+    }
+
+    /// Returns the subtree at exactly given `path`, so that its extra can be
+    /// inspected as well
+    #[inline]
+    pub fn subtree(&self, path: impl AsRef<BitSlice<u8, Msb0>>) -> Option<&Self> {
+        let mut tree = self;
+        for bit in path.as_ref().iter().by_vals() {
+            let BinTreeNode::Fork([left, right]) = &tree.node else {
+                return None;
+            };
+            tree = if bit { right } else { left };
+        }
+        Some(tree)
+    }
+
+    /// Returns the leaf whose path is a prefix of given `key`.
+    /// See [`BinTree::get()`](super::BinTree::get).
+    #[inline]
+    pub fn get(&self, key: impl AsRef<BitSlice<u8, Msb0>>) -> Option<&T> {
+        let mut tree = self;
+        let mut key = key.as_ref().iter().by_vals();
+        loop {
+            match &tree.node {
+                BinTreeNode::Leaf(v) => return Some(v),
+                BinTreeNode::Fork([left, right]) => tree = if key.next()? { right } else { left },
+            }
+        }
+    }
+
+    /// Returns iterator over `(path, leaf, extra)` in left-to-right order
+    pub fn iter(&self) -> impl Iterator<Item = (BitVec<u8, Msb0>, &T, &E)> {
+        let mut stack = vec![(BitVec::new(), self)];
+        iter::from_fn(move || loop {
+            let (path, tree) = stack.pop()?;
+            match &tree.node {
+                BinTreeNode::Leaf(v) => return Some((path, v, &tree.extra)),
+                BinTreeNode::Fork([left, right]) => {
+                    for (bit, child) in [(true, right), (false, left)] {
+                        let mut path = path.clone();
+                        path.push(bit);
+                        stack.push((path, child.as_ref()));
+                    }
+                }
+            }
+        })
+    }
+
+    /// Returns subtree at exactly given path
+    fn subtree_mut(&mut self, path: &BitSlice<u8, Msb0>) -> Result<&mut Self, StringError> {
+        let mut tree = self;
+        for (i, bit) in path.iter().by_vals().enumerate() {
+            let BinTreeNode::Fork([left, right]) = &mut tree.node else {
+                return Err(Error::custom(format!(
+                    "path {path} goes through leaf at depth {i}"
+                )));
+            };
+            tree = if bit { right } else { left };
+        }
+        Ok(tree)
+    }
+
+    /// Splits the leaf at exactly given `path` into a fork of two leaves
+    /// produced by `f` from the original leaf and its extra.
+    /// Extra of the split node itself, as well as of its ancestors, is left
+    /// as is. See [`BinTree::split()`](super::BinTree::split).
+    pub fn split(
+        &mut self,
+        path: impl AsRef<BitSlice<u8, Msb0>>,
+        f: impl FnOnce(&T, &E) -> [(T, E); 2],
+    ) -> Result<&mut Self, StringError> {
+        let tree = self.subtree_mut(path.as_ref())?;
+        let BinTreeNode::Leaf(leaf) = &tree.node else {
+            return Err(Error::custom("not a leaf"));
+        };
+        tree.node = BinTreeNode::Fork(f(leaf, &tree.extra).map(|(leaf, extra)| {
+            Box::new(Self {
+                node: BinTreeNode::Leaf(leaf),
+                extra,
+            })
+        }));
+        Ok(self)
+    }
+
+    /// Merges the fork at exactly given `path`, which must consist of two
+    /// leaves, into a single leaf produced by `f` from `[left, right]`
+    /// leaves and their extras.
+    /// Extra of the merged node itself, as well as of its ancestors, is left
+    /// as is. See [`BinTree::merge()`](super::BinTree::merge).
+    pub fn merge(
+        &mut self,
+        path: impl AsRef<BitSlice<u8, Msb0>>,
+        f: impl FnOnce([(&T, &E); 2]) -> T,
+    ) -> Result<&mut Self, StringError> {
+        let tree = self.subtree_mut(path.as_ref())?;
+        let BinTreeNode::Fork([left, right]) = &tree.node else {
+            return Err(Error::custom("not a fork of two leaves"));
+        };
+        let (BinTreeNode::Leaf(l), BinTreeNode::Leaf(r)) = (&left.node, &right.node) else {
+            return Err(Error::custom("not a fork of two leaves"));
+        };
+        tree.node = BinTreeNode::Leaf(f([(l, &left.extra), (r, &right.extra)]));
+        Ok(self)
+    }
+}
+
+/// Stores only the node itself without its `extra:Y`, i.e. constructor tag
+/// followed by either `leaf:X` or `left:^(BinTreeAug X Y) right:^(BinTreeAug X Y)`
 impl<T, AsT, E, AsE> CellSerializeAsWithArgs<BinTreeNode<T, E>> for BinTreeNode<AsT, AsE>
 where
     AsT: CellSerializeAsWithArgs<T>,
@@ -101,6 +265,7 @@ where
     }
 }
 
+/// Parses only the node itself without its `extra:Y`
 impl<'de, T, AsT, E, AsE> CellDeserializeAsWithArgs<'de, BinTreeNode<T, E>>
     for BinTreeNode<AsT, AsE>
 where
@@ -126,3 +291,108 @@ where
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use tlb::{
+        bits::bitvec::bits,
+        r#as::{Data, NoArgs},
+        ser::{r#as::CellSerializeWrapAsExt, CellSerializeExt},
+        Cell,
+    };
+
+    use super::*;
+
+    #[test]
+    fn bin_tree_aug_serde() {
+        let data = (
+            bits![u8, Msb0; 1].wrap_as::<Data>(),
+            // bta_leaf$0 extra:8 leaf:8
+            bits![u8, Msb0; 0, 0,0,0,0,0,0,0,1, 0,0,0,0,0,1,0,1].wrap_as::<Ref<Data>>(),
+            bits![u8, Msb0; 0, 0,0,0,0,0,0,1,0, 0,0,0,0,0,0,1,1].wrap_as::<Ref<Data>>(),
+            // extra:8
+            bits![u8, Msb0; 0,0,0,0,0,0,1,1].wrap_as::<Data>(),
+        )
+            .to_cell()
+            .unwrap();
+
+        let tree: BinTreeAug<u8, u8> = data
+            .parse_fully_as_with::<_, BinTreeAug<Data<NoArgs<_>>, Data<NoArgs<_>>>>(((), ()))
+            .unwrap();
+
+        assert_eq!(tree.extra, 3);
+        assert_eq!(tree.len(), 2);
+        assert_eq!(tree.get(bits![u8, Msb0; 1]), Some(&3));
+        assert_eq!(tree.subtree(bits![u8, Msb0; 0]).map(|t| t.extra), Some(1));
+        assert_eq!(
+            tree.iter().map(|(_, v, e)| (*v, *e)).collect::<Vec<_>>(),
+            [(5, 1), (3, 2)],
+        );
+
+        let mut builder = Cell::builder();
+        builder
+            .store_as_with::<_, &BinTreeAug<Data<NoArgs<_>>, Data<NoArgs<_>>>>(&tree, ((), ()))
+            .unwrap();
+        assert_eq!(builder.into_cell(), data);
+    }
+
+    #[test]
+    fn bin_tree_aug_split_merge() {
+        let mut tree = BinTreeAug {
+            node: BinTreeNode::Leaf(1u8),
+            extra: 10u8,
+        };
+        tree.split(bits![u8, Msb0;], |v, e| {
+            [(v * 2, e + 1), (v * 2 + 1, e + 2)]
+        })
+        .unwrap()
+        .split(bits![u8, Msb0; 1], |v, e| {
+            [(v * 2, e * 2), (v * 2 + 1, e * 3)]
+        })
+        .unwrap();
+
+        assert_eq!(tree.extra, 10);
+        assert_eq!(tree.subtree(bits![u8, Msb0; 1]).map(|t| t.extra), Some(12));
+        assert_eq!(
+            tree.iter().map(|(_, v, e)| (*v, *e)).collect::<Vec<_>>(),
+            [(2, 11), (6, 24), (7, 36)],
+        );
+
+        assert!(tree
+            .split(bits![u8, Msb0; 1], |v, e| [(*v, *e), (*v, *e)])
+            .is_err());
+        assert!(tree
+            .split(bits![u8, Msb0; 0, 1], |v, e| [(*v, *e), (*v, *e)])
+            .is_err());
+        assert!(tree
+            .merge(bits![u8, Msb0;], |[(l, _), (r, _)]| l + r)
+            .is_err());
+        tree.merge(bits![u8, Msb0; 1], |[(l, le), (r, re)]| l + r + le + re)
+            .unwrap();
+        assert_eq!(tree.len(), 2);
+        assert_eq!(tree.get(bits![u8, Msb0; 1]), Some(&73));
+        assert_eq!(tree.subtree(bits![u8, Msb0; 1]).map(|t| t.extra), Some(12));
+    }
+
+    #[test]
+    fn bin_tree_node_without_extra() {
+        let node: BinTreeNode<u8, u8> = BinTreeNode::Fork([(5, 1), (3, 2)].map(|(leaf, extra)| {
+            Box::new(BinTreeAug {
+                node: BinTreeNode::Leaf(leaf),
+                extra,
+            })
+        }));
+
+        let mut builder = Cell::builder();
+        builder
+            .store_as_with::<_, &BinTreeNode<Data<NoArgs<_>>, Data<NoArgs<_>>>>(&node, ((), ()))
+            .unwrap();
+        let cell = builder.into_cell();
+        assert_eq!(cell.data.len(), 1);
+
+        let got: BinTreeNode<u8, u8> = cell
+            .parse_fully_as_with::<_, BinTreeNode<Data<NoArgs<_>>, Data<NoArgs<_>>>>(((), ()))
+            .unwrap();
+        assert_eq!(got, node);
+    }
+}
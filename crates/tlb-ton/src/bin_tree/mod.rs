@@ -1,19 +1,23 @@
 //! Collection of bintree-like **de**/**ser**ializable data structures
 pub mod aug;
 
-use std::ops::Deref;
+use std::{iter, ops::Deref};
 
-use tlb::bits::de::BitReaderExt;
-use tlb::de::args::r#as::CellDeserializeAsWithArgs;
+use tlb::bits::bitvec::{order::Msb0, slice::BitSlice, vec::BitVec};
+use tlb::bits::{de::BitReaderExt, ser::BitWriterExt};
+use tlb::de::args::{r#as::CellDeserializeAsWithArgs, CellDeserializeWithArgs};
 use tlb::de::{CellParser, CellParserError};
-use tlb::r#as::Ref;
+use tlb::r#as::{Ref, Same};
+use tlb::ser::args::{r#as::CellSerializeAsWithArgs, CellSerializeWithArgs};
+use tlb::ser::{CellBuilder, CellBuilderError};
+use tlb::{Error, StringError};
 
 /// [`BinTree X`](https://docs.ton.org/develop/data-formats/tl-b-types#bintree)
 /// ```tlb
 /// bt_leaf$0 {X:Type} leaf:X = BinTree X;
 /// bt_fork$1 {X:Type} left:^(BinTree X) right:^(BinTree X) = BinTree X;
 /// ```
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum BinTree<X> {
     Leaf(X),
     Fork([Box<BinTree<X>>; 2]),
@@ -51,6 +55,154 @@ impl<X> BinTree<X> {
             _ => None,
         }
     }
+
+    /// Returns number of leaves in this tree
+    #[inline]
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> usize {
+        match self {
+            Self::Leaf(_) => 1,
+            Self::Fork([left, right]) => left.len() + right.len(),
+        }
+    }
+
+    /// Returns the leaf whose path is a prefix of given `key`, e.g. the
+    /// shard which an account with given address prefix belongs to.
+    /// Returns `None` if `key` is too short to reach a leaf.
+    #[inline]
+    pub fn get(&self, key: impl AsRef<BitSlice<u8, Msb0>>) -> Option<&X> {
+        let mut node = self;
+        let mut key = key.as_ref().iter().by_vals();
+        loop {
+            match node {
+                Self::Leaf(v) => return Some(v),
+                Self::Fork([left, right]) => node = if key.next()? { right } else { left },
+            }
+        }
+    }
+
+    /// Returns mutable reference to the leaf whose path is a prefix of
+    /// given `key`. See [`.get()`](BinTree::get).
+    #[inline]
+    pub fn get_mut(&mut self, key: impl AsRef<BitSlice<u8, Msb0>>) -> Option<&mut X> {
+        let mut node = self;
+        let mut key = key.as_ref().iter().by_vals();
+        loop {
+            match node {
+                Self::Leaf(v) => return Some(v),
+                Self::Fork([left, right]) => node = if key.next()? { right } else { left },
+            }
+        }
+    }
+
+    /// Returns node at exactly given path
+    fn node_mut(&mut self, path: &BitSlice<u8, Msb0>) -> Result<&mut Self, StringError> {
+        let mut node = self;
+        for (i, bit) in path.iter().by_vals().enumerate() {
+            let Self::Fork([left, right]) = node else {
+                return Err(Error::custom(format!(
+                    "path {path} goes through leaf at depth {i}"
+                )));
+            };
+            node = if bit { right } else { left };
+        }
+        Ok(node)
+    }
+
+    /// Returns iterator over `(path, leaf)` pairs in left-to-right order
+    pub fn iter(&self) -> impl Iterator<Item = (BitVec<u8, Msb0>, &X)> {
+        let mut stack = vec![(BitVec::new(), self)];
+        iter::from_fn(move || loop {
+            let (path, node) = stack.pop()?;
+            match node {
+                Self::Leaf(v) => return Some((path, v)),
+                Self::Fork([left, right]) => {
+                    for (bit, child) in [(true, right), (false, left)] {
+                        let mut path = path.clone();
+                        path.push(bit);
+                        stack.push((path, child));
+                    }
+                }
+            }
+        })
+    }
+
+    /// Splits the leaf at exactly given `path` into a fork of two leaves
+    /// produced by `f` from the original one
+    pub fn split(
+        &mut self,
+        path: impl AsRef<BitSlice<u8, Msb0>>,
+        f: impl FnOnce(&X) -> [X; 2],
+    ) -> Result<&mut Self, StringError> {
+        let node = self.node_mut(path.as_ref())?;
+        let Self::Leaf(leaf) = node else {
+            return Err(Error::custom("not a leaf"));
+        };
+        *node = Self::Fork(f(leaf).map(Self::Leaf).map(Box::new));
+        Ok(self)
+    }
+
+    /// Merges the fork at exactly given `path`, which must consist of two
+    /// leaves, into a single leaf produced by `f`
+    pub fn merge(
+        &mut self,
+        path: impl AsRef<BitSlice<u8, Msb0>>,
+        f: impl FnOnce([&X; 2]) -> X,
+    ) -> Result<&mut Self, StringError> {
+        let node = self.node_mut(path.as_ref())?;
+        let Some([Self::Leaf(left), Self::Leaf(right)]) = node.as_fork() else {
+            return Err(Error::custom("not a fork of two leaves"));
+        };
+        *node = Self::Leaf(f([left, right]));
+        Ok(self)
+    }
+}
+
+impl<T, As> CellSerializeAsWithArgs<BinTree<T>> for BinTree<As>
+where
+    As: CellSerializeAsWithArgs<T>,
+    As::Args: Clone,
+{
+    type Args = As::Args;
+
+    #[inline]
+    fn store_as_with(
+        source: &BinTree<T>,
+        builder: &mut CellBuilder,
+        args: Self::Args,
+    ) -> Result<(), CellBuilderError> {
+        match source {
+            BinTree::Leaf(leaf) => builder
+                // bt_leaf$0
+                .pack(false)?
+                // leaf:X
+                .store_as_with::<_, &As>(leaf, args)?,
+            BinTree::Fork(fork) => builder
+                // bt_fork$1
+                .pack(true)?
+                // left:^(BinTree X) right:^(BinTree X)
+                .store_as_with::<_, &[Box<Ref<BinTree<As>>>; 2]>(fork, args)?,
+        };
+        Ok(())
+    }
+}
+
+impl<T> CellSerializeWithArgs for BinTree<T>
+where
+    T: CellSerializeWithArgs,
+    T::Args: Clone,
+{
+    type Args = T::Args;
+
+    #[inline]
+    fn store_with(
+        &self,
+        builder: &mut CellBuilder,
+        args: Self::Args,
+    ) -> Result<(), CellBuilderError> {
+        builder.store_as_with::<_, Same>(self, args)?;
+        Ok(())
+    }
 }
 
 impl<'de, T, As> CellDeserializeAsWithArgs<'de, BinTree<T>> for BinTree<As>
@@ -74,6 +226,22 @@ where
     }
 }
 
+impl<'de, T> CellDeserializeWithArgs<'de> for BinTree<T>
+where
+    T: CellDeserializeWithArgs<'de>,
+    T::Args: Clone,
+{
+    type Args = T::Args;
+
+    #[inline]
+    fn parse_with(
+        parser: &mut CellParser<'de>,
+        args: Self::Args,
+    ) -> Result<Self, CellParserError<'de>> {
+        parser.parse_as_with::<_, Same>(args)
+    }
+}
+
 impl<'de, T, As> CellDeserializeAsWithArgs<'de, Vec<T>> for BinTree<As>
 where
     As: CellDeserializeAsWithArgs<'de, T>,
@@ -133,6 +301,7 @@ mod tests {
     use tlb::r#as::{Data, NoArgs, Ref, Same};
     use tlb::ser::r#as::CellSerializeWrapAsExt;
     use tlb::ser::CellSerializeExt;
+    use tlb::Cell;
 
     #[test]
     fn bin_tree_leaf() {
@@ -257,4 +426,54 @@ mod tests {
 
         assert_eq!(got, vec![0, 1, 2, 3, 4, 5, 6, 7]);
     }
+
+    #[test]
+    fn bin_tree_serde() {
+        let data = (
+            bits![u8, Msb0; 1].wrap_as::<Data>(),
+            bits![u8, Msb0; 0, 0, 0, 0, 0, 0, 1, 0, 1].wrap_as::<Ref<Data>>(),
+            bits![u8, Msb0; 0, 0, 0, 0, 0, 0, 0, 1, 1].wrap_as::<Ref<Data>>(),
+        )
+            .to_cell()
+            .unwrap();
+
+        let tree: BinTree<u8> = data
+            .parse_fully_as_with::<_, BinTree<Data<NoArgs<_>>>>(())
+            .unwrap();
+
+        let mut builder = Cell::builder();
+        builder
+            .store_as_with::<_, &BinTree<Data<NoArgs<_>>>>(&tree, ())
+            .unwrap();
+        assert_eq!(builder.into_cell(), data);
+    }
+
+    #[test]
+    fn bin_tree_lookup() {
+        let mut tree = BinTree::Leaf(0u8);
+        tree.split(bits![u8, Msb0;], |v| [v * 2, v * 2 + 1])
+            .unwrap()
+            .split(bits![u8, Msb0; 1], |v| [v * 2, v * 2 + 1])
+            .unwrap();
+
+        assert_eq!(tree.len(), 3);
+        assert_eq!(tree.get(bits![u8, Msb0; 0, 1, 1]), Some(&0));
+        assert_eq!(tree.get(bits![u8, Msb0; 1, 1, 0]), Some(&3));
+        assert_eq!(tree.get(bits![u8, Msb0; 1]), None);
+        assert_eq!(
+            tree.iter()
+                .map(|(path, v)| (path.to_string(), *v))
+                .collect::<Vec<_>>(),
+            [
+                ("[0]".to_string(), 0),
+                ("[1, 0]".to_string(), 2),
+                ("[1, 1]".to_string(), 3)
+            ],
+        );
+
+        assert!(tree.split(bits![u8, Msb0; 1], |v| [*v, *v]).is_err());
+        assert!(tree.merge(bits![u8, Msb0;], |[l, r]| l + r).is_err());
+        tree.merge(bits![u8, Msb0; 1], |[l, r]| l + r).unwrap();
+        assert_eq!(tree.get(bits![u8, Msb0; 1]), Some(&5));
+    }
 }
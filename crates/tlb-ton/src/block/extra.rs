@@ -0,0 +1,302 @@
+use tlb::{
+    bits::{
+        de::{BitReader, BitReaderExt, BitUnpack},
+        r#as::NBits,
+        ser::{BitPack, BitWriter, BitWriterExt},
+    },
+    de::{CellDeserialize, CellParser, CellParserError},
+    r#as::{Data, NoArgs, Ref},
+    ser::{CellBuilder, CellBuilderError, CellSerialize},
+    Cell, Error, ResultExt,
+};
+
+use crate::{
+    config::ConfigParams,
+    currency::CurrencyCollection,
+    hashmap::{aug::HashmapAugE, HashmapE},
+};
+
+use super::{InMsg, InMsgDescr, OutMsgDescr, ShardHashes};
+
+/// ```tlb
+/// block_extra#4a33f6fd in_msg_descr:^InMsgDescr
+/// out_msg_descr:^OutMsgDescr
+/// account_blocks:^ShardAccountBlocks
+/// rand_seed:bits256
+/// created_by:bits256
+/// custom:(Maybe ^McBlockExtra) = BlockExtra;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockExtra {
+    pub in_msg_descr: InMsgDescr,
+    pub out_msg_descr: OutMsgDescr,
+    /// `ShardAccountBlocks`
+    pub account_blocks: Cell,
+    pub rand_seed: [u8; 32],
+    pub created_by: [u8; 32],
+    /// Present only in masterchain blocks
+    pub custom: Option<McBlockExtra>,
+}
+
+impl BlockExtra {
+    const TAG: u32 = 0x4a33f6fd;
+}
+
+impl CellSerialize for BlockExtra {
+    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        builder
+            // block_extra#4a33f6fd
+            .pack(Self::TAG)?
+            // in_msg_descr:^InMsgDescr
+            .store_as_with::<_, Ref<&HashmapAugE<NoArgs<_>, NoArgs<_>>>>(
+                &self.in_msg_descr,
+                (256, (), ()),
+            )
+            .context("in_msg_descr")?
+            // out_msg_descr:^OutMsgDescr
+            .store_as_with::<_, Ref<&HashmapAugE<NoArgs<_>, NoArgs<_>>>>(
+                &self.out_msg_descr,
+                (256, (), ()),
+            )
+            .context("out_msg_descr")?
+            // account_blocks:^ShardAccountBlocks
+            .store_as::<_, Ref>(&self.account_blocks)?
+            // rand_seed:bits256
+            .pack(self.rand_seed)?
+            // created_by:bits256
+            .pack(self.created_by)?
+            // custom:(Maybe ^McBlockExtra)
+            .store_as::<_, Option<Ref>>(self.custom.as_ref())
+            .context("custom")?;
+        Ok(())
+    }
+}
+
+impl<'de> CellDeserialize<'de> for BlockExtra {
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        let tag: u32 = parser.unpack()?;
+        if tag != Self::TAG {
+            return Err(Error::custom(format!("unknown block_extra tag: {tag:#0x}")));
+        }
+        Ok(Self {
+            // in_msg_descr:^InMsgDescr
+            in_msg_descr: parser
+                .parse_as_with::<_, Ref<HashmapAugE<NoArgs<_>, NoArgs<_>>>>((256, (), ()))
+                .context("in_msg_descr")?,
+            // out_msg_descr:^OutMsgDescr
+            out_msg_descr: parser
+                .parse_as_with::<_, Ref<HashmapAugE<NoArgs<_>, NoArgs<_>>>>((256, (), ()))
+                .context("out_msg_descr")?,
+            // account_blocks:^ShardAccountBlocks
+            account_blocks: parser.parse_as::<_, Ref>().context("account_blocks")?,
+            // rand_seed:bits256
+            rand_seed: parser.unpack()?,
+            // created_by:bits256
+            created_by: parser.unpack()?,
+            // custom:(Maybe ^McBlockExtra)
+            custom: parser.parse_as::<_, Option<Ref>>().context("custom")?,
+        })
+    }
+}
+
+/// Masterchain-specific part of [`BlockExtra`]
+///
+/// `key_block` flag is derived from presence of [`config`](McBlockExtra::config).
+/// ```tlb
+/// masterchain_block_extra#cca5
+/// key_block:(## 1)
+/// shard_hashes:ShardHashes
+/// shard_fees:ShardFees
+/// ^[ prev_blk_signatures:(HashmapE 16 CryptoSignaturePair)
+/// recover_create_msg:(Maybe ^InMsg)
+/// mint_msg:(Maybe ^InMsg) ]
+/// config:key_block?ConfigParams
+/// = McBlockExtra;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct McBlockExtra {
+    pub shard_hashes: ShardHashes,
+    pub shard_fees: ShardFees,
+    pub prev_blk_signatures: HashmapE<CryptoSignaturePair>,
+    pub recover_create_msg: Option<InMsg>,
+    pub mint_msg: Option<InMsg>,
+    /// Present only in key blocks
+    pub config: Option<ConfigParams>,
+}
+
+impl McBlockExtra {
+    const TAG: u16 = 0xcca5;
+}
+
+impl CellSerialize for McBlockExtra {
+    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        let mut info = Cell::builder();
+        info
+            // prev_blk_signatures:(HashmapE 16 CryptoSignaturePair)
+            .store_as_with::<_, &HashmapE<Data<NoArgs<_>>, NoArgs<_>>>(
+                &self.prev_blk_signatures,
+                (16, (), ()),
+            )?
+            // recover_create_msg:(Maybe ^InMsg)
+            .store_as::<_, Option<Ref>>(self.recover_create_msg.as_ref())?
+            // mint_msg:(Maybe ^InMsg)
+            .store_as::<_, Option<Ref>>(self.mint_msg.as_ref())?;
+
+        builder
+            // masterchain_block_extra#cca5
+            .pack(Self::TAG)?
+            // key_block:(## 1)
+            .pack(self.config.is_some())?
+            // shard_hashes:ShardHashes
+            .store(&self.shard_hashes)
+            .context("shard_hashes")?
+            // shard_fees:ShardFees
+            .store_as_with::<_, &HashmapAugE<NoArgs<_>, NoArgs<_>>>(&self.shard_fees, (96, (), ()))
+            .context("shard_fees")?
+            // ^[ ... ]
+            .store_as::<_, Ref>(info)?;
+        if let Some(config) = &self.config {
+            // config:key_block?ConfigParams
+            builder.store(config).context("config")?;
+        }
+        Ok(())
+    }
+}
+
+impl<'de> CellDeserialize<'de> for McBlockExtra {
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        let tag: u16 = parser.unpack()?;
+        if tag != Self::TAG {
+            return Err(Error::custom(format!(
+                "unknown masterchain_block_extra tag: {tag:#0x}"
+            )));
+        }
+        // key_block:(## 1)
+        let key_block: bool = parser.unpack()?;
+        // shard_hashes:ShardHashes
+        let shard_hashes = parser.parse().context("shard_hashes")?;
+        // shard_fees:ShardFees
+        let shard_fees = parser
+            .parse_as_with::<_, HashmapAugE<NoArgs<_>, NoArgs<_>>>((96, (), ()))
+            .context("shard_fees")?;
+        // ^[ ... ]
+        let mut info: CellParser<'de> = parser.parse_as::<_, Ref>()?;
+        let prev_blk_signatures = info
+            .parse_as_with::<_, HashmapE<Data<NoArgs<_>>, NoArgs<_>>>((16, (), ()))
+            .context("prev_blk_signatures")?;
+        let recover_create_msg = info
+            .parse_as::<_, Option<Ref>>()
+            .context("recover_create_msg")?;
+        let mint_msg = info.parse_as::<_, Option<Ref>>().context("mint_msg")?;
+        info.ensure_empty()?;
+        Ok(Self {
+            shard_hashes,
+            shard_fees,
+            prev_blk_signatures,
+            recover_create_msg,
+            mint_msg,
+            // config:key_block?ConfigParams
+            config: if key_block {
+                Some(parser.parse().context("config")?)
+            } else {
+                None
+            },
+        })
+    }
+}
+
+/// Fees collected and funds created in every shard
+/// ```tlb
+/// _ (HashmapAugE 96 ShardFeeCreated ShardFeeCreated) = ShardFees;
+/// ```
+pub type ShardFees = HashmapAugE<ShardFeeCreated, ShardFeeCreated>;
+
+/// ```tlb
+/// shard_fee_created$_ fees:CurrencyCollection create:CurrencyCollection = ShardFeeCreated;
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ShardFeeCreated {
+    pub fees: CurrencyCollection,
+    pub create: CurrencyCollection,
+}
+
+impl CellSerialize for ShardFeeCreated {
+    #[inline]
+    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        builder
+            // fees:CurrencyCollection
+            .store(&self.fees)?
+            // create:CurrencyCollection
+            .store(&self.create)?;
+        Ok(())
+    }
+}
+
+impl<'de> CellDeserialize<'de> for ShardFeeCreated {
+    #[inline]
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        Ok(Self {
+            // fees:CurrencyCollection
+            fees: parser.parse()?,
+            // create:CurrencyCollection
+            create: parser.parse()?,
+        })
+    }
+}
+
+/// Validator signature of the previous block
+/// ```tlb
+/// sig_pair$_ node_id_short:bits256 sign:CryptoSignature = CryptoSignaturePair;
+///
+/// ed25519_signature#5 R:bits256 s:bits256 = CryptoSignatureSimple;
+/// _ CryptoSignatureSimple = CryptoSignature;
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CryptoSignaturePair {
+    pub node_id_short: [u8; 32],
+    /// ed25519 signature: `R || s`
+    pub sign: [u8; 64],
+}
+
+impl CryptoSignaturePair {
+    const ED25519_SIGNATURE_TAG: u8 = 0x5;
+}
+
+impl BitPack for CryptoSignaturePair {
+    #[inline]
+    fn pack<W>(&self, mut writer: W) -> Result<(), W::Error>
+    where
+        W: BitWriter,
+    {
+        writer
+            // node_id_short:bits256
+            .pack(self.node_id_short)?
+            // ed25519_signature#5
+            .pack_as::<_, NBits<4>>(Self::ED25519_SIGNATURE_TAG)?
+            // R:bits256 s:bits256
+            .pack(self.sign)?;
+        Ok(())
+    }
+}
+
+impl BitUnpack for CryptoSignaturePair {
+    #[inline]
+    fn unpack<R>(mut reader: R) -> Result<Self, R::Error>
+    where
+        R: BitReader,
+    {
+        // node_id_short:bits256
+        let node_id_short = reader.unpack()?;
+        let tag: u8 = reader.unpack_as::<_, NBits<4>>()?;
+        if tag != Self::ED25519_SIGNATURE_TAG {
+            return Err(Error::custom(format!(
+                "unknown CryptoSignature tag: {tag:#x}"
+            )));
+        }
+        Ok(Self {
+            node_id_short,
+            // R:bits256 s:bits256
+            sign: reader.unpack()?,
+        })
+    }
+}
@@ -0,0 +1,696 @@
+//! Collection of types related to [Block](https://docs.ton.org/develop/data-formats/block-layout)
+mod extra;
+mod msg_descr;
+mod shard;
+mod value_flow;
+
+pub use self::{extra::*, msg_descr::*, shard::*, value_flow::*};
+
+use chrono::{DateTime, Utc};
+use tlb::{
+    bits::{
+        de::{BitReader, BitReaderExt, BitUnpack},
+        r#as::NBits,
+        ser::{BitPack, BitWriter, BitWriterExt},
+    },
+    de::{args::CellDeserializeWithArgs, CellDeserialize, CellParser, CellParserError},
+    r#as::{Data, Ref},
+    ser::{CellBuilder, CellBuilderError, CellSerialize},
+    Cell, Error, ResultExt,
+};
+
+use crate::UnixTimestamp;
+
+/// [Block](https://docs.ton.org/develop/data-formats/block-layout#block)
+/// ```tlb
+/// block#11ef55aa global_id:int32
+/// info:^BlockInfo value_flow:^ValueFlow
+/// state_update:^(MERKLE_UPDATE ShardState)
+/// extra:^BlockExtra = Block;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Block {
+    pub global_id: i32,
+    pub info: BlockInfo,
+    pub value_flow: ValueFlow,
+    /// `MERKLE_UPDATE ShardState`
+    pub state_update: Cell,
+    pub extra: BlockExtra,
+}
+
+impl Block {
+    const TAG: u32 = 0x11ef55aa;
+}
+
+impl CellSerialize for Block {
+    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        builder
+            // block#11ef55aa
+            .pack(Self::TAG)?
+            // global_id:int32
+            .pack(self.global_id)?
+            // info:^BlockInfo
+            .store_as::<_, Ref>(&self.info)
+            .context("info")?
+            // value_flow:^ValueFlow
+            .store_as::<_, Ref>(&self.value_flow)
+            .context("value_flow")?
+            // state_update:^(MERKLE_UPDATE ShardState)
+            .store_as::<_, Ref>(&self.state_update)?
+            // extra:^BlockExtra
+            .store_as::<_, Ref>(&self.extra)
+            .context("extra")?;
+        Ok(())
+    }
+}
+
+impl<'de> CellDeserialize<'de> for Block {
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        let tag: u32 = parser.unpack()?;
+        if tag != Self::TAG {
+            return Err(Error::custom(format!("unknown block tag: {tag:#0x}")));
+        }
+        Ok(Self {
+            // global_id:int32
+            global_id: parser.unpack()?,
+            // info:^BlockInfo
+            info: parser.parse_as::<_, Ref>().context("info")?,
+            // value_flow:^ValueFlow
+            value_flow: parser.parse_as::<_, Ref>().context("value_flow")?,
+            // state_update:^(MERKLE_UPDATE ShardState)
+            state_update: parser.parse_as::<_, Ref>().context("state_update")?,
+            // extra:^BlockExtra
+            extra: parser.parse_as::<_, Ref>().context("extra")?,
+        })
+    }
+}
+
+/// [BlockInfo](https://docs.ton.org/develop/data-formats/block-layout#blockinfo)
+///
+/// Flags `not_master`, `after_merge`, `vert_seqno_incr` and `flags` are
+/// not stored explicitly, since they are derived from [`master_ref`](BlockInfo::master_ref),
+/// [`prev_ref`](BlockInfo::prev_ref), [`prev_vert_ref`](BlockInfo::prev_vert_ref)
+/// and [`gen_software`](BlockInfo::gen_software) respectively.
+/// ```tlb
+/// block_info#9bc7a987 version:uint32
+/// not_master:(## 1)
+/// after_merge:(## 1) before_split:(## 1)
+/// after_split:(## 1)
+/// want_split:Bool want_merge:Bool
+/// key_block:Bool vert_seqno_incr:(## 1)
+/// flags:(## 8) { flags <= 1 }
+/// seq_no:# vert_seq_no:# { vert_seq_no >= vert_seqno_incr }
+/// { prev_seq_no:# } { ~prev_seq_no + 1 = seq_no }
+/// shard:ShardIdent gen_utime:uint32
+/// start_lt:uint64 end_lt:uint64
+/// gen_validator_list_hash_short:uint32
+/// gen_catchain_seqno:uint32
+/// min_ref_mc_seqno:uint32
+/// prev_key_block_seqno:uint32
+/// gen_software:flags . 0?GlobalVersion
+/// master_ref:not_master?^BlkMasterInfo
+/// prev_ref:^(BlkPrevInfo after_merge)
+/// prev_vert_ref:vert_seqno_incr?^(BlkPrevInfo 0)
+/// = BlockInfo;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockInfo {
+    pub version: u32,
+    pub before_split: bool,
+    pub after_split: bool,
+    pub want_split: bool,
+    pub want_merge: bool,
+    pub key_block: bool,
+    pub seq_no: u32,
+    pub vert_seq_no: u32,
+    pub shard: ShardIdent,
+    pub gen_utime: DateTime<Utc>,
+    pub start_lt: u64,
+    pub end_lt: u64,
+    pub gen_validator_list_hash_short: u32,
+    pub gen_catchain_seqno: u32,
+    pub min_ref_mc_seqno: u32,
+    pub prev_key_block_seqno: u32,
+    pub gen_software: Option<GlobalVersion>,
+    /// `None` for masterchain blocks
+    pub master_ref: Option<ExtBlkRef>,
+    pub prev_ref: BlkPrevInfo,
+    pub prev_vert_ref: Option<ExtBlkRef>,
+}
+
+impl BlockInfo {
+    const TAG: u32 = 0x9bc7a987;
+
+    /// Returns whether this block belongs to masterchain
+    #[inline]
+    pub fn is_masterchain(&self) -> bool {
+        self.master_ref.is_none()
+    }
+
+    /// Returns whether this block is right after merge of two shards
+    #[inline]
+    pub fn after_merge(&self) -> bool {
+        matches!(self.prev_ref, BlkPrevInfo::AfterMerge { .. })
+    }
+}
+
+impl CellSerialize for BlockInfo {
+    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        builder
+            // block_info#9bc7a987
+            .pack(Self::TAG)?
+            // version:uint32
+            .pack(self.version)?
+            // not_master:(## 1)
+            .pack(self.master_ref.is_some())?
+            // after_merge:(## 1)
+            .pack(self.after_merge())?
+            // before_split:(## 1)
+            .pack(self.before_split)?
+            // after_split:(## 1)
+            .pack(self.after_split)?
+            // want_split:Bool
+            .pack(self.want_split)?
+            // want_merge:Bool
+            .pack(self.want_merge)?
+            // key_block:Bool
+            .pack(self.key_block)?
+            // vert_seqno_incr:(## 1)
+            .pack(self.prev_vert_ref.is_some())?
+            // flags:(## 8) { flags <= 1 }
+            .pack(self.gen_software.is_some() as u8)?
+            // seq_no:#
+            .pack(self.seq_no)?
+            // vert_seq_no:#
+            .pack(self.vert_seq_no)?
+            // shard:ShardIdent
+            .pack(self.shard)?
+            // gen_utime:uint32
+            .pack_as::<_, UnixTimestamp>(self.gen_utime)?
+            // start_lt:uint64
+            .pack(self.start_lt)?
+            // end_lt:uint64
+            .pack(self.end_lt)?
+            // gen_validator_list_hash_short:uint32
+            .pack(self.gen_validator_list_hash_short)?
+            // gen_catchain_seqno:uint32
+            .pack(self.gen_catchain_seqno)?
+            // min_ref_mc_seqno:uint32
+            .pack(self.min_ref_mc_seqno)?
+            // prev_key_block_seqno:uint32
+            .pack(self.prev_key_block_seqno)?;
+        if let Some(gen_software) = self.gen_software {
+            // gen_software:flags . 0?GlobalVersion
+            builder.pack(gen_software)?;
+        }
+        if let Some(master_ref) = self.master_ref {
+            // master_ref:not_master?^BlkMasterInfo
+            builder.store_as::<_, Ref<Data>>(master_ref)?;
+        }
+        // prev_ref:^(BlkPrevInfo after_merge)
+        builder.store_as::<_, Ref>(&self.prev_ref)?;
+        if let Some(prev_vert_ref) = self.prev_vert_ref {
+            // prev_vert_ref:vert_seqno_incr?^(BlkPrevInfo 0)
+            builder.store_as::<_, Ref<Data>>(prev_vert_ref)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'de> CellDeserialize<'de> for BlockInfo {
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        let tag: u32 = parser.unpack()?;
+        if tag != Self::TAG {
+            return Err(Error::custom(format!("unknown block_info tag: {tag:#0x}")));
+        }
+        // version:uint32
+        let version = parser.unpack()?;
+        // not_master:(## 1)
+        let not_master: bool = parser.unpack()?;
+        // after_merge:(## 1)
+        let after_merge: bool = parser.unpack()?;
+        // before_split:(## 1)
+        let before_split = parser.unpack()?;
+        // after_split:(## 1)
+        let after_split = parser.unpack()?;
+        // want_split:Bool
+        let want_split = parser.unpack()?;
+        // want_merge:Bool
+        let want_merge = parser.unpack()?;
+        // key_block:Bool
+        let key_block = parser.unpack()?;
+        // vert_seqno_incr:(## 1)
+        let vert_seqno_incr: bool = parser.unpack()?;
+        // flags:(## 8) { flags <= 1 }
+        let flags: u8 = parser.unpack()?;
+        if flags > 1 {
+            return Err(Error::custom(format!("flags <= 1, got: {flags}")));
+        }
+        Ok(Self {
+            version,
+            before_split,
+            after_split,
+            want_split,
+            want_merge,
+            key_block,
+            // seq_no:#
+            seq_no: parser.unpack()?,
+            // vert_seq_no:#
+            vert_seq_no: parser.unpack()?,
+            // shard:ShardIdent
+            shard: parser.unpack().context("shard")?,
+            // gen_utime:uint32
+            gen_utime: parser.unpack_as::<_, UnixTimestamp>()?,
+            // start_lt:uint64
+            start_lt: parser.unpack()?,
+            // end_lt:uint64
+            end_lt: parser.unpack()?,
+            // gen_validator_list_hash_short:uint32
+            gen_validator_list_hash_short: parser.unpack()?,
+            // gen_catchain_seqno:uint32
+            gen_catchain_seqno: parser.unpack()?,
+            // min_ref_mc_seqno:uint32
+            min_ref_mc_seqno: parser.unpack()?,
+            // prev_key_block_seqno:uint32
+            prev_key_block_seqno: parser.unpack()?,
+            // gen_software:flags . 0?GlobalVersion
+            gen_software: if flags & 1 == 1 {
+                Some(parser.unpack().context("gen_software")?)
+            } else {
+                None
+            },
+            // master_ref:not_master?^BlkMasterInfo
+            master_ref: if not_master {
+                Some(parser.parse_as::<_, Ref<Data>>().context("master_ref")?)
+            } else {
+                None
+            },
+            // prev_ref:^(BlkPrevInfo after_merge)
+            prev_ref: parser
+                .parse_as_with::<_, Ref>(after_merge)
+                .context("prev_ref")?,
+            // prev_vert_ref:vert_seqno_incr?^(BlkPrevInfo 0)
+            prev_vert_ref: if vert_seqno_incr {
+                Some(parser.parse_as::<_, Ref<Data>>().context("prev_vert_ref")?)
+            } else {
+                None
+            },
+        })
+    }
+}
+
+/// ```tlb
+/// shard_ident$00 shard_pfx_bits:(#<= 60)
+/// workchain_id:int32 shard_prefix:uint64 = ShardIdent;
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ShardIdent {
+    pub shard_pfx_bits: u8,
+    pub workchain_id: i32,
+    pub shard_prefix: u64,
+}
+
+impl BitPack for ShardIdent {
+    #[inline]
+    fn pack<W>(&self, mut writer: W) -> Result<(), W::Error>
+    where
+        W: BitWriter,
+    {
+        if self.shard_pfx_bits > 60 {
+            return Err(Error::custom(format!(
+                "shard_pfx_bits <= 60, got: {}",
+                self.shard_pfx_bits
+            )));
+        }
+        writer
+            // shard_ident$00
+            .pack_as::<_, NBits<2>>(0b00)?
+            // shard_pfx_bits:(#<= 60)
+            .pack_as::<_, NBits<6>>(self.shard_pfx_bits)?
+            // workchain_id:int32
+            .pack(self.workchain_id)?
+            // shard_prefix:uint64
+            .pack(self.shard_prefix)?;
+        Ok(())
+    }
+}
+
+impl BitUnpack for ShardIdent {
+    #[inline]
+    fn unpack<R>(mut reader: R) -> Result<Self, R::Error>
+    where
+        R: BitReader,
+    {
+        let tag: u8 = reader.unpack_as::<_, NBits<2>>()?;
+        if tag != 0b00 {
+            return Err(Error::custom(format!("unknown shard_ident tag: {tag:#b}")));
+        }
+        // shard_pfx_bits:(#<= 60)
+        let shard_pfx_bits = reader.unpack_as::<_, NBits<6>>()?;
+        if shard_pfx_bits > 60 {
+            return Err(Error::custom(format!(
+                "shard_pfx_bits <= 60, got: {shard_pfx_bits}"
+            )));
+        }
+        Ok(Self {
+            shard_pfx_bits,
+            // workchain_id:int32
+            workchain_id: reader.unpack()?,
+            // shard_prefix:uint64
+            shard_prefix: reader.unpack()?,
+        })
+    }
+}
+
+/// ```tlb
+/// capabilities#c4 version:uint32 capabilities:uint64 = GlobalVersion;
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GlobalVersion {
+    pub version: u32,
+    pub capabilities: u64,
+}
+
+impl GlobalVersion {
+    const TAG: u8 = 0xc4;
+}
+
+impl BitPack for GlobalVersion {
+    #[inline]
+    fn pack<W>(&self, mut writer: W) -> Result<(), W::Error>
+    where
+        W: BitWriter,
+    {
+        writer
+            // capabilities#c4
+            .pack(Self::TAG)?
+            // version:uint32
+            .pack(self.version)?
+            // capabilities:uint64
+            .pack(self.capabilities)?;
+        Ok(())
+    }
+}
+
+impl BitUnpack for GlobalVersion {
+    #[inline]
+    fn unpack<R>(mut reader: R) -> Result<Self, R::Error>
+    where
+        R: BitReader,
+    {
+        let tag: u8 = reader.unpack()?;
+        if tag != Self::TAG {
+            return Err(Error::custom(format!(
+                "unknown capabilities tag: {tag:#0x}"
+            )));
+        }
+        Ok(Self {
+            // version:uint32
+            version: reader.unpack()?,
+            // capabilities:uint64
+            capabilities: reader.unpack()?,
+        })
+    }
+}
+
+/// Reference to an external block
+/// ```tlb
+/// ext_blk_ref$_ end_lt:uint64
+/// seq_no:uint32 root_hash:bits256 file_hash:bits256
+/// = ExtBlkRef;
+///
+/// master_info$_ master:ExtBlkRef = BlkMasterInfo;
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ExtBlkRef {
+    pub end_lt: u64,
+    pub seq_no: u32,
+    pub root_hash: [u8; 32],
+    pub file_hash: [u8; 32],
+}
+
+impl BitPack for ExtBlkRef {
+    #[inline]
+    fn pack<W>(&self, mut writer: W) -> Result<(), W::Error>
+    where
+        W: BitWriter,
+    {
+        writer
+            // end_lt:uint64
+            .pack(self.end_lt)?
+            // seq_no:uint32
+            .pack(self.seq_no)?
+            // root_hash:bits256
+            .pack(self.root_hash)?
+            // file_hash:bits256
+            .pack(self.file_hash)?;
+        Ok(())
+    }
+}
+
+impl BitUnpack for ExtBlkRef {
+    #[inline]
+    fn unpack<R>(mut reader: R) -> Result<Self, R::Error>
+    where
+        R: BitReader,
+    {
+        Ok(Self {
+            // end_lt:uint64
+            end_lt: reader.unpack()?,
+            // seq_no:uint32
+            seq_no: reader.unpack()?,
+            // root_hash:bits256
+            root_hash: reader.unpack()?,
+            // file_hash:bits256
+            file_hash: reader.unpack()?,
+        })
+    }
+}
+
+/// ```tlb
+/// prev_blk_info$_ prev:ExtBlkRef = BlkPrevInfo 0;
+/// prev_blks_info$_ prev1:^ExtBlkRef prev2:^ExtBlkRef = BlkPrevInfo 1;
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BlkPrevInfo {
+    /// ```tlb
+    /// prev_blk_info$_ prev:ExtBlkRef = BlkPrevInfo 0;
+    /// ```
+    Prev(ExtBlkRef),
+    /// ```tlb
+    /// prev_blks_info$_ prev1:^ExtBlkRef prev2:^ExtBlkRef = BlkPrevInfo 1;
+    /// ```
+    AfterMerge { prev1: ExtBlkRef, prev2: ExtBlkRef },
+}
+
+impl CellSerialize for BlkPrevInfo {
+    #[inline]
+    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        match self {
+            Self::Prev(prev) => builder
+                // prev:ExtBlkRef
+                .pack(prev)?,
+            Self::AfterMerge { prev1, prev2 } => builder
+                // prev1:^ExtBlkRef
+                .store_as::<_, Ref<Data>>(prev1)?
+                // prev2:^ExtBlkRef
+                .store_as::<_, Ref<Data>>(prev2)?,
+        };
+        Ok(())
+    }
+}
+
+impl<'de> CellDeserializeWithArgs<'de> for BlkPrevInfo {
+    /// after_merge
+    type Args = bool;
+
+    #[inline]
+    fn parse_with(
+        parser: &mut CellParser<'de>,
+        after_merge: Self::Args,
+    ) -> Result<Self, CellParserError<'de>> {
+        Ok(match after_merge {
+            false => Self::Prev(
+                // prev:ExtBlkRef
+                parser.unpack()?,
+            ),
+            true => Self::AfterMerge {
+                // prev1:^ExtBlkRef
+                prev1: parser.parse_as::<_, Ref<Data>>().context("prev1")?,
+                // prev2:^ExtBlkRef
+                prev2: parser.parse_as::<_, Ref<Data>>().context("prev2")?,
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tlb::{
+        bits::bitvec::{bits, order::Msb0, view::AsBits},
+        ser::CellSerializeExt,
+    };
+
+    use crate::{bin_tree::BinTree, hashmap::HashmapE};
+
+    use super::*;
+
+    fn given_ext_blk_ref(seq_no: u32) -> ExtBlkRef {
+        ExtBlkRef {
+            end_lt: 1_000 * seq_no as u64,
+            seq_no,
+            root_hash: [seq_no as u8; 32],
+            file_hash: [!seq_no as u8; 32],
+        }
+    }
+
+    fn given_shard_descr(seq_no: u32) -> ShardDescr {
+        ShardDescr {
+            seq_no,
+            reg_mc_seqno: 10,
+            start_lt: 100,
+            end_lt: 200,
+            root_hash: [1; 32],
+            file_hash: [2; 32],
+            before_split: false,
+            before_merge: false,
+            want_split: true,
+            want_merge: false,
+            nx_cc_updated: false,
+            next_catchain_seqno: 3,
+            next_validator_shard: 1 << 63,
+            min_ref_mc_seqno: 9,
+            gen_utime: DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+            split_merge_at: FutureSplitMerge::Split {
+                split_utime: DateTime::from_timestamp(1_700_000_100, 0).unwrap(),
+                interval: 60,
+            },
+            fees_collected: Default::default(),
+            funds_created: Default::default(),
+        }
+    }
+
+    #[test]
+    fn masterchain_block_serde() {
+        let mut shards = BinTree::Leaf(given_shard_descr(1));
+        shards
+            .split(bits![u8, Msb0;], |d| {
+                [
+                    given_shard_descr(d.seq_no + 1),
+                    given_shard_descr(d.seq_no + 2),
+                ]
+            })
+            .unwrap();
+        let mut shard_hashes = ShardHashes::default();
+        shard_hashes
+            .0
+            .insert(0i32.to_be_bytes().as_bits::<Msb0>(), shards)
+            .unwrap();
+
+        let block = Block {
+            global_id: -239,
+            info: BlockInfo {
+                version: 0,
+                before_split: false,
+                after_split: false,
+                want_split: false,
+                want_merge: true,
+                key_block: true,
+                seq_no: 42,
+                vert_seq_no: 1,
+                shard: ShardIdent {
+                    shard_pfx_bits: 0,
+                    workchain_id: -1,
+                    shard_prefix: 1 << 63,
+                },
+                gen_utime: DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+                start_lt: 41_000,
+                end_lt: 41_005,
+                gen_validator_list_hash_short: 0xdeadbeef,
+                gen_catchain_seqno: 7,
+                min_ref_mc_seqno: 40,
+                prev_key_block_seqno: 30,
+                gen_software: Some(GlobalVersion {
+                    version: 9,
+                    capabilities: 0x7ee,
+                }),
+                master_ref: None,
+                prev_ref: BlkPrevInfo::Prev(given_ext_blk_ref(41)),
+                prev_vert_ref: None,
+            },
+            value_flow: ValueFlow {
+                burned: Some(Default::default()),
+                ..Default::default()
+            },
+            state_update: Cell::default(),
+            extra: BlockExtra {
+                in_msg_descr: Default::default(),
+                out_msg_descr: Default::default(),
+                account_blocks: Cell::default(),
+                rand_seed: [3; 32],
+                created_by: [4; 32],
+                custom: Some(McBlockExtra {
+                    shard_hashes,
+                    shard_fees: Default::default(),
+                    prev_blk_signatures: HashmapE::Empty,
+                    recover_create_msg: None,
+                    mint_msg: None,
+                    config: None,
+                }),
+            },
+        };
+
+        let cell = block.to_cell().unwrap();
+        let got: Block = cell.parse_fully().unwrap();
+        assert_eq!(got, block);
+
+        let mc_extra = got.extra.custom.unwrap();
+        assert_eq!(
+            mc_extra
+                .shard_hashes
+                .get(0, bits![u8, Msb0; 1, 0, 1])
+                .map(|d| d.seq_no),
+            Some(3),
+        );
+        assert!(mc_extra.shard_hashes.get(1, bits![u8, Msb0; 1]).is_none());
+    }
+
+    #[test]
+    fn shardchain_block_info_serde() {
+        let info = BlockInfo {
+            version: 0,
+            before_split: false,
+            after_split: true,
+            want_split: false,
+            want_merge: false,
+            key_block: false,
+            seq_no: 100,
+            vert_seq_no: 2,
+            shard: ShardIdent {
+                shard_pfx_bits: 2,
+                workchain_id: 0,
+                shard_prefix: 0b11 << 62,
+            },
+            gen_utime: DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+            start_lt: 5,
+            end_lt: 6,
+            gen_validator_list_hash_short: 0,
+            gen_catchain_seqno: 0,
+            min_ref_mc_seqno: 1,
+            prev_key_block_seqno: 0,
+            gen_software: None,
+            master_ref: Some(given_ext_blk_ref(1)),
+            prev_ref: BlkPrevInfo::AfterMerge {
+                prev1: given_ext_blk_ref(98),
+                prev2: given_ext_blk_ref(99),
+            },
+            prev_vert_ref: Some(given_ext_blk_ref(50)),
+        };
+
+        let cell = info.to_cell().unwrap();
+        let got: BlockInfo = cell.parse_fully().unwrap();
+        assert_eq!(got, info);
+        assert!(!got.is_masterchain());
+        assert!(got.after_merge());
+    }
+}
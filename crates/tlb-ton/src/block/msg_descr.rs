@@ -0,0 +1,917 @@
+use num_bigint::BigUint;
+use tlb::{
+    bits::{
+        de::{BitReader, BitReaderExt, BitUnpack},
+        r#as::NBits,
+        ser::{BitPack, BitWriter, BitWriterExt},
+    },
+    de::{CellDeserialize, CellParser, CellParserError},
+    r#as::Ref,
+    ser::{CellBuilder, CellBuilderError, CellSerialize},
+    Cell, Error, ResultExt,
+};
+
+use crate::{
+    currency::{CurrencyCollection, Grams},
+    hashmap::aug::HashmapAugE,
+    MsgAddress,
+};
+
+/// Inbound messages of a block by their hashes
+/// ```tlb
+/// _ (HashmapAugE 256 InMsg ImportFees) = InMsgDescr;
+/// ```
+pub type InMsgDescr = HashmapAugE<InMsg, ImportFees>;
+
+/// Outbound messages of a block by their hashes
+/// ```tlb
+/// _ (HashmapAugE 256 OutMsg CurrencyCollection) = OutMsgDescr;
+/// ```
+pub type OutMsgDescr = HashmapAugE<OutMsg, CurrencyCollection>;
+
+/// ```tlb
+/// import_fees$_ fees_collected:Grams
+/// value_imported:CurrencyCollection = ImportFees;
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportFees {
+    pub fees_collected: BigUint,
+    pub value_imported: CurrencyCollection,
+}
+
+impl CellSerialize for ImportFees {
+    #[inline]
+    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        builder
+            // fees_collected:Grams
+            .pack_as::<_, &Grams>(&self.fees_collected)?
+            // value_imported:CurrencyCollection
+            .store(&self.value_imported)?;
+        Ok(())
+    }
+}
+
+impl<'de> CellDeserialize<'de> for ImportFees {
+    #[inline]
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        Ok(Self {
+            // fees_collected:Grams
+            fees_collected: parser.unpack_as::<_, Grams>()?,
+            // value_imported:CurrencyCollection
+            value_imported: parser.parse()?,
+        })
+    }
+}
+
+/// Inbound message of a block.
+///
+/// Both `msg` and `transaction` are kept as raw cells, so that they can be
+/// parsed on demand into [`Message`](crate::message::Message) and
+/// [`Transaction`](crate::transaction::Transaction) respectively.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InMsg {
+    /// ```tlb
+    /// msg_import_ext$000 msg:^(Message Any) transaction:^Transaction = InMsg;
+    /// ```
+    External { msg: Cell, transaction: Cell },
+    /// ```tlb
+    /// msg_import_ihr$010 msg:^(Message Any) transaction:^Transaction
+    /// ihr_fee:Grams proof_created:^Cell = InMsg;
+    /// ```
+    Ihr {
+        msg: Cell,
+        transaction: Cell,
+        ihr_fee: BigUint,
+        proof_created: Cell,
+    },
+    /// ```tlb
+    /// msg_import_imm$011 in_msg:^MsgEnvelope
+    /// transaction:^Transaction fwd_fee:Grams = InMsg;
+    /// ```
+    Immediate {
+        in_msg: MsgEnvelope,
+        transaction: Cell,
+        fwd_fee: BigUint,
+    },
+    /// ```tlb
+    /// msg_import_fin$100 in_msg:^MsgEnvelope
+    /// transaction:^Transaction fwd_fee:Grams = InMsg;
+    /// ```
+    Final {
+        in_msg: MsgEnvelope,
+        transaction: Cell,
+        fwd_fee: BigUint,
+    },
+    /// ```tlb
+    /// msg_import_tr$101 in_msg:^MsgEnvelope out_msg:^MsgEnvelope
+    /// transit_fee:Grams = InMsg;
+    /// ```
+    Transit {
+        in_msg: MsgEnvelope,
+        out_msg: MsgEnvelope,
+        transit_fee: BigUint,
+    },
+    /// ```tlb
+    /// msg_discard_fin$110 in_msg:^MsgEnvelope transaction_id:uint64
+    /// fwd_fee:Grams = InMsg;
+    /// ```
+    DiscardFinal {
+        in_msg: MsgEnvelope,
+        transaction_id: u64,
+        fwd_fee: BigUint,
+    },
+    /// ```tlb
+    /// msg_discard_tr$111 in_msg:^MsgEnvelope transaction_id:uint64
+    /// fwd_fee:Grams proof_delivered:^Cell = InMsg;
+    /// ```
+    DiscardTransit {
+        in_msg: MsgEnvelope,
+        transaction_id: u64,
+        fwd_fee: BigUint,
+        proof_delivered: Cell,
+    },
+    /// ```tlb
+    /// msg_import_deferred_fin$00100 in_msg:^MsgEnvelope
+    /// transaction:^Transaction fwd_fee:Grams = InMsg;
+    /// ```
+    DeferredFinal {
+        in_msg: MsgEnvelope,
+        transaction: Cell,
+        fwd_fee: BigUint,
+    },
+    /// ```tlb
+    /// msg_import_deferred_tr$00101 in_msg:^MsgEnvelope out_msg:^MsgEnvelope = InMsg;
+    /// ```
+    DeferredTransit {
+        in_msg: MsgEnvelope,
+        out_msg: MsgEnvelope,
+    },
+}
+
+impl CellSerialize for InMsg {
+    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        match self {
+            Self::External { msg, transaction } => builder
+                // msg_import_ext$000
+                .pack_as::<_, NBits<3>>(0b000)?
+                // msg:^(Message Any)
+                .store_as::<_, Ref>(msg)?
+                // transaction:^Transaction
+                .store_as::<_, Ref>(transaction)?,
+            Self::Ihr {
+                msg,
+                transaction,
+                ihr_fee,
+                proof_created,
+            } => builder
+                // msg_import_ihr$010
+                .pack_as::<_, NBits<3>>(0b010)?
+                // msg:^(Message Any)
+                .store_as::<_, Ref>(msg)?
+                // transaction:^Transaction
+                .store_as::<_, Ref>(transaction)?
+                // ihr_fee:Grams
+                .pack_as::<_, &Grams>(ihr_fee)?
+                // proof_created:^Cell
+                .store_as::<_, Ref>(proof_created)?,
+            Self::Immediate {
+                in_msg,
+                transaction,
+                fwd_fee,
+            } => builder
+                // msg_import_imm$011
+                .pack_as::<_, NBits<3>>(0b011)?
+                // in_msg:^MsgEnvelope
+                .store_as::<_, Ref>(in_msg)?
+                // transaction:^Transaction
+                .store_as::<_, Ref>(transaction)?
+                // fwd_fee:Grams
+                .pack_as::<_, &Grams>(fwd_fee)?,
+            Self::Final {
+                in_msg,
+                transaction,
+                fwd_fee,
+            } => builder
+                // msg_import_fin$100
+                .pack_as::<_, NBits<3>>(0b100)?
+                // in_msg:^MsgEnvelope
+                .store_as::<_, Ref>(in_msg)?
+                // transaction:^Transaction
+                .store_as::<_, Ref>(transaction)?
+                // fwd_fee:Grams
+                .pack_as::<_, &Grams>(fwd_fee)?,
+            Self::Transit {
+                in_msg,
+                out_msg,
+                transit_fee,
+            } => builder
+                // msg_import_tr$101
+                .pack_as::<_, NBits<3>>(0b101)?
+                // in_msg:^MsgEnvelope
+                .store_as::<_, Ref>(in_msg)?
+                // out_msg:^MsgEnvelope
+                .store_as::<_, Ref>(out_msg)?
+                // transit_fee:Grams
+                .pack_as::<_, &Grams>(transit_fee)?,
+            Self::DiscardFinal {
+                in_msg,
+                transaction_id,
+                fwd_fee,
+            } => builder
+                // msg_discard_fin$110
+                .pack_as::<_, NBits<3>>(0b110)?
+                // in_msg:^MsgEnvelope
+                .store_as::<_, Ref>(in_msg)?
+                // transaction_id:uint64
+                .pack(transaction_id)?
+                // fwd_fee:Grams
+                .pack_as::<_, &Grams>(fwd_fee)?,
+            Self::DiscardTransit {
+                in_msg,
+                transaction_id,
+                fwd_fee,
+                proof_delivered,
+            } => builder
+                // msg_discard_tr$111
+                .pack_as::<_, NBits<3>>(0b111)?
+                // in_msg:^MsgEnvelope
+                .store_as::<_, Ref>(in_msg)?
+                // transaction_id:uint64
+                .pack(transaction_id)?
+                // fwd_fee:Grams
+                .pack_as::<_, &Grams>(fwd_fee)?
+                // proof_delivered:^Cell
+                .store_as::<_, Ref>(proof_delivered)?,
+            Self::DeferredFinal {
+                in_msg,
+                transaction,
+                fwd_fee,
+            } => builder
+                // msg_import_deferred_fin$00100
+                .pack_as::<_, NBits<5>>(0b00100)?
+                // in_msg:^MsgEnvelope
+                .store_as::<_, Ref>(in_msg)?
+                // transaction:^Transaction
+                .store_as::<_, Ref>(transaction)?
+                // fwd_fee:Grams
+                .pack_as::<_, &Grams>(fwd_fee)?,
+            Self::DeferredTransit { in_msg, out_msg } => builder
+                // msg_import_deferred_tr$00101
+                .pack_as::<_, NBits<5>>(0b00101)?
+                // in_msg:^MsgEnvelope
+                .store_as::<_, Ref>(in_msg)?
+                // out_msg:^MsgEnvelope
+                .store_as::<_, Ref>(out_msg)?,
+        };
+        Ok(())
+    }
+}
+
+impl<'de> CellDeserialize<'de> for InMsg {
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        Ok(match parser.unpack_as::<u8, NBits<3>>()? {
+            // msg_import_ext$000
+            0b000 => Self::External {
+                msg: parser.parse_as::<_, Ref>().context("msg")?,
+                transaction: parser.parse_as::<_, Ref>().context("transaction")?,
+            },
+            // msg_import_ihr$010
+            0b010 => Self::Ihr {
+                msg: parser.parse_as::<_, Ref>().context("msg")?,
+                transaction: parser.parse_as::<_, Ref>().context("transaction")?,
+                ihr_fee: parser.unpack_as::<_, Grams>()?,
+                proof_created: parser.parse_as::<_, Ref>().context("proof_created")?,
+            },
+            // msg_import_imm$011
+            0b011 => Self::Immediate {
+                in_msg: parser.parse_as::<_, Ref>().context("in_msg")?,
+                transaction: parser.parse_as::<_, Ref>().context("transaction")?,
+                fwd_fee: parser.unpack_as::<_, Grams>()?,
+            },
+            // msg_import_fin$100
+            0b100 => Self::Final {
+                in_msg: parser.parse_as::<_, Ref>().context("in_msg")?,
+                transaction: parser.parse_as::<_, Ref>().context("transaction")?,
+                fwd_fee: parser.unpack_as::<_, Grams>()?,
+            },
+            // msg_import_tr$101
+            0b101 => Self::Transit {
+                in_msg: parser.parse_as::<_, Ref>().context("in_msg")?,
+                out_msg: parser.parse_as::<_, Ref>().context("out_msg")?,
+                transit_fee: parser.unpack_as::<_, Grams>()?,
+            },
+            // msg_discard_fin$110
+            0b110 => Self::DiscardFinal {
+                in_msg: parser.parse_as::<_, Ref>().context("in_msg")?,
+                transaction_id: parser.unpack()?,
+                fwd_fee: parser.unpack_as::<_, Grams>()?,
+            },
+            // msg_discard_tr$111
+            0b111 => Self::DiscardTransit {
+                in_msg: parser.parse_as::<_, Ref>().context("in_msg")?,
+                transaction_id: parser.unpack()?,
+                fwd_fee: parser.unpack_as::<_, Grams>()?,
+                proof_delivered: parser.parse_as::<_, Ref>().context("proof_delivered")?,
+            },
+            0b001 => match parser.unpack_as::<u8, NBits<2>>()? {
+                // msg_import_deferred_fin$00100
+                0b00 => Self::DeferredFinal {
+                    in_msg: parser.parse_as::<_, Ref>().context("in_msg")?,
+                    transaction: parser.parse_as::<_, Ref>().context("transaction")?,
+                    fwd_fee: parser.unpack_as::<_, Grams>()?,
+                },
+                // msg_import_deferred_tr$00101
+                0b01 => Self::DeferredTransit {
+                    in_msg: parser.parse_as::<_, Ref>().context("in_msg")?,
+                    out_msg: parser.parse_as::<_, Ref>().context("out_msg")?,
+                },
+                tag => return Err(Error::custom(format!("unknown InMsg tag: 0b001{tag:02b}"))),
+            },
+            _ => unreachable!(),
+        })
+    }
+}
+
+/// Outbound message of a block.
+///
+/// Both `msg` and `transaction` are kept as raw cells, see [`InMsg`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OutMsg {
+    /// ```tlb
+    /// msg_export_ext$000 msg:^(Message Any)
+    /// transaction:^Transaction = OutMsg;
+    /// ```
+    External { msg: Cell, transaction: Cell },
+    /// ```tlb
+    /// msg_export_imm$010 out_msg:^MsgEnvelope
+    /// transaction:^Transaction reimport:^InMsg = OutMsg;
+    /// ```
+    Immediate {
+        out_msg: MsgEnvelope,
+        transaction: Cell,
+        reimport: InMsg,
+    },
+    /// ```tlb
+    /// msg_export_new$001 out_msg:^MsgEnvelope
+    /// transaction:^Transaction = OutMsg;
+    /// ```
+    New {
+        out_msg: MsgEnvelope,
+        transaction: Cell,
+    },
+    /// ```tlb
+    /// msg_export_tr$011 out_msg:^MsgEnvelope
+    /// imported:^InMsg = OutMsg;
+    /// ```
+    Transit {
+        out_msg: MsgEnvelope,
+        imported: InMsg,
+    },
+    /// ```tlb
+    /// msg_export_deq$1100 out_msg:^MsgEnvelope
+    /// import_block_lt:uint63 = OutMsg;
+    /// ```
+    Dequeue {
+        out_msg: MsgEnvelope,
+        import_block_lt: u64,
+    },
+    /// ```tlb
+    /// msg_export_deq_short$1101 msg_env_hash:bits256
+    /// next_workchain:int32 next_addr_pfx:uint64
+    /// import_block_lt:uint64 = OutMsg;
+    /// ```
+    DequeueShort {
+        msg_env_hash: [u8; 32],
+        next_workchain: i32,
+        next_addr_pfx: u64,
+        import_block_lt: u64,
+    },
+    /// ```tlb
+    /// msg_export_tr_req$111 out_msg:^MsgEnvelope
+    /// imported:^InMsg = OutMsg;
+    /// ```
+    TransitRequired {
+        out_msg: MsgEnvelope,
+        imported: InMsg,
+    },
+    /// ```tlb
+    /// msg_export_deq_imm$100 out_msg:^MsgEnvelope
+    /// reimport:^InMsg = OutMsg;
+    /// ```
+    DequeueImmediate {
+        out_msg: MsgEnvelope,
+        reimport: InMsg,
+    },
+    /// ```tlb
+    /// msg_export_new_defer$10100 out_msg:^MsgEnvelope
+    /// transaction:^Transaction = OutMsg;
+    /// ```
+    NewDeferred {
+        out_msg: MsgEnvelope,
+        transaction: Cell,
+    },
+    /// ```tlb
+    /// msg_export_deferred_tr$10101 out_msg:^MsgEnvelope
+    /// imported:^InMsg = OutMsg;
+    /// ```
+    DeferredTransit {
+        out_msg: MsgEnvelope,
+        imported: InMsg,
+    },
+}
+
+impl CellSerialize for OutMsg {
+    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        match self {
+            Self::External { msg, transaction } => builder
+                // msg_export_ext$000
+                .pack_as::<_, NBits<3>>(0b000)?
+                // msg:^(Message Any)
+                .store_as::<_, Ref>(msg)?
+                // transaction:^Transaction
+                .store_as::<_, Ref>(transaction)?,
+            Self::Immediate {
+                out_msg,
+                transaction,
+                reimport,
+            } => builder
+                // msg_export_imm$010
+                .pack_as::<_, NBits<3>>(0b010)?
+                // out_msg:^MsgEnvelope
+                .store_as::<_, Ref>(out_msg)?
+                // transaction:^Transaction
+                .store_as::<_, Ref>(transaction)?
+                // reimport:^InMsg
+                .store_as::<_, Ref>(reimport)?,
+            Self::New {
+                out_msg,
+                transaction,
+            } => builder
+                // msg_export_new$001
+                .pack_as::<_, NBits<3>>(0b001)?
+                // out_msg:^MsgEnvelope
+                .store_as::<_, Ref>(out_msg)?
+                // transaction:^Transaction
+                .store_as::<_, Ref>(transaction)?,
+            Self::Transit { out_msg, imported } => builder
+                // msg_export_tr$011
+                .pack_as::<_, NBits<3>>(0b011)?
+                // out_msg:^MsgEnvelope
+                .store_as::<_, Ref>(out_msg)?
+                // imported:^InMsg
+                .store_as::<_, Ref>(imported)?,
+            Self::Dequeue {
+                out_msg,
+                import_block_lt,
+            } => builder
+                // msg_export_deq$1100
+                .pack_as::<_, NBits<4>>(0b1100)?
+                // out_msg:^MsgEnvelope
+                .store_as::<_, Ref>(out_msg)?
+                // import_block_lt:uint63
+                .pack_as::<_, NBits<63>>(*import_block_lt)?,
+            Self::DequeueShort {
+                msg_env_hash,
+                next_workchain,
+                next_addr_pfx,
+                import_block_lt,
+            } => builder
+                // msg_export_deq_short$1101
+                .pack_as::<_, NBits<4>>(0b1101)?
+                // msg_env_hash:bits256
+                .pack(msg_env_hash)?
+                // next_workchain:int32
+                .pack(next_workchain)?
+                // next_addr_pfx:uint64
+                .pack(next_addr_pfx)?
+                // import_block_lt:uint64
+                .pack(import_block_lt)?,
+            Self::TransitRequired { out_msg, imported } => builder
+                // msg_export_tr_req$111
+                .pack_as::<_, NBits<3>>(0b111)?
+                // out_msg:^MsgEnvelope
+                .store_as::<_, Ref>(out_msg)?
+                // imported:^InMsg
+                .store_as::<_, Ref>(imported)?,
+            Self::DequeueImmediate { out_msg, reimport } => builder
+                // msg_export_deq_imm$100
+                .pack_as::<_, NBits<3>>(0b100)?
+                // out_msg:^MsgEnvelope
+                .store_as::<_, Ref>(out_msg)?
+                // reimport:^InMsg
+                .store_as::<_, Ref>(reimport)?,
+            Self::NewDeferred {
+                out_msg,
+                transaction,
+            } => builder
+                // msg_export_new_defer$10100
+                .pack_as::<_, NBits<5>>(0b10100)?
+                // out_msg:^MsgEnvelope
+                .store_as::<_, Ref>(out_msg)?
+                // transaction:^Transaction
+                .store_as::<_, Ref>(transaction)?,
+            Self::DeferredTransit { out_msg, imported } => builder
+                // msg_export_deferred_tr$10101
+                .pack_as::<_, NBits<5>>(0b10101)?
+                // out_msg:^MsgEnvelope
+                .store_as::<_, Ref>(out_msg)?
+                // imported:^InMsg
+                .store_as::<_, Ref>(imported)?,
+        };
+        Ok(())
+    }
+}
+
+impl<'de> CellDeserialize<'de> for OutMsg {
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        Ok(match parser.unpack_as::<u8, NBits<3>>()? {
+            // msg_export_ext$000
+            0b000 => Self::External {
+                msg: parser.parse_as::<_, Ref>().context("msg")?,
+                transaction: parser.parse_as::<_, Ref>().context("transaction")?,
+            },
+            // msg_export_imm$010
+            0b010 => Self::Immediate {
+                out_msg: parser.parse_as::<_, Ref>().context("out_msg")?,
+                transaction: parser.parse_as::<_, Ref>().context("transaction")?,
+                reimport: parser.parse_as::<_, Ref>().context("reimport")?,
+            },
+            // msg_export_new$001
+            0b001 => Self::New {
+                out_msg: parser.parse_as::<_, Ref>().context("out_msg")?,
+                transaction: parser.parse_as::<_, Ref>().context("transaction")?,
+            },
+            // msg_export_tr$011
+            0b011 => Self::Transit {
+                out_msg: parser.parse_as::<_, Ref>().context("out_msg")?,
+                imported: parser.parse_as::<_, Ref>().context("imported")?,
+            },
+            0b110 => match parser.unpack()? {
+                // msg_export_deq$1100
+                false => Self::Dequeue {
+                    out_msg: parser.parse_as::<_, Ref>().context("out_msg")?,
+                    import_block_lt: parser.unpack_as::<_, NBits<63>>()?,
+                },
+                // msg_export_deq_short$1101
+                true => Self::DequeueShort {
+                    msg_env_hash: parser.unpack()?,
+                    next_workchain: parser.unpack()?,
+                    next_addr_pfx: parser.unpack()?,
+                    import_block_lt: parser.unpack()?,
+                },
+            },
+            // msg_export_tr_req$111
+            0b111 => Self::TransitRequired {
+                out_msg: parser.parse_as::<_, Ref>().context("out_msg")?,
+                imported: parser.parse_as::<_, Ref>().context("imported")?,
+            },
+            // msg_export_deq_imm$100
+            0b100 => Self::DequeueImmediate {
+                out_msg: parser.parse_as::<_, Ref>().context("out_msg")?,
+                reimport: parser.parse_as::<_, Ref>().context("reimport")?,
+            },
+            0b101 => match parser.unpack_as::<u8, NBits<2>>()? {
+                // msg_export_new_defer$10100
+                0b00 => Self::NewDeferred {
+                    out_msg: parser.parse_as::<_, Ref>().context("out_msg")?,
+                    transaction: parser.parse_as::<_, Ref>().context("transaction")?,
+                },
+                // msg_export_deferred_tr$10101
+                0b01 => Self::DeferredTransit {
+                    out_msg: parser.parse_as::<_, Ref>().context("out_msg")?,
+                    imported: parser.parse_as::<_, Ref>().context("imported")?,
+                },
+                tag => return Err(Error::custom(format!("unknown OutMsg tag: 0b101{tag:02b}"))),
+            },
+            _ => unreachable!(),
+        })
+    }
+}
+
+/// Message in transit between shards
+/// ```tlb
+/// msg_envelope#4 cur_addr:IntermediateAddress
+/// next_addr:IntermediateAddress fwd_fee_remaining:Grams
+/// msg:^(Message Any) = MsgEnvelope;
+///
+/// msg_envelope_v2#5 cur_addr:IntermediateAddress
+/// next_addr:IntermediateAddress fwd_fee_remaining:Grams
+/// msg:^(Message Any)
+/// emitted_lt:(Maybe uint64)
+/// metadata:(Maybe MsgMetadata) = MsgEnvelope;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MsgEnvelope {
+    pub cur_addr: IntermediateAddress,
+    pub next_addr: IntermediateAddress,
+    pub fwd_fee_remaining: BigUint,
+    /// `Message Any`
+    pub msg: Cell,
+    /// Present only in `msg_envelope_v2#5`
+    pub v2: Option<MsgEnvelopeV2>,
+}
+
+/// Fields specific to `msg_envelope_v2#5`, see [`MsgEnvelope`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MsgEnvelopeV2 {
+    pub emitted_lt: Option<u64>,
+    pub metadata: Option<MsgMetadata>,
+}
+
+impl MsgEnvelope {
+    const TAG: u8 = 0x4;
+    const V2_TAG: u8 = 0x5;
+}
+
+impl CellSerialize for MsgEnvelope {
+    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        builder
+            .pack_as::<_, NBits<4>>(if self.v2.is_some() {
+                Self::V2_TAG
+            } else {
+                Self::TAG
+            })?
+            // cur_addr:IntermediateAddress
+            .pack(self.cur_addr)?
+            // next_addr:IntermediateAddress
+            .pack(self.next_addr)?
+            // fwd_fee_remaining:Grams
+            .pack_as::<_, &Grams>(&self.fwd_fee_remaining)?
+            // msg:^(Message Any)
+            .store_as::<_, Ref>(&self.msg)?;
+        if let Some(v2) = &self.v2 {
+            builder
+                // emitted_lt:(Maybe uint64)
+                .pack(v2.emitted_lt)?
+                // metadata:(Maybe MsgMetadata)
+                .pack(v2.metadata)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'de> CellDeserialize<'de> for MsgEnvelope {
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        let is_v2 = match parser.unpack_as::<u8, NBits<4>>()? {
+            Self::TAG => false,
+            Self::V2_TAG => true,
+            tag => return Err(Error::custom(format!("unknown msg_envelope tag: {tag:#x}"))),
+        };
+        Ok(Self {
+            // cur_addr:IntermediateAddress
+            cur_addr: parser.unpack().context("cur_addr")?,
+            // next_addr:IntermediateAddress
+            next_addr: parser.unpack().context("next_addr")?,
+            // fwd_fee_remaining:Grams
+            fwd_fee_remaining: parser.unpack_as::<_, Grams>()?,
+            // msg:^(Message Any)
+            msg: parser.parse_as::<_, Ref>().context("msg")?,
+            v2: if is_v2 {
+                Some(MsgEnvelopeV2 {
+                    // emitted_lt:(Maybe uint64)
+                    emitted_lt: parser.unpack()?,
+                    // metadata:(Maybe MsgMetadata)
+                    metadata: parser.unpack().context("metadata")?,
+                })
+            } else {
+                None
+            },
+        })
+    }
+}
+
+/// ```tlb
+/// msg_metadata#0 depth:uint32 initiator_addr:MsgAddressInt
+/// initiator_lt:uint64 = MsgMetadata;
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MsgMetadata {
+    pub depth: u32,
+    pub initiator_addr: MsgAddress,
+    pub initiator_lt: u64,
+}
+
+impl BitPack for MsgMetadata {
+    #[inline]
+    fn pack<W>(&self, mut writer: W) -> Result<(), W::Error>
+    where
+        W: BitWriter,
+    {
+        writer
+            // msg_metadata#0
+            .pack_as::<_, NBits<4>>(0u8)?
+            // depth:uint32
+            .pack(self.depth)?
+            // initiator_addr:MsgAddressInt
+            .pack(self.initiator_addr)?
+            // initiator_lt:uint64
+            .pack(self.initiator_lt)?;
+        Ok(())
+    }
+}
+
+impl BitUnpack for MsgMetadata {
+    #[inline]
+    fn unpack<R>(mut reader: R) -> Result<Self, R::Error>
+    where
+        R: BitReader,
+    {
+        let tag: u8 = reader.unpack_as::<_, NBits<4>>()?;
+        if tag != 0 {
+            return Err(Error::custom(format!("unknown msg_metadata tag: {tag:#x}")));
+        }
+        Ok(Self {
+            // depth:uint32
+            depth: reader.unpack()?,
+            // initiator_addr:MsgAddressInt
+            initiator_addr: reader.unpack()?,
+            // initiator_lt:uint64
+            initiator_lt: reader.unpack()?,
+        })
+    }
+}
+
+/// ```tlb
+/// interm_addr_regular$0 use_dest_bits:(#<= 96) = IntermediateAddress;
+/// interm_addr_simple$10 workchain_id:int8 addr_pfx:uint64 = IntermediateAddress;
+/// interm_addr_ext$11 workchain_id:int32 addr_pfx:uint64 = IntermediateAddress;
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IntermediateAddress {
+    /// ```tlb
+    /// interm_addr_regular$0 use_dest_bits:(#<= 96) = IntermediateAddress;
+    /// ```
+    Regular { use_dest_bits: u8 },
+    /// ```tlb
+    /// interm_addr_simple$10 workchain_id:int8 addr_pfx:uint64 = IntermediateAddress;
+    /// ```
+    Simple { workchain_id: i8, addr_pfx: u64 },
+    /// ```tlb
+    /// interm_addr_ext$11 workchain_id:int32 addr_pfx:uint64 = IntermediateAddress;
+    /// ```
+    Ext { workchain_id: i32, addr_pfx: u64 },
+}
+
+impl BitPack for IntermediateAddress {
+    #[inline]
+    fn pack<W>(&self, mut writer: W) -> Result<(), W::Error>
+    where
+        W: BitWriter,
+    {
+        match self {
+            Self::Regular { use_dest_bits } => {
+                if *use_dest_bits > 96 {
+                    return Err(Error::custom(format!(
+                        "use_dest_bits <= 96, got: {use_dest_bits}"
+                    )));
+                }
+                writer
+                    // interm_addr_regular$0
+                    .pack(false)?
+                    // use_dest_bits:(#<= 96)
+                    .pack_as::<_, NBits<7>>(*use_dest_bits)?
+            }
+            Self::Simple {
+                workchain_id,
+                addr_pfx,
+            } => writer
+                // interm_addr_simple$10
+                .pack_as::<_, NBits<2>>(0b10)?
+                // workchain_id:int8
+                .pack(workchain_id)?
+                // addr_pfx:uint64
+                .pack(addr_pfx)?,
+            Self::Ext {
+                workchain_id,
+                addr_pfx,
+            } => writer
+                // interm_addr_ext$11
+                .pack_as::<_, NBits<2>>(0b11)?
+                // workchain_id:int32
+                .pack(workchain_id)?
+                // addr_pfx:uint64
+                .pack(addr_pfx)?,
+        };
+        Ok(())
+    }
+}
+
+impl BitUnpack for IntermediateAddress {
+    #[inline]
+    fn unpack<R>(mut reader: R) -> Result<Self, R::Error>
+    where
+        R: BitReader,
+    {
+        // interm_addr_regular$0
+        if !reader.unpack::<bool>()? {
+            // use_dest_bits:(#<= 96)
+            let use_dest_bits = reader.unpack_as::<_, NBits<7>>()?;
+            if use_dest_bits > 96 {
+                return Err(Error::custom(format!(
+                    "use_dest_bits <= 96, got: {use_dest_bits}"
+                )));
+            }
+            return Ok(Self::Regular { use_dest_bits });
+        }
+        Ok(match reader.unpack()? {
+            // interm_addr_simple$10
+            false => Self::Simple {
+                workchain_id: reader.unpack()?,
+                addr_pfx: reader.unpack()?,
+            },
+            // interm_addr_ext$11
+            true => Self::Ext {
+                workchain_id: reader.unpack()?,
+                addr_pfx: reader.unpack()?,
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tlb::ser::CellSerializeExt;
+
+    use super::*;
+
+    fn given_envelope(v2: bool) -> MsgEnvelope {
+        MsgEnvelope {
+            cur_addr: IntermediateAddress::Regular { use_dest_bits: 0 },
+            next_addr: IntermediateAddress::Simple {
+                workchain_id: -1,
+                addr_pfx: 1 << 63,
+            },
+            fwd_fee_remaining: 1_000u32.into(),
+            msg: Cell::default(),
+            v2: v2.then_some(MsgEnvelopeV2 {
+                emitted_lt: Some(123),
+                metadata: Some(MsgMetadata {
+                    depth: 1,
+                    initiator_addr: MsgAddress::NULL,
+                    initiator_lt: 100,
+                }),
+            }),
+        }
+    }
+
+    #[test]
+    fn in_msg_serde() {
+        for msg in [
+            InMsg::External {
+                msg: Cell::default(),
+                transaction: Cell::default(),
+            },
+            InMsg::Final {
+                in_msg: given_envelope(false),
+                transaction: Cell::default(),
+                fwd_fee: 10u32.into(),
+            },
+            InMsg::DiscardTransit {
+                in_msg: given_envelope(true),
+                transaction_id: 5,
+                fwd_fee: 10u32.into(),
+                proof_delivered: Cell::default(),
+            },
+            InMsg::DeferredTransit {
+                in_msg: given_envelope(true),
+                out_msg: given_envelope(false),
+            },
+        ] {
+            let cell = msg.to_cell().unwrap();
+            let got: InMsg = cell.parse_fully().unwrap();
+            assert_eq!(got, msg);
+        }
+    }
+
+    #[test]
+    fn out_msg_serde() {
+        for msg in [
+            OutMsg::New {
+                out_msg: given_envelope(false),
+                transaction: Cell::default(),
+            },
+            OutMsg::Dequeue {
+                out_msg: given_envelope(false),
+                import_block_lt: (1 << 63) - 1,
+            },
+            OutMsg::DequeueShort {
+                msg_env_hash: [7; 32],
+                next_workchain: 0,
+                next_addr_pfx: 1 << 63,
+                import_block_lt: u64::MAX,
+            },
+            OutMsg::DeferredTransit {
+                out_msg: given_envelope(true),
+                imported: InMsg::DeferredFinal {
+                    in_msg: given_envelope(true),
+                    transaction: Cell::default(),
+                    fwd_fee: 1u32.into(),
+                },
+            },
+        ] {
+            let cell = msg.to_cell().unwrap();
+            let got: OutMsg = cell.parse_fully().unwrap();
+            assert_eq!(got, msg);
+        }
+    }
+}
@@ -0,0 +1,307 @@
+use chrono::{DateTime, Utc};
+use tlb::{
+    bits::{
+        bitvec::{order::Msb0, slice::BitSlice, view::AsBits},
+        de::{BitReader, BitReaderExt, BitUnpack},
+        r#as::NBits,
+        ser::{BitPack, BitWriter, BitWriterExt},
+    },
+    de::{CellDeserialize, CellParser, CellParserError},
+    r#as::{NoArgs, Ref},
+    ser::{CellBuilder, CellBuilderError, CellSerialize},
+    Error, ResultExt,
+};
+
+use crate::{bin_tree::BinTree, currency::CurrencyCollection, hashmap::HashmapE, UnixTimestamp};
+
+/// Shard configuration of every workchain as seen by masterchain
+/// ```tlb
+/// _ (HashmapE 32 ^(BinTree ShardDescr)) = ShardHashes;
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ShardHashes(pub HashmapE<BinTree<ShardDescr>>);
+
+impl ShardHashes {
+    /// Returns shards of given workchain
+    #[inline]
+    pub fn workchain(&self, workchain_id: i32) -> Option<&BinTree<ShardDescr>> {
+        self.0.get(workchain_id.to_be_bytes().as_bits::<Msb0>())
+    }
+
+    /// Returns description of the shard in given workchain which is
+    /// responsible for given account prefix.
+    /// See [`BinTree::get()`].
+    #[inline]
+    pub fn get(
+        &self,
+        workchain_id: i32,
+        prefix: impl AsRef<BitSlice<u8, Msb0>>,
+    ) -> Option<&ShardDescr> {
+        self.workchain(workchain_id)?.get(prefix)
+    }
+}
+
+impl CellSerialize for ShardHashes {
+    #[inline]
+    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        builder.store_as_with::<_, &HashmapE<Ref<BinTree<NoArgs<_>>>, NoArgs<_>>>(
+            &self.0,
+            (32, (), ()),
+        )?;
+        Ok(())
+    }
+}
+
+impl<'de> CellDeserialize<'de> for ShardHashes {
+    #[inline]
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        Ok(Self(
+            parser.parse_as_with::<_, HashmapE<Ref<BinTree<NoArgs<_>>>, NoArgs<_>>>((
+                32,
+                (),
+                (),
+            ))?,
+        ))
+    }
+}
+
+/// Description of a shard in [`ShardHashes`].
+///
+/// Both `shard_descr#b` and `shard_descr_new#a` are accepted when parsing,
+/// while it is always serialized as `shard_descr_new#a`.
+/// ```tlb
+/// shard_descr#b seq_no:uint32 reg_mc_seqno:uint32
+/// start_lt:uint64 end_lt:uint64
+/// root_hash:bits256 file_hash:bits256
+/// before_split:Bool before_merge:Bool
+/// want_split:Bool want_merge:Bool
+/// nx_cc_updated:Bool flags:(## 3) { flags = 0 }
+/// next_catchain_seqno:uint32 next_validator_shard:uint64
+/// min_ref_mc_seqno:uint32 gen_utime:uint32
+/// split_merge_at:FutureSplitMerge
+/// fees_collected:CurrencyCollection
+/// funds_created:CurrencyCollection = ShardDescr;
+///
+/// shard_descr_new#a seq_no:uint32 reg_mc_seqno:uint32
+/// start_lt:uint64 end_lt:uint64
+/// root_hash:bits256 file_hash:bits256
+/// before_split:Bool before_merge:Bool
+/// want_split:Bool want_merge:Bool
+/// nx_cc_updated:Bool flags:(## 3) { flags = 0 }
+/// next_catchain_seqno:uint32 next_validator_shard:uint64
+/// min_ref_mc_seqno:uint32 gen_utime:uint32
+/// split_merge_at:FutureSplitMerge
+/// ^[ fees_collected:CurrencyCollection
+/// funds_created:CurrencyCollection ] = ShardDescr;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShardDescr {
+    pub seq_no: u32,
+    pub reg_mc_seqno: u32,
+    pub start_lt: u64,
+    pub end_lt: u64,
+    pub root_hash: [u8; 32],
+    pub file_hash: [u8; 32],
+    pub before_split: bool,
+    pub before_merge: bool,
+    pub want_split: bool,
+    pub want_merge: bool,
+    pub nx_cc_updated: bool,
+    pub next_catchain_seqno: u32,
+    pub next_validator_shard: u64,
+    pub min_ref_mc_seqno: u32,
+    pub gen_utime: DateTime<Utc>,
+    pub split_merge_at: FutureSplitMerge,
+    pub fees_collected: CurrencyCollection,
+    pub funds_created: CurrencyCollection,
+}
+
+impl ShardDescr {
+    const TAG: u8 = 0xb;
+    const NEW_TAG: u8 = 0xa;
+}
+
+impl CellSerialize for ShardDescr {
+    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        builder
+            // shard_descr_new#a
+            .pack_as::<_, NBits<4>>(Self::NEW_TAG)?
+            // seq_no:uint32
+            .pack(self.seq_no)?
+            // reg_mc_seqno:uint32
+            .pack(self.reg_mc_seqno)?
+            // start_lt:uint64
+            .pack(self.start_lt)?
+            // end_lt:uint64
+            .pack(self.end_lt)?
+            // root_hash:bits256
+            .pack(self.root_hash)?
+            // file_hash:bits256
+            .pack(self.file_hash)?
+            // before_split:Bool
+            .pack(self.before_split)?
+            // before_merge:Bool
+            .pack(self.before_merge)?
+            // want_split:Bool
+            .pack(self.want_split)?
+            // want_merge:Bool
+            .pack(self.want_merge)?
+            // nx_cc_updated:Bool
+            .pack(self.nx_cc_updated)?
+            // flags:(## 3) { flags = 0 }
+            .pack_as::<_, NBits<3>>(0u8)?
+            // next_catchain_seqno:uint32
+            .pack(self.next_catchain_seqno)?
+            // next_validator_shard:uint64
+            .pack(self.next_validator_shard)?
+            // min_ref_mc_seqno:uint32
+            .pack(self.min_ref_mc_seqno)?
+            // gen_utime:uint32
+            .pack_as::<_, UnixTimestamp>(self.gen_utime)?
+            // split_merge_at:FutureSplitMerge
+            .pack(self.split_merge_at)?
+            // ^[ fees_collected:CurrencyCollection
+            //    funds_created:CurrencyCollection ]
+            .store_as::<_, Ref>((&self.fees_collected, &self.funds_created))?;
+        Ok(())
+    }
+}
+
+impl<'de> CellDeserialize<'de> for ShardDescr {
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        let tag: u8 = parser.unpack_as::<_, NBits<4>>()?;
+        if ![Self::TAG, Self::NEW_TAG].contains(&tag) {
+            return Err(Error::custom(format!("unknown shard_descr tag: {tag:#x}")));
+        }
+        let seq_no = parser.unpack()?;
+        let reg_mc_seqno = parser.unpack()?;
+        let start_lt = parser.unpack()?;
+        let end_lt = parser.unpack()?;
+        let root_hash = parser.unpack()?;
+        let file_hash = parser.unpack()?;
+        let before_split = parser.unpack()?;
+        let before_merge = parser.unpack()?;
+        let want_split = parser.unpack()?;
+        let want_merge = parser.unpack()?;
+        let nx_cc_updated = parser.unpack()?;
+        // flags:(## 3) { flags = 0 }
+        let flags: u8 = parser.unpack_as::<_, NBits<3>>()?;
+        if flags != 0 {
+            return Err(Error::custom(format!("flags = 0, got: {flags}")));
+        }
+        let next_catchain_seqno = parser.unpack()?;
+        let next_validator_shard = parser.unpack()?;
+        let min_ref_mc_seqno = parser.unpack()?;
+        let gen_utime = parser.unpack_as::<_, UnixTimestamp>()?;
+        let split_merge_at = parser.unpack().context("split_merge_at")?;
+        let (fees_collected, funds_created) = if tag == Self::NEW_TAG {
+            // ^[ fees_collected:CurrencyCollection
+            //    funds_created:CurrencyCollection ]
+            parser.parse_as::<_, Ref>()?
+        } else {
+            // fees_collected:CurrencyCollection
+            // funds_created:CurrencyCollection
+            parser.parse()?
+        };
+        Ok(Self {
+            seq_no,
+            reg_mc_seqno,
+            start_lt,
+            end_lt,
+            root_hash,
+            file_hash,
+            before_split,
+            before_merge,
+            want_split,
+            want_merge,
+            nx_cc_updated,
+            next_catchain_seqno,
+            next_validator_shard,
+            min_ref_mc_seqno,
+            gen_utime,
+            split_merge_at,
+            fees_collected,
+            funds_created,
+        })
+    }
+}
+
+/// ```tlb
+/// fsm_none$0 = FutureSplitMerge;
+/// fsm_split$10 split_utime:uint32 interval:uint32 = FutureSplitMerge;
+/// fsm_merge$11 merge_utime:uint32 interval:uint32 = FutureSplitMerge;
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum FutureSplitMerge {
+    /// ```tlb
+    /// fsm_none$0 = FutureSplitMerge;
+    /// ```
+    #[default]
+    None,
+    /// ```tlb
+    /// fsm_split$10 split_utime:uint32 interval:uint32 = FutureSplitMerge;
+    /// ```
+    Split {
+        split_utime: DateTime<Utc>,
+        interval: u32,
+    },
+    /// ```tlb
+    /// fsm_merge$11 merge_utime:uint32 interval:uint32 = FutureSplitMerge;
+    /// ```
+    Merge {
+        merge_utime: DateTime<Utc>,
+        interval: u32,
+    },
+}
+
+impl BitPack for FutureSplitMerge {
+    #[inline]
+    fn pack<W>(&self, mut writer: W) -> Result<(), W::Error>
+    where
+        W: BitWriter,
+    {
+        match self {
+            Self::None => writer.pack(false)?,
+            Self::Split {
+                split_utime,
+                interval,
+            } => writer
+                .pack_as::<_, NBits<2>>(0b10)?
+                .pack_as::<_, UnixTimestamp>(*split_utime)?
+                .pack(interval)?,
+            Self::Merge {
+                merge_utime,
+                interval,
+            } => writer
+                .pack_as::<_, NBits<2>>(0b11)?
+                .pack_as::<_, UnixTimestamp>(*merge_utime)?
+                .pack(interval)?,
+        };
+        Ok(())
+    }
+}
+
+impl BitUnpack for FutureSplitMerge {
+    #[inline]
+    fn unpack<R>(mut reader: R) -> Result<Self, R::Error>
+    where
+        R: BitReader,
+    {
+        // fsm_none$0
+        if !reader.unpack::<bool>()? {
+            return Ok(Self::None);
+        }
+        Ok(match reader.unpack()? {
+            // fsm_split$10
+            false => Self::Split {
+                split_utime: reader.unpack_as::<_, UnixTimestamp>()?,
+                interval: reader.unpack()?,
+            },
+            // fsm_merge$11
+            true => Self::Merge {
+                merge_utime: reader.unpack_as::<_, UnixTimestamp>()?,
+                interval: reader.unpack()?,
+            },
+        })
+    }
+}
@@ -0,0 +1,126 @@
+use tlb::{
+    bits::{de::BitReaderExt, ser::BitWriterExt},
+    de::{CellDeserialize, CellParser, CellParserError},
+    r#as::Ref,
+    ser::{CellBuilder, CellBuilderError, CellSerialize},
+    Error, ResultExt,
+};
+
+use crate::currency::CurrencyCollection;
+
+/// Global balance changes within a [`Block`](super::Block)
+/// ```tlb
+/// value_flow#b8e48dfb ^[ from_prev_blk:CurrencyCollection
+/// to_next_blk:CurrencyCollection
+/// imported:CurrencyCollection
+/// exported:CurrencyCollection ]
+/// fees_collected:CurrencyCollection
+/// ^[
+/// fees_imported:CurrencyCollection
+/// recovered:CurrencyCollection
+/// created:CurrencyCollection
+/// minted:CurrencyCollection
+/// ] = ValueFlow;
+///
+/// value_flow_v2#3ebf98b7 ^[ from_prev_blk:CurrencyCollection
+/// to_next_blk:CurrencyCollection
+/// imported:CurrencyCollection
+/// exported:CurrencyCollection ]
+/// fees_collected:CurrencyCollection
+/// burned:CurrencyCollection
+/// ^[
+/// fees_imported:CurrencyCollection
+/// recovered:CurrencyCollection
+/// created:CurrencyCollection
+/// minted:CurrencyCollection
+/// ] = ValueFlow;
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValueFlow {
+    pub from_prev_blk: CurrencyCollection,
+    pub to_next_blk: CurrencyCollection,
+    pub imported: CurrencyCollection,
+    pub exported: CurrencyCollection,
+    pub fees_collected: CurrencyCollection,
+    /// Present only in `value_flow_v2`
+    pub burned: Option<CurrencyCollection>,
+    pub fees_imported: CurrencyCollection,
+    pub recovered: CurrencyCollection,
+    pub created: CurrencyCollection,
+    pub minted: CurrencyCollection,
+}
+
+impl ValueFlow {
+    const TAG: u32 = 0xb8e48dfb;
+    const V2_TAG: u32 = 0x3ebf98b7;
+}
+
+impl CellSerialize for ValueFlow {
+    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        builder
+            .pack(if self.burned.is_some() {
+                Self::V2_TAG
+            } else {
+                Self::TAG
+            })?
+            // ^[ from_prev_blk:CurrencyCollection to_next_blk:CurrencyCollection
+            //    imported:CurrencyCollection exported:CurrencyCollection ]
+            .store_as::<_, Ref>((
+                &self.from_prev_blk,
+                &self.to_next_blk,
+                &self.imported,
+                &self.exported,
+            ))?
+            // fees_collected:CurrencyCollection
+            .store(&self.fees_collected)?;
+        if let Some(burned) = &self.burned {
+            // burned:CurrencyCollection
+            builder.store(burned)?;
+        }
+        // ^[ fees_imported:CurrencyCollection recovered:CurrencyCollection
+        //    created:CurrencyCollection minted:CurrencyCollection ]
+        builder.store_as::<_, Ref>((
+            &self.fees_imported,
+            &self.recovered,
+            &self.created,
+            &self.minted,
+        ))?;
+        Ok(())
+    }
+}
+
+impl<'de> CellDeserialize<'de> for ValueFlow {
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        let is_v2 = match parser.unpack()? {
+            Self::TAG => false,
+            Self::V2_TAG => true,
+            tag => return Err(Error::custom(format!("unknown value_flow tag: {tag:#0x}"))),
+        };
+        // ^[ from_prev_blk:CurrencyCollection to_next_blk:CurrencyCollection
+        //    imported:CurrencyCollection exported:CurrencyCollection ]
+        let (from_prev_blk, to_next_blk, imported, exported) = parser.parse_as::<_, Ref>()?;
+        // fees_collected:CurrencyCollection
+        let fees_collected = parser.parse().context("fees_collected")?;
+        // burned:CurrencyCollection
+        let burned = if is_v2 {
+            Some(parser.parse().context("burned")?)
+        } else {
+            None
+        };
+        // ^[ fees_imported:CurrencyCollection recovered:CurrencyCollection
+        //    created:CurrencyCollection minted:CurrencyCollection ]
+        let (fees_imported, recovered, created, minted) = parser.parse_as::<_, Ref>()?;
+        Ok(Self {
+            from_prev_blk,
+            to_next_blk,
+            imported,
+            exported,
+            fees_collected,
+            burned,
+            fees_imported,
+            recovered,
+            created,
+            minted,
+        })
+    }
+}
@@ -14,7 +14,7 @@ use tlb::{
         r#as::{NBits, VarNBytes},
         ser::{args::BitPackWithArgs, BitWriter, BitWriterExt},
     },
-    Cell, Error, ResultExt, StringError,
+    Cell, CellHasher, Error, ResultExt, StringError,
 };
 
 /// Alias to [`BagOfCells`]
@@ -134,8 +134,8 @@ pub struct BagOfCellsArgs {
 ///   tot_cells_size:(##(off_bytes * 8))
 ///   index:(cells * ##(off_bytes * 8))
 ///   cell_data:(tot_cells_size * [ uint8 ])
-///   = BagOfCells;///
-
+///   = BagOfCells;
+///
 /// serialized_boc_idx_crc32c#acc3a728 size:(## 8) { size <= 4 }
 ///   off_bytes:(## 8) { off_bytes <= 8 }
 ///   cells:(##(size * 8))
@@ -144,8 +144,8 @@ pub struct BagOfCellsArgs {
 ///   tot_cells_size:(##(off_bytes * 8))
 ///   index:(cells * ##(off_bytes * 8))
 ///   cell_data:(tot_cells_size * [ uint8 ])
-///   crc32c:uint32 = BagOfCells;///
-
+///   crc32c:uint32 = BagOfCells;
+///
 /// serialized_boc#b5ee9c72 has_idx:(## 1) has_crc32c:(## 1)
 ///   has_cache_bits:(## 1) flags:(## 2) { flags = 0 }
 ///   size:(## 3) { size <= 4 }
@@ -197,10 +197,15 @@ impl BitPackWithArgs for BagOfCells {
         if !in_refs.is_empty() {
             return Err(Error::custom("reference cycle detected"));
         }
+        for (i, cell) in ordered_cells.iter().enumerate() {
+            cell.cell_type()
+                .map_err(|err| Error::custom(format!("[{i}]: {err}")))?;
+        }
 
+        let mut hasher = CellHasher::new();
         RawBagOfCells {
             cells: ordered_cells
-                .into_iter()
+                .iter()
                 .map(|cell| RawCell {
                     data: cell.data.clone(),
                     references: cell
@@ -208,7 +213,8 @@ impl BitPackWithArgs for BagOfCells {
                         .iter()
                         .map(|c| *indices.get(c).unwrap())
                         .collect(),
-                    level: cell.level(),
+                    level_mask: hasher.level_mask(cell),
+                    is_exotic: cell.is_exotic,
                 })
                 .collect(),
             roots: self
@@ -230,8 +236,8 @@ impl BitPackWithArgs for BagOfCells {
 ///   tot_cells_size:(##(off_bytes * 8))
 ///   index:(cells * ##(off_bytes * 8))
 ///   cell_data:(tot_cells_size * [ uint8 ])
-///   = BagOfCells;///
-
+///   = BagOfCells;
+///
 /// serialized_boc_idx_crc32c#acc3a728 size:(## 8) { size <= 4 }
 ///   off_bytes:(## 8) { off_bytes <= 8 }
 ///   cells:(##(size * 8))
@@ -240,8 +246,8 @@ impl BitPackWithArgs for BagOfCells {
 ///   tot_cells_size:(##(off_bytes * 8))
 ///   index:(cells * ##(off_bytes * 8))
 ///   cell_data:(tot_cells_size * [ uint8 ])
-///   crc32c:uint32 = BagOfCells;///
-
+///   crc32c:uint32 = BagOfCells;
+///
 /// serialized_boc#b5ee9c72 has_idx:(## 1) has_crc32c:(## 1)
 ///   has_cache_bits:(## 1) flags:(## 2) { flags = 0 }
 ///   size:(## 3) { size <= 4 }
@@ -265,24 +271,25 @@ impl BitUnpack for BagOfCells {
         let num_cells = raw.cells.len();
         let mut cells: Vec<Arc<Cell>> = Vec::new();
         for (i, raw_cell) in raw.cells.into_iter().enumerate().rev() {
-            cells.push(
-                Cell {
-                    data: raw_cell.data,
-                    references: raw_cell
-                        .references
-                        .into_iter()
-                        .map(|r| {
-                            if r <= i as u32 {
-                                return Err(Error::custom(format!(
-                                    "references to previous cells are not supported: [{i}] -> [{r}]"
-                                )));
-                            }
-                            Ok(cells[num_cells - 1 - r as usize].clone())
-                        })
-                        .collect::<Result<_, _>>()?,
-                }
-                .into(),
-            );
+            let cell = Cell {
+                is_exotic: raw_cell.is_exotic,
+                data: raw_cell.data,
+                references: raw_cell
+                    .references
+                    .into_iter()
+                    .map(|r| {
+                        if r <= i as u32 {
+                            return Err(Error::custom(format!(
+                                "references to previous cells are not supported: [{i}] -> [{r}]"
+                            )));
+                        }
+                        Ok(cells[num_cells - 1 - r as usize].clone())
+                    })
+                    .collect::<Result<_, _>>()?,
+            };
+            cell.cell_type()
+                .map_err(|err| Error::custom(format!("[{i}]: {err}")))?;
+            cells.push(cell.into());
         }
         Ok(BagOfCells {
             roots: raw
@@ -330,7 +337,7 @@ impl BitPackWithArgs for RawBagOfCells {
             return Err(Error::custom("only single root cell supported"));
         }
         let size_bits: u32 = 32 - (self.cells.len() as u32).leading_zeros();
-        let size_bytes: u32 = (size_bits + 7) / 8;
+        let size_bytes: u32 = size_bits.div_ceil(8);
 
         let mut tot_cells_size: u32 = 0;
         let mut index = Vec::<u32>::with_capacity(self.cells.len());
@@ -340,7 +347,7 @@ impl BitPackWithArgs for RawBagOfCells {
         }
 
         let off_bits: u32 = 32 - tot_cells_size.leading_zeros();
-        let off_bytes: u32 = (off_bits + 7) / 8;
+        let off_bytes: u32 = off_bits.div_ceil(8);
 
         let mut buffered = writer.as_mut().tee(BitVec::<u8, Msb0>::new());
         buffered
@@ -379,7 +386,7 @@ impl BitPackWithArgs for RawBagOfCells {
         }
 
         let buf = buffered.into_writer();
-        if buf.len() % 8 != 0 {
+        if !buf.len().is_multiple_of(8) {
             return Err(Error::custom("produced stream is not byte-aligned"));
         }
         // crc32c:has_crc32c?uint32
@@ -458,7 +465,7 @@ impl BitUnpack for RawBagOfCells {
             .context("cell_data")?;
 
         let buf = buffered.into_writer();
-        if buf.len() % 8 != 0 {
+        if !buf.len().is_multiple_of(8) {
             return Err(Error::custom("produced stream is not byte-aligned"));
         }
         if has_crc32c {
@@ -480,7 +487,8 @@ impl BitUnpack for RawBagOfCells {
 pub(crate) struct RawCell {
     pub data: BitVec<u8, Msb0>,
     pub references: Vec<u32>,
-    pub level: u8,
+    pub level_mask: u8,
+    pub is_exotic: bool,
 }
 
 impl BitUnpackWithArgs for RawCell {
@@ -492,14 +500,23 @@ impl BitUnpackWithArgs for RawCell {
         R: BitReader,
     {
         let refs_descriptor: u8 = reader.unpack()?;
-        let level: u8 = refs_descriptor >> 5;
-        let _is_exotic: bool = refs_descriptor >> 3 & 0b1 == 1;
+        let level_mask: u8 = refs_descriptor >> 5;
+        let with_hashes: bool = refs_descriptor >> 4 & 0b1 == 1;
+        let is_exotic: bool = refs_descriptor >> 3 & 0b1 == 1;
         let ref_num: usize = refs_descriptor as usize & 0b111;
 
         let bits_descriptor: u8 = reader.unpack()?;
         let num_bytes: usize = ((bits_descriptor >> 1) + (bits_descriptor & 1)) as usize;
         let full_bytes = (bits_descriptor & 1) == 0;
 
+        if with_hashes {
+            // hashes and depths are recalculated anyway
+            let hashes_len = (level_mask.count_ones() as usize + 1) * (256 + 16);
+            if reader.skip(hashes_len)? != hashes_len {
+                return Err(Error::custom("EOF"));
+            }
+        }
+
         let mut data: BitVec<u8, Msb0> = reader.unpack_with(num_bytes * 8)?;
         if !data.is_empty() && !full_bytes {
             let trailing_zeros = data.trailing_zeros();
@@ -517,7 +534,8 @@ impl BitUnpackWithArgs for RawCell {
         Ok(RawCell {
             data,
             references,
-            level,
+            level_mask,
+            is_exotic,
         })
     }
 }
@@ -530,14 +548,13 @@ impl BitPackWithArgs for RawCell {
     where
         W: BitWriter,
     {
-        let level: u8 = 0;
-        let is_exotic: u8 = 0;
-        let refs_descriptor: u8 = self.references.len() as u8 + is_exotic * 8 + level * 32;
+        let refs_descriptor: u8 =
+            self.references.len() as u8 + self.is_exotic as u8 * 8 + self.level_mask * 32;
         writer.pack(refs_descriptor)?;
 
         let padding_bits = self.data.len() % 8;
         let full_bytes = padding_bits == 0;
-        let data_bytes = (self.data.len() + 7) / 8;
+        let data_bytes = self.data.len().div_ceil(8);
         let bits_descriptor: u8 = data_bytes as u8 * 2 - if full_bytes { 0 } else { 1 }; // subtract 1 if the last byte is not full
         writer.pack(bits_descriptor)?;
 
@@ -555,7 +572,77 @@ impl BitPackWithArgs for RawCell {
 
 impl RawCell {
     fn size(&self, ref_size_bytes: u32) -> u32 {
-        let data_len: u32 = (self.data.len() as u32 + 7) / 8;
+        let data_len: u32 = (self.data.len() as u32).div_ceil(8);
         2 + data_len + self.references.len() as u32 * ref_size_bytes
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use tlb::{
+        bits::{de::unpack_fully, ser::pack_with},
+        r#as::{Data, Ref},
+        ser::{r#as::CellSerializeWrapAsExt, CellSerializeExt},
+    };
+
+    use super::*;
+
+    #[test]
+    fn exotic_cells_serde() {
+        let cell = (0x0000000F_u32.wrap_as::<Data>(), ().wrap_as::<Ref>())
+            .to_cell()
+            .unwrap();
+        let partial = Cell {
+            data: cell.data.clone(),
+            references: [cell.references[0].pruned_branch(1).unwrap().into()].into(),
+            is_exotic: false,
+        };
+        let proof: Arc<Cell> = Cell::merkle_proof(partial).into();
+
+        let packed = pack_with(
+            BagOfCells::from_root(proof.clone()),
+            BagOfCellsArgs {
+                has_idx: false,
+                has_crc32c: true,
+            },
+        )
+        .unwrap();
+        let unpacked: BagOfCells = unpack_fully(packed).unwrap();
+        let got = unpacked.single_root().unwrap();
+
+        assert_eq!(got, &proof);
+        assert_eq!(got.hash(), proof.hash());
+        assert_eq!(got.references[0].hash_at(0), cell.hash());
+    }
+
+    #[test]
+    fn invalid_exotic_cell() {
+        // pruned branch of level 1 without hashes and depths
+        let cell = Cell {
+            data: BitVec::from_vec(vec![0x01, 0x01]),
+            references: Vec::new(),
+            is_exotic: true,
+        };
+        let args = BagOfCellsArgs {
+            has_idx: false,
+            has_crc32c: true,
+        };
+        assert!(pack_with(BagOfCells::from_root(cell.clone()), args).is_err());
+
+        let packed = pack_with(
+            RawBagOfCells {
+                cells: [RawCell {
+                    data: cell.data,
+                    references: Vec::new(),
+                    level_mask: 1,
+                    is_exotic: true,
+                }]
+                .into(),
+                roots: [0].into(),
+            },
+            args,
+        )
+        .unwrap();
+        assert!(unpack_fully::<BagOfCells>(packed).is_err());
+    }
+}
@@ -0,0 +1,566 @@
+use std::collections::HashSet;
+
+use chrono::{DateTime, Utc};
+use num_bigint::BigUint;
+use tlb::{
+    bits::{
+        de::{BitReader, BitReaderExt, BitUnpack},
+        ser::{BitPack, BitWriter, BitWriterExt},
+    },
+    Cell, CellHasher, Error,
+};
+
+use crate::UnixTimestamp;
+
+/// Gas prices and limits, see [`ConfigParams::gas_prices()`](super::ConfigParams::gas_prices)
+/// ```tlb
+/// gas_prices#dd gas_price:uint64 gas_limit:uint64 gas_credit:uint64
+/// block_gas_limit:uint64 freeze_due_limit:uint64 delete_due_limit:uint64
+/// = GasLimitsPrices;
+///
+/// gas_prices_ext#de gas_price:uint64 gas_limit:uint64 special_gas_limit:uint64 gas_credit:uint64
+/// block_gas_limit:uint64 freeze_due_limit:uint64 delete_due_limit:uint64
+/// = GasLimitsPrices;
+///
+/// gas_flat_pfx#d1 flat_gas_limit:uint64 flat_gas_price:uint64 other:GasLimitsPrices
+/// = GasLimitsPrices;
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct GasLimitsPrices {
+    /// Present only in `gas_flat_pfx`
+    pub flat: Option<GasFlatPfx>,
+    /// Price of gas unit in nanotons multiplied by `2^16`
+    pub gas_price: u64,
+    pub gas_limit: u64,
+    /// Present only in `gas_prices_ext`
+    pub special_gas_limit: Option<u64>,
+    pub gas_credit: u64,
+    pub block_gas_limit: u64,
+    pub freeze_due_limit: u64,
+    pub delete_due_limit: u64,
+}
+
+impl GasLimitsPrices {
+    const TAG: u8 = 0xdd;
+    const EXT_TAG: u8 = 0xde;
+    const FLAT_PFX_TAG: u8 = 0xd1;
+
+    /// Converts given amount of gas to nanotons
+    #[inline]
+    pub fn compute_gas_fee(&self, gas_used: u64) -> BigUint {
+        let GasFlatPfx {
+            flat_gas_limit,
+            flat_gas_price,
+        } = self.flat.unwrap_or_default();
+        if gas_used <= flat_gas_limit {
+            return flat_gas_price.into();
+        }
+        BigUint::from(flat_gas_price)
+            + shr16_ceil(BigUint::from(self.gas_price) * (gas_used - flat_gas_limit))
+    }
+}
+
+impl BitPack for GasLimitsPrices {
+    fn pack<W>(&self, mut writer: W) -> Result<(), W::Error>
+    where
+        W: BitWriter,
+    {
+        if let Some(flat) = self.flat {
+            writer
+                // gas_flat_pfx#d1
+                .pack(Self::FLAT_PFX_TAG)?
+                .pack(flat)?;
+        }
+        writer
+            .pack(if self.special_gas_limit.is_some() {
+                Self::EXT_TAG
+            } else {
+                Self::TAG
+            })?
+            // gas_price:uint64
+            .pack(self.gas_price)?
+            // gas_limit:uint64
+            .pack(self.gas_limit)?;
+        if let Some(special_gas_limit) = self.special_gas_limit {
+            // special_gas_limit:uint64
+            writer.pack(special_gas_limit)?;
+        }
+        writer
+            // gas_credit:uint64
+            .pack(self.gas_credit)?
+            // block_gas_limit:uint64
+            .pack(self.block_gas_limit)?
+            // freeze_due_limit:uint64
+            .pack(self.freeze_due_limit)?
+            // delete_due_limit:uint64
+            .pack(self.delete_due_limit)?;
+        Ok(())
+    }
+}
+
+impl BitUnpack for GasLimitsPrices {
+    fn unpack<R>(mut reader: R) -> Result<Self, R::Error>
+    where
+        R: BitReader,
+    {
+        let mut tag: u8 = reader.unpack()?;
+        let flat = if tag == Self::FLAT_PFX_TAG {
+            let flat = reader.unpack()?;
+            tag = reader.unpack()?;
+            Some(flat)
+        } else {
+            None
+        };
+        let is_ext = match tag {
+            Self::TAG => false,
+            Self::EXT_TAG => true,
+            _ => return Err(Error::custom(format!("unknown gas_prices tag: {tag:#0x}"))),
+        };
+        Ok(Self {
+            flat,
+            // gas_price:uint64
+            gas_price: reader.unpack()?,
+            // gas_limit:uint64
+            gas_limit: reader.unpack()?,
+            // special_gas_limit:uint64
+            special_gas_limit: if is_ext { Some(reader.unpack()?) } else { None },
+            // gas_credit:uint64
+            gas_credit: reader.unpack()?,
+            // block_gas_limit:uint64
+            block_gas_limit: reader.unpack()?,
+            // freeze_due_limit:uint64
+            freeze_due_limit: reader.unpack()?,
+            // delete_due_limit:uint64
+            delete_due_limit: reader.unpack()?,
+        })
+    }
+}
+
+/// Flat part of [`GasLimitsPrices`]: first `flat_gas_limit` gas units
+/// cost `flat_gas_price` nanotons in total
+/// ```tlb
+/// gas_flat_pfx#d1 flat_gas_limit:uint64 flat_gas_price:uint64 other:GasLimitsPrices
+/// = GasLimitsPrices;
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct GasFlatPfx {
+    pub flat_gas_limit: u64,
+    pub flat_gas_price: u64,
+}
+
+impl BitPack for GasFlatPfx {
+    #[inline]
+    fn pack<W>(&self, mut writer: W) -> Result<(), W::Error>
+    where
+        W: BitWriter,
+    {
+        writer
+            // flat_gas_limit:uint64
+            .pack(self.flat_gas_limit)?
+            // flat_gas_price:uint64
+            .pack(self.flat_gas_price)?;
+        Ok(())
+    }
+}
+
+impl BitUnpack for GasFlatPfx {
+    #[inline]
+    fn unpack<R>(mut reader: R) -> Result<Self, R::Error>
+    where
+        R: BitReader,
+    {
+        Ok(Self {
+            // flat_gas_limit:uint64
+            flat_gas_limit: reader.unpack()?,
+            // flat_gas_price:uint64
+            flat_gas_price: reader.unpack()?,
+        })
+    }
+}
+
+/// Message forwarding prices, see [`ConfigParams::fwd_prices()`](super::ConfigParams::fwd_prices)
+/// ```tlb
+/// msg_forward_prices#ea lump_price:uint64 bit_price:uint64 cell_price:uint64
+/// ihr_price_factor:uint32 first_frac:uint16 next_frac:uint16 = MsgForwardPrices;
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct MsgForwardPrices {
+    pub lump_price: u64,
+    /// Price of a bit in nanotons multiplied by `2^16`
+    pub bit_price: u64,
+    /// Price of a cell in nanotons multiplied by `2^16`
+    pub cell_price: u64,
+    pub ihr_price_factor: u32,
+    /// Fraction of forward fee (multiplied by `2^16`) that goes to the
+    /// current validators
+    pub first_frac: u16,
+    pub next_frac: u16,
+}
+
+impl MsgForwardPrices {
+    const TAG: u8 = 0xea;
+
+    /// Computes total forward fee for a message, see
+    /// [`StorageStat::of_message()`]
+    #[inline]
+    pub fn compute_fwd_fee(&self, stat: StorageStat) -> BigUint {
+        BigUint::from(self.lump_price)
+            + shr16_ceil(
+                BigUint::from(self.bit_price) * stat.bits
+                    + BigUint::from(self.cell_price) * stat.cells,
+            )
+    }
+
+    /// Returns the part of total forward fee that is collected by validators
+    /// of the current shard
+    #[inline]
+    pub fn first_part(&self, fwd_fee: &BigUint) -> BigUint {
+        (fwd_fee * self.first_frac) >> 16
+    }
+
+    /// Returns the part of total forward fee that remains in
+    /// [`InternalMsgInfo::fwd_fee`](crate::message::InternalMsgInfo::fwd_fee)
+    #[inline]
+    pub fn remaining_fwd_fee(&self, fwd_fee: &BigUint) -> BigUint {
+        fwd_fee - self.first_part(fwd_fee)
+    }
+}
+
+impl BitPack for MsgForwardPrices {
+    #[inline]
+    fn pack<W>(&self, mut writer: W) -> Result<(), W::Error>
+    where
+        W: BitWriter,
+    {
+        writer
+            // msg_forward_prices#ea
+            .pack(Self::TAG)?
+            // lump_price:uint64
+            .pack(self.lump_price)?
+            // bit_price:uint64
+            .pack(self.bit_price)?
+            // cell_price:uint64
+            .pack(self.cell_price)?
+            // ihr_price_factor:uint32
+            .pack(self.ihr_price_factor)?
+            // first_frac:uint16
+            .pack(self.first_frac)?
+            // next_frac:uint16
+            .pack(self.next_frac)?;
+        Ok(())
+    }
+}
+
+impl BitUnpack for MsgForwardPrices {
+    #[inline]
+    fn unpack<R>(mut reader: R) -> Result<Self, R::Error>
+    where
+        R: BitReader,
+    {
+        let tag: u8 = reader.unpack()?;
+        if tag != Self::TAG {
+            return Err(Error::custom(format!(
+                "unknown msg_forward_prices tag: {tag:#0x}"
+            )));
+        }
+        Ok(Self {
+            // lump_price:uint64
+            lump_price: reader.unpack()?,
+            // bit_price:uint64
+            bit_price: reader.unpack()?,
+            // cell_price:uint64
+            cell_price: reader.unpack()?,
+            // ihr_price_factor:uint32
+            ihr_price_factor: reader.unpack()?,
+            // first_frac:uint16
+            first_frac: reader.unpack()?,
+            // next_frac:uint16
+            next_frac: reader.unpack()?,
+        })
+    }
+}
+
+/// Storage prices effective since `utime_since`,
+/// see [`ConfigParams::storage_prices()`](super::ConfigParams::storage_prices)
+/// ```tlb
+/// _#cc utime_since:uint32 bit_price_ps:uint64 cell_price_ps:uint64
+/// mc_bit_price_ps:uint64 mc_cell_price_ps:uint64 = StoragePrices;
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StoragePrices {
+    pub utime_since: DateTime<Utc>,
+    /// Price of storing a bit for a second in nanotons multiplied by `2^16`
+    pub bit_price_ps: u64,
+    /// Price of storing a cell for a second in nanotons multiplied by `2^16`
+    pub cell_price_ps: u64,
+    pub mc_bit_price_ps: u64,
+    pub mc_cell_price_ps: u64,
+}
+
+impl StoragePrices {
+    const TAG: u8 = 0xcc;
+
+    /// Computes storage fee for keeping cells for `period` seconds
+    #[inline]
+    pub fn compute_storage_fee(
+        &self,
+        stat: StorageStat,
+        period: u32,
+        masterchain: bool,
+    ) -> BigUint {
+        shr16_ceil(self.storage_price(stat, masterchain) * period)
+    }
+
+    /// Computes storage fee for keeping cells from `since` to `until`, taking
+    /// into account every change of prices in between.
+    ///
+    /// `prices` must be sorted by [`utime_since`](StoragePrices::utime_since),
+    /// as returned by [`ConfigParams::storage_prices()`](super::ConfigParams::storage_prices).
+    pub fn compute_storage_fee_between(
+        prices: &[Self],
+        stat: StorageStat,
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+        masterchain: bool,
+    ) -> BigUint {
+        let mut total = BigUint::ZERO;
+        for (i, p) in prices.iter().enumerate() {
+            let from = since.max(p.utime_since);
+            let to = prices
+                .get(i + 1)
+                .map_or(until, |next| until.min(next.utime_since));
+            if to <= from {
+                continue;
+            }
+            total += p.storage_price(stat, masterchain) * (to - from).num_seconds() as u64;
+        }
+        shr16_ceil(total)
+    }
+
+    fn storage_price(&self, stat: StorageStat, masterchain: bool) -> BigUint {
+        let (bit_price, cell_price) = if masterchain {
+            (self.mc_bit_price_ps, self.mc_cell_price_ps)
+        } else {
+            (self.bit_price_ps, self.cell_price_ps)
+        };
+        BigUint::from(bit_price) * stat.bits + BigUint::from(cell_price) * stat.cells
+    }
+}
+
+impl BitPack for StoragePrices {
+    #[inline]
+    fn pack<W>(&self, mut writer: W) -> Result<(), W::Error>
+    where
+        W: BitWriter,
+    {
+        writer
+            // _#cc
+            .pack(Self::TAG)?
+            // utime_since:uint32
+            .pack_as::<_, UnixTimestamp>(self.utime_since)?
+            // bit_price_ps:uint64
+            .pack(self.bit_price_ps)?
+            // cell_price_ps:uint64
+            .pack(self.cell_price_ps)?
+            // mc_bit_price_ps:uint64
+            .pack(self.mc_bit_price_ps)?
+            // mc_cell_price_ps:uint64
+            .pack(self.mc_cell_price_ps)?;
+        Ok(())
+    }
+}
+
+impl BitUnpack for StoragePrices {
+    #[inline]
+    fn unpack<R>(mut reader: R) -> Result<Self, R::Error>
+    where
+        R: BitReader,
+    {
+        let tag: u8 = reader.unpack()?;
+        if tag != Self::TAG {
+            return Err(Error::custom(format!(
+                "unknown storage_prices tag: {tag:#0x}"
+            )));
+        }
+        Ok(Self {
+            // utime_since:uint32
+            utime_since: reader.unpack_as::<_, UnixTimestamp>()?,
+            // bit_price_ps:uint64
+            bit_price_ps: reader.unpack()?,
+            // cell_price_ps:uint64
+            cell_price_ps: reader.unpack()?,
+            // mc_bit_price_ps:uint64
+            mc_bit_price_ps: reader.unpack()?,
+            // mc_cell_price_ps:uint64
+            mc_cell_price_ps: reader.unpack()?,
+        })
+    }
+}
+
+/// Number of unique cells and their total bits in a tree of cells, as
+/// counted by validators for fees. Cells with the same hash are counted once.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct StorageStat {
+    pub cells: u64,
+    pub bits: u64,
+}
+
+impl StorageStat {
+    /// Counts all cells in the tree including the root
+    #[inline]
+    pub fn of_cell(root: &Cell) -> Self {
+        Self::of_cells([root])
+    }
+
+    /// Counts cells of a serialized [`Message`](crate::message::Message) for
+    /// forward fee. The root cell itself is not counted, but only the cells
+    /// referenced from it.
+    #[inline]
+    pub fn of_message(msg: &Cell) -> Self {
+        Self::of_cells(msg.references.iter().map(AsRef::as_ref))
+    }
+
+    /// Counts all cells in the trees, deduplicated across all of them
+    pub fn of_cells<'a>(roots: impl IntoIterator<Item = &'a Cell>) -> Self {
+        let mut stat = Self::default();
+        let mut hasher = CellHasher::new();
+        let mut visited = HashSet::new();
+        let mut stack: Vec<&Cell> = roots.into_iter().collect();
+        while let Some(cell) = stack.pop() {
+            if !visited.insert(hasher.hash(cell)) {
+                continue;
+            }
+            stat.cells += 1;
+            stat.bits += cell.data.len() as u64;
+            stack.extend(cell.references.iter().map(AsRef::as_ref));
+        }
+        stat
+    }
+}
+
+/// `ceil(v / 2^16)`
+#[inline]
+fn shr16_ceil(v: BigUint) -> BigUint {
+    (v + 0xffffu32) >> 16
+}
+
+#[cfg(test)]
+mod tests {
+    use tlb::{
+        r#as::{Data, Ref},
+        ser::{r#as::CellSerializeWrapAsExt, CellSerializeExt},
+    };
+
+    use super::*;
+
+    const BASECHAIN_FWD_PRICES: MsgForwardPrices = MsgForwardPrices {
+        lump_price: 400_000,
+        bit_price: 26_214_400,
+        cell_price: 2_621_440_000,
+        ihr_price_factor: 98_304,
+        first_frac: 21_845,
+        next_frac: 21_845,
+    };
+
+    #[test]
+    fn storage_stat_dedup() {
+        let child = 0xdeadbeef_u32.wrap_as::<Data>().to_cell().unwrap();
+        let root = (child.clone().wrap_as::<Ref>(), child.wrap_as::<Ref>())
+            .to_cell()
+            .unwrap();
+
+        assert_eq!(
+            StorageStat::of_cell(&root),
+            StorageStat { cells: 2, bits: 32 }
+        );
+        assert_eq!(
+            StorageStat::of_message(&root),
+            StorageStat { cells: 1, bits: 32 }
+        );
+    }
+
+    #[test]
+    fn fwd_fee() {
+        let prices = BASECHAIN_FWD_PRICES;
+
+        let fee = prices.compute_fwd_fee(StorageStat::default());
+        assert_eq!(fee, BigUint::from(400_000u32));
+        assert_eq!(prices.first_part(&fee), BigUint::from(133_331u32));
+        assert_eq!(prices.remaining_fwd_fee(&fee), BigUint::from(266_669u32));
+
+        // 400_000 + 400 * 100 + 40_000 * 1
+        let fee = prices.compute_fwd_fee(StorageStat {
+            cells: 1,
+            bits: 100,
+        });
+        assert_eq!(fee, BigUint::from(480_000u32));
+    }
+
+    #[test]
+    fn gas_fee() {
+        let prices = GasLimitsPrices {
+            flat: Some(GasFlatPfx {
+                flat_gas_limit: 100,
+                flat_gas_price: 40_000,
+            }),
+            gas_price: 26_214_400,
+            gas_limit: 1_000_000,
+            special_gas_limit: Some(1_000_000),
+            gas_credit: 10_000,
+            block_gas_limit: 10_000_000,
+            freeze_due_limit: 100_000_000,
+            delete_due_limit: 1_000_000_000,
+        };
+
+        assert_eq!(prices.compute_gas_fee(50), BigUint::from(40_000u32));
+        // 40_000 + 400 * (3308 - 100)
+        assert_eq!(prices.compute_gas_fee(3308), BigUint::from(1_323_200u32));
+    }
+
+    #[test]
+    fn storage_fee() {
+        let stat = StorageStat {
+            cells: 10,
+            bits: 5_000,
+        };
+        let since = DateTime::from_timestamp(1_000_000, 0).unwrap();
+        let prices = [
+            StoragePrices {
+                utime_since: DateTime::UNIX_EPOCH,
+                bit_price_ps: 1,
+                cell_price_ps: 500,
+                mc_bit_price_ps: 1_000,
+                mc_cell_price_ps: 500_000,
+            },
+            StoragePrices {
+                utime_since: since,
+                bit_price_ps: 2,
+                cell_price_ps: 1_000,
+                mc_bit_price_ps: 2_000,
+                mc_cell_price_ps: 1_000_000,
+            },
+        ];
+
+        // ceil((5_000 + 10 * 500) * 86_400 / 2^16)
+        assert_eq!(
+            prices[0].compute_storage_fee(stat, 86_400, false),
+            BigUint::from(13_184u32)
+        );
+        // ceil((5_000 * 1_000 + 10 * 500_000) * 86_400 / 2^16)
+        assert_eq!(
+            prices[0].compute_storage_fee(stat, 86_400, true),
+            BigUint::from(13_183_594u32)
+        );
+        // ceil((10_000 * 100 + 20_000 * 100) / 2^16)
+        assert_eq!(
+            StoragePrices::compute_storage_fee_between(
+                &prices,
+                stat,
+                since - chrono::Duration::seconds(100),
+                since + chrono::Duration::seconds(100),
+                false,
+            ),
+            BigUint::from(46u32)
+        );
+    }
+}
@@ -0,0 +1,432 @@
+//! Collection of types related to [blockchain configuration](https://docs.ton.org/develop/howto/blockchain-configs)
+mod fees;
+mod validators;
+mod voting;
+mod workchain;
+
+pub use self::{fees::*, validators::*, voting::*, workchain::*};
+
+use tlb::{
+    bits::{
+        bitvec::{order::Msb0, view::AsBits},
+        de::BitReaderExt,
+        ser::BitWriterExt,
+    },
+    de::{r#as::CellDeserializeAs, CellDeserialize, CellParser, CellParserError},
+    r#as::{Data, NoArgs, Ref},
+    ser::{CellBuilder, CellBuilderError, CellSerialize},
+    Cell, ResultExt,
+};
+
+use crate::{
+    block::GlobalVersion,
+    hashmap::{Hashmap, HashmapE},
+};
+
+/// Blockchain configuration stored in key blocks and config contract.
+///
+/// Params are kept as raw cells and parsed on access, so that unknown or
+/// not yet supported params are preserved as is.
+/// ```tlb
+/// _ config_addr:bits256 config:^(Hashmap 32 ^Cell) = ConfigParams;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigParams {
+    pub config_addr: [u8; 32],
+    /// Raw config params by their 32-bit index
+    pub config: Hashmap<Cell>,
+}
+
+impl ConfigParams {
+    /// Returns raw config param with given index
+    #[inline]
+    pub fn get(&self, index: u32) -> Option<&Cell> {
+        self.config.get(index.to_be_bytes().as_bits::<Msb0>())
+    }
+
+    /// Parses config param with given index, if present
+    #[inline]
+    pub fn parse<'a, T>(&'a self, index: u32) -> Result<Option<T>, CellParserError<'a>>
+    where
+        T: CellDeserialize<'a>,
+    {
+        self.get(index).map(Cell::parse_fully).transpose()
+    }
+
+    /// Parses config param with given index using an adapter, if present
+    #[inline]
+    pub fn parse_as<'a, T, As>(&'a self, index: u32) -> Result<Option<T>, CellParserError<'a>>
+    where
+        As: CellDeserializeAs<'a, T> + ?Sized,
+    {
+        self.get(index)
+            .map(Cell::parse_fully_as::<T, As>)
+            .transpose()
+    }
+
+    /// ```tlb
+    /// _ elector_addr:bits256 = ConfigParam 1;
+    /// ```
+    #[inline]
+    pub fn elector_addr(&self) -> Result<Option<[u8; 32]>, CellParserError<'_>> {
+        self.parse_as::<_, Data>(1)
+    }
+
+    /// ```tlb
+    /// _ GlobalVersion = ConfigParam 8;
+    /// ```
+    #[inline]
+    pub fn global_version(&self) -> Result<Option<GlobalVersion>, CellParserError<'_>> {
+        self.parse_as::<_, Data>(8)
+    }
+
+    /// ```tlb
+    /// _ ConfigVotingSetup = ConfigParam 11;
+    /// ```
+    #[inline]
+    pub fn voting_setup(&self) -> Result<Option<ConfigVotingSetup>, CellParserError<'_>> {
+        self.parse(11)
+    }
+
+    /// Workchains by their 32-bit ids
+    /// ```tlb
+    /// _ workchains:(HashmapE 32 WorkchainDescr) = ConfigParam 12;
+    /// ```
+    #[inline]
+    pub fn workchains(&self) -> Result<Option<HashmapE<WorkchainDescr>>, CellParserError<'_>> {
+        self.get(12)
+            .map(|cell| {
+                cell.parse_fully_as_with::<_, HashmapE<Data<NoArgs<_>>, NoArgs<_>>>((32, (), ()))
+            })
+            .transpose()
+    }
+
+    /// ```tlb
+    /// _ validators_elected_for:uint32 elections_start_before:uint32
+    /// elections_end_before:uint32 stake_held_for:uint32
+    /// = ConfigParam 15;
+    /// ```
+    #[inline]
+    pub fn election_timings(&self) -> Result<Option<ElectionTimings>, CellParserError<'_>> {
+        self.parse_as::<_, Data>(15)
+    }
+
+    /// ```tlb
+    /// _ min_stake:Grams max_stake:Grams min_total_stake:Grams max_stake_factor:uint32
+    /// = ConfigParam 17;
+    /// ```
+    #[inline]
+    pub fn stake_limits(&self) -> Result<Option<StakeLimits>, CellParserError<'_>> {
+        self.parse_as::<_, Data>(17)
+    }
+
+    /// Storage prices in ascending order of
+    /// [`utime_since`](StoragePrices::utime_since)
+    /// ```tlb
+    /// _ (Hashmap 32 StoragePrices) = ConfigParam 18;
+    /// ```
+    #[inline]
+    pub fn storage_prices(&self) -> Result<Option<Vec<StoragePrices>>, CellParserError<'_>> {
+        self.get(18)
+            .map(|cell| {
+                cell.parse_fully_as_with::<Vec<(_, _)>, Hashmap<Data<NoArgs<_>>>>((32, ()))
+                    .map(|prices| prices.into_iter().map(|(_, p)| p).collect())
+            })
+            .transpose()
+    }
+
+    /// Gas prices in masterchain
+    /// ```tlb
+    /// config_mc_gas_prices#_ GasLimitsPrices = ConfigParam 20;
+    /// ```
+    #[inline]
+    pub fn mc_gas_prices(&self) -> Result<Option<GasLimitsPrices>, CellParserError<'_>> {
+        self.parse_as::<_, Data>(20)
+    }
+
+    /// Gas prices in basechain
+    /// ```tlb
+    /// config_gas_prices#_ GasLimitsPrices = ConfigParam 21;
+    /// ```
+    #[inline]
+    pub fn gas_prices(&self) -> Result<Option<GasLimitsPrices>, CellParserError<'_>> {
+        self.parse_as::<_, Data>(21)
+    }
+
+    /// Message forwarding prices in masterchain
+    /// ```tlb
+    /// config_mc_fwd_prices#_ MsgForwardPrices = ConfigParam 24;
+    /// ```
+    #[inline]
+    pub fn mc_fwd_prices(&self) -> Result<Option<MsgForwardPrices>, CellParserError<'_>> {
+        self.parse_as::<_, Data>(24)
+    }
+
+    /// Message forwarding prices in basechain
+    /// ```tlb
+    /// config_fwd_prices#_ MsgForwardPrices = ConfigParam 25;
+    /// ```
+    #[inline]
+    pub fn fwd_prices(&self) -> Result<Option<MsgForwardPrices>, CellParserError<'_>> {
+        self.parse_as::<_, Data>(25)
+    }
+
+    /// ```tlb
+    /// _ prev_validators:ValidatorSet = ConfigParam 32;
+    /// ```
+    #[inline]
+    pub fn prev_validators(&self) -> Result<Option<ValidatorSet>, CellParserError<'_>> {
+        self.parse(32)
+    }
+
+    /// ```tlb
+    /// _ cur_validators:ValidatorSet = ConfigParam 34;
+    /// ```
+    #[inline]
+    pub fn cur_validators(&self) -> Result<Option<ValidatorSet>, CellParserError<'_>> {
+        self.parse(34)
+    }
+
+    /// ```tlb
+    /// _ next_validators:ValidatorSet = ConfigParam 36;
+    /// ```
+    #[inline]
+    pub fn next_validators(&self) -> Result<Option<ValidatorSet>, CellParserError<'_>> {
+        self.parse(36)
+    }
+}
+
+impl CellSerialize for ConfigParams {
+    #[inline]
+    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        builder
+            // config_addr:bits256
+            .pack(self.config_addr)?
+            // config:^(Hashmap 32 ^Cell)
+            .store_as_with::<_, Ref<&Hashmap<Ref<NoArgs<_>>, NoArgs<_>>>>(
+                &self.config,
+                (32, (), ()),
+            )?;
+        Ok(())
+    }
+}
+
+impl<'de> CellDeserialize<'de> for ConfigParams {
+    #[inline]
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        Ok(Self {
+            // config_addr:bits256
+            config_addr: parser.unpack().context("config_addr")?,
+            // config:^(Hashmap 32 ^Cell)
+            config: parser
+                .parse_as_with::<_, Ref<Hashmap<Ref<NoArgs<_>>, NoArgs<_>>>>((32, (), ()))
+                .context("config")?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::DateTime;
+    use tlb::ser::{r#as::CellSerializeWrapAsExt, CellSerializeExt};
+
+    use super::*;
+
+    fn given_config(params: impl IntoIterator<Item = (u32, Cell)>) -> ConfigParams {
+        let mut config = HashmapE::new();
+        for (index, param) in params {
+            config
+                .insert(index.to_be_bytes().as_bits::<Msb0>(), param)
+                .unwrap();
+        }
+        let HashmapE::Root(config) = config else {
+            unreachable!()
+        };
+        ConfigParams {
+            config_addr: [0x55; 32],
+            config,
+        }
+    }
+
+    fn given_data_cell(v: impl tlb::bits::ser::BitPack) -> Cell {
+        v.wrap_as::<Data>().to_cell().unwrap()
+    }
+
+    #[test]
+    fn fee_params() {
+        let gas_prices = GasLimitsPrices {
+            flat: Some(GasFlatPfx {
+                flat_gas_limit: 100,
+                flat_gas_price: 40_000,
+            }),
+            gas_price: 400 << 16,
+            gas_limit: 1_000_000,
+            special_gas_limit: Some(1_000_000),
+            gas_credit: 10_000,
+            block_gas_limit: 10_000_000,
+            freeze_due_limit: 100_000_000,
+            delete_due_limit: 1_000_000_000,
+        };
+        let fwd_prices = MsgForwardPrices {
+            lump_price: 400_000,
+            bit_price: 400 << 16,
+            cell_price: 40_000 << 16,
+            ihr_price_factor: 98_304,
+            first_frac: 21_845,
+            next_frac: 21_845,
+        };
+        let storage_prices = [0, 1_700_000_000].map(|utime_since| StoragePrices {
+            utime_since: DateTime::from_timestamp(utime_since, 0).unwrap(),
+            bit_price_ps: 1,
+            cell_price_ps: 500,
+            mc_bit_price_ps: 1_000,
+            mc_cell_price_ps: 500_000,
+        });
+        let mut storage_prices_dict = HashmapE::new();
+        for p in storage_prices {
+            storage_prices_dict
+                .insert(
+                    (p.utime_since.timestamp() as u32)
+                        .to_be_bytes()
+                        .as_bits::<Msb0>(),
+                    p,
+                )
+                .unwrap();
+        }
+        let HashmapE::Root(storage_prices_dict) = storage_prices_dict else {
+            unreachable!()
+        };
+        let unknown = given_data_cell(0xdeadbeef_u32);
+
+        let config = given_config([
+            (18, {
+                let mut builder = Cell::builder();
+                builder
+                    .store_as_with::<_, Hashmap<Data<NoArgs<_>>, NoArgs<_>>>(
+                        storage_prices_dict,
+                        (32, (), ()),
+                    )
+                    .unwrap();
+                builder.into_cell()
+            }),
+            (21, given_data_cell(gas_prices)),
+            (25, given_data_cell(fwd_prices)),
+            (1000, unknown.clone()),
+        ]);
+        let cell = config.to_cell().unwrap();
+        let config: ConfigParams = cell.parse_fully().unwrap();
+
+        assert_eq!(config.gas_prices().unwrap(), Some(gas_prices));
+        assert_eq!(config.mc_gas_prices().unwrap(), None);
+        assert_eq!(config.fwd_prices().unwrap(), Some(fwd_prices));
+        assert_eq!(
+            config.storage_prices().unwrap().as_deref(),
+            Some(storage_prices.as_slice())
+        );
+        assert_eq!(config.get(1000), Some(&unknown));
+    }
+
+    #[test]
+    fn validator_params() {
+        let mut list = HashmapE::new();
+        for i in 0u16..3 {
+            list.insert(
+                i.to_be_bytes().as_bits::<Msb0>(),
+                ValidatorDescr {
+                    public_key: [i as u8; 32],
+                    weight: 100 * (i as u64 + 1),
+                    adnl_addr: (i != 0).then_some([0xad; 32]),
+                },
+            )
+            .unwrap();
+        }
+        let validators = ValidatorSet {
+            utime_since: DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+            utime_until: DateTime::from_timestamp(1_700_065_536, 0).unwrap(),
+            total: 3,
+            main: 3,
+            total_weight: Some(600),
+            list,
+        };
+        let prev_validators = ValidatorSet {
+            total_weight: None,
+            ..validators.clone()
+        };
+        let voting_setup = ConfigVotingSetup {
+            normal_params: ConfigProposalSetup {
+                min_tot_rounds: 2,
+                max_tot_rounds: 3,
+                min_wins: 2,
+                max_losses: 2,
+                min_store_sec: 1_000_000,
+                max_store_sec: 10_000_000,
+                bit_price: 1,
+                cell_price: 500,
+            },
+            critical_params: ConfigProposalSetup {
+                min_tot_rounds: 7,
+                max_tot_rounds: 10,
+                min_wins: 4,
+                ..Default::default()
+            },
+        };
+
+        let config = given_config([
+            (11, voting_setup.to_cell().unwrap()),
+            (32, prev_validators.to_cell().unwrap()),
+            (34, validators.to_cell().unwrap()),
+        ]);
+
+        assert_eq!(config.voting_setup().unwrap(), Some(voting_setup));
+        assert_eq!(config.prev_validators().unwrap(), Some(prev_validators));
+        assert_eq!(config.next_validators().unwrap(), None);
+        let cur = config.cur_validators().unwrap().unwrap();
+        assert_eq!(cur, validators);
+        assert_eq!(
+            cur.validators().map(|v| v.weight).collect::<Vec<_>>(),
+            [100, 200, 300]
+        );
+        assert_eq!(cur.get(1).and_then(|v| v.adnl_addr), Some([0xad; 32]));
+    }
+
+    #[test]
+    fn workchains_param() {
+        let basechain = WorkchainDescr {
+            enabled_since: DateTime::from_timestamp(1_573_821_854, 0).unwrap(),
+            monitor_min_split: 0,
+            min_split: 0,
+            max_split: 60,
+            active: true,
+            accept_msgs: true,
+            zerostate_root_hash: [1; 32],
+            zerostate_file_hash: [2; 32],
+            version: 0,
+            format: WorkchainFormat::Basic {
+                vm_version: -1,
+                vm_mode: 0,
+            },
+            split_merge_timings: Some(WcSplitMergeTimings {
+                split_merge_delay: 100,
+                split_merge_interval: 100,
+                min_split_merge_interval: 30,
+                max_split_merge_delay: 1_000,
+            }),
+        };
+        let mut workchains = HashmapE::new();
+        workchains
+            .insert(0i32.to_be_bytes().as_bits::<Msb0>(), basechain)
+            .unwrap();
+
+        let mut builder = Cell::builder();
+        builder
+            .store_as_with::<_, HashmapE<Data<NoArgs<_>>, NoArgs<_>>>(workchains, (32, (), ()))
+            .unwrap();
+        let config = given_config([(12, builder.into_cell())]);
+
+        let got = config.workchains().unwrap().unwrap();
+        assert_eq!(
+            got.get(0i32.to_be_bytes().as_bits::<Msb0>()),
+            Some(&basechain)
+        );
+    }
+}
@@ -0,0 +1,322 @@
+use chrono::{DateTime, Utc};
+use num_bigint::BigUint;
+use tlb::{
+    bits::{
+        bitvec::{order::Msb0, view::AsBits},
+        de::{BitReader, BitReaderExt, BitUnpack},
+        ser::{BitPack, BitWriter, BitWriterExt},
+    },
+    de::{CellDeserialize, CellParser, CellParserError},
+    r#as::{Data, NoArgs},
+    ser::{CellBuilder, CellBuilderError, CellSerialize},
+    Error, ResultExt,
+};
+
+use crate::{
+    currency::Grams,
+    hashmap::{Hashmap, HashmapE},
+    UnixTimestamp,
+};
+
+/// Set of validators, see [`ConfigParams::cur_validators()`](super::ConfigParams::cur_validators)
+/// ```tlb
+/// validators#11 utime_since:uint32 utime_until:uint32
+/// total:(## 16) main:(## 16) { main <= total } { main >= 1 }
+/// list:(Hashmap 16 ValidatorDescr) = ValidatorSet;
+///
+/// validators_ext#12 utime_since:uint32 utime_until:uint32
+/// total:(## 16) main:(## 16) { main <= total } { main >= 1 }
+/// total_weight:uint64 list:(HashmapE 16 ValidatorDescr) = ValidatorSet;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidatorSet {
+    pub utime_since: DateTime<Utc>,
+    pub utime_until: DateTime<Utc>,
+    pub total: u16,
+    pub main: u16,
+    /// Present only in `validators_ext`
+    pub total_weight: Option<u64>,
+    /// Validators by their 16-bit index
+    pub list: HashmapE<ValidatorDescr>,
+}
+
+impl ValidatorSet {
+    const TAG: u8 = 0x11;
+    const EXT_TAG: u8 = 0x12;
+
+    /// Returns iterator over validators in order of their indices
+    #[inline]
+    pub fn validators(&self) -> impl Iterator<Item = &ValidatorDescr> {
+        self.list.iter().map(|(_, v)| v)
+    }
+
+    /// Returns validator at given index
+    #[inline]
+    pub fn get(&self, index: u16) -> Option<&ValidatorDescr> {
+        self.list.get(index.to_be_bytes().as_bits::<Msb0>())
+    }
+}
+
+impl CellSerialize for ValidatorSet {
+    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        if !(1..=self.total).contains(&self.main) {
+            return Err(Error::custom("1 <= main <= total"));
+        }
+        builder
+            .pack(if self.total_weight.is_some() {
+                Self::EXT_TAG
+            } else {
+                Self::TAG
+            })?
+            // utime_since:uint32
+            .pack_as::<_, UnixTimestamp>(self.utime_since)?
+            // utime_until:uint32
+            .pack_as::<_, UnixTimestamp>(self.utime_until)?
+            // total:(## 16)
+            .pack(self.total)?
+            // main:(## 16)
+            .pack(self.main)?;
+        match (self.total_weight, &self.list) {
+            (Some(total_weight), list) => {
+                builder
+                    // total_weight:uint64
+                    .pack(total_weight)?
+                    // list:(HashmapE 16 ValidatorDescr)
+                    .store_as_with::<_, &HashmapE<Data<NoArgs<_>>, NoArgs<_>>>(list, (16, (), ()))
+                    .context("list")?;
+            }
+            (None, HashmapE::Root(list)) => {
+                // list:(Hashmap 16 ValidatorDescr)
+                builder
+                    .store_as_with::<_, &Hashmap<Data<NoArgs<_>>, NoArgs<_>>>(list, (16, (), ()))
+                    .context("list")?;
+            }
+            (None, HashmapE::Empty) => return Err(Error::custom("list: empty Hashmap")),
+        }
+        Ok(())
+    }
+}
+
+impl<'de> CellDeserialize<'de> for ValidatorSet {
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        let is_ext = match parser.unpack()? {
+            Self::TAG => false,
+            Self::EXT_TAG => true,
+            tag => return Err(Error::custom(format!("unknown validators tag: {tag:#0x}"))),
+        };
+        let utime_since = parser.unpack_as::<_, UnixTimestamp>()?;
+        let utime_until = parser.unpack_as::<_, UnixTimestamp>()?;
+        let total = parser.unpack()?;
+        let main = parser.unpack()?;
+        if !(1..=total).contains(&main) {
+            return Err(Error::custom("1 <= main <= total"));
+        }
+        let (total_weight, list) = if is_ext {
+            (
+                // total_weight:uint64
+                Some(parser.unpack()?),
+                // list:(HashmapE 16 ValidatorDescr)
+                parser
+                    .parse_as_with::<_, HashmapE<Data<NoArgs<_>>, NoArgs<_>>>((16, (), ()))
+                    .context("list")?,
+            )
+        } else {
+            (
+                None,
+                // list:(Hashmap 16 ValidatorDescr)
+                HashmapE::Root(
+                    parser
+                        .parse_as_with::<_, Hashmap<Data<NoArgs<_>>, NoArgs<_>>>((16, (), ()))
+                        .context("list")?,
+                ),
+            )
+        };
+        Ok(Self {
+            utime_since,
+            utime_until,
+            total,
+            main,
+            total_weight,
+            list,
+        })
+    }
+}
+
+/// ```tlb
+/// validator#53 public_key:SigPubKey weight:uint64 = ValidatorDescr;
+/// validator_addr#73 public_key:SigPubKey weight:uint64 adnl_addr:bits256 = ValidatorDescr;
+///
+/// ed25519_pubkey#8e81278a pubkey:bits256 = SigPubKey;
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ValidatorDescr {
+    /// ed25519 public key
+    pub public_key: [u8; 32],
+    pub weight: u64,
+    /// Present only in `validator_addr`
+    pub adnl_addr: Option<[u8; 32]>,
+}
+
+impl ValidatorDescr {
+    const TAG: u8 = 0x53;
+    const ADDR_TAG: u8 = 0x73;
+    const ED25519_PUBKEY_TAG: u32 = 0x8e81278a;
+}
+
+impl BitPack for ValidatorDescr {
+    #[inline]
+    fn pack<W>(&self, mut writer: W) -> Result<(), W::Error>
+    where
+        W: BitWriter,
+    {
+        writer
+            .pack(if self.adnl_addr.is_some() {
+                Self::ADDR_TAG
+            } else {
+                Self::TAG
+            })?
+            // public_key:SigPubKey
+            .pack(Self::ED25519_PUBKEY_TAG)?
+            .pack(self.public_key)?
+            // weight:uint64
+            .pack(self.weight)?;
+        if let Some(adnl_addr) = self.adnl_addr {
+            // adnl_addr:bits256
+            writer.pack(adnl_addr)?;
+        }
+        Ok(())
+    }
+}
+
+impl BitUnpack for ValidatorDescr {
+    #[inline]
+    fn unpack<R>(mut reader: R) -> Result<Self, R::Error>
+    where
+        R: BitReader,
+    {
+        let has_addr = match reader.unpack()? {
+            Self::TAG => false,
+            Self::ADDR_TAG => true,
+            tag => return Err(Error::custom(format!("unknown validator tag: {tag:#0x}"))),
+        };
+        // public_key:SigPubKey
+        let tag: u32 = reader.unpack()?;
+        if tag != Self::ED25519_PUBKEY_TAG {
+            return Err(Error::custom(format!("unknown SigPubKey tag: {tag:#0x}")));
+        }
+        Ok(Self {
+            public_key: reader.unpack()?,
+            // weight:uint64
+            weight: reader.unpack()?,
+            // adnl_addr:bits256
+            adnl_addr: if has_addr {
+                Some(reader.unpack()?)
+            } else {
+                None
+            },
+        })
+    }
+}
+
+/// Validator elections timings in seconds
+/// ```tlb
+/// _ validators_elected_for:uint32 elections_start_before:uint32
+/// elections_end_before:uint32 stake_held_for:uint32
+/// = ConfigParam 15;
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct ElectionTimings {
+    pub validators_elected_for: u32,
+    pub elections_start_before: u32,
+    pub elections_end_before: u32,
+    pub stake_held_for: u32,
+}
+
+impl BitPack for ElectionTimings {
+    #[inline]
+    fn pack<W>(&self, mut writer: W) -> Result<(), W::Error>
+    where
+        W: BitWriter,
+    {
+        writer
+            // validators_elected_for:uint32
+            .pack(self.validators_elected_for)?
+            // elections_start_before:uint32
+            .pack(self.elections_start_before)?
+            // elections_end_before:uint32
+            .pack(self.elections_end_before)?
+            // stake_held_for:uint32
+            .pack(self.stake_held_for)?;
+        Ok(())
+    }
+}
+
+impl BitUnpack for ElectionTimings {
+    #[inline]
+    fn unpack<R>(mut reader: R) -> Result<Self, R::Error>
+    where
+        R: BitReader,
+    {
+        Ok(Self {
+            // validators_elected_for:uint32
+            validators_elected_for: reader.unpack()?,
+            // elections_start_before:uint32
+            elections_start_before: reader.unpack()?,
+            // elections_end_before:uint32
+            elections_end_before: reader.unpack()?,
+            // stake_held_for:uint32
+            stake_held_for: reader.unpack()?,
+        })
+    }
+}
+
+/// ```tlb
+/// _ min_stake:Grams max_stake:Grams min_total_stake:Grams max_stake_factor:uint32
+/// = ConfigParam 17;
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct StakeLimits {
+    pub min_stake: BigUint,
+    pub max_stake: BigUint,
+    pub min_total_stake: BigUint,
+    /// Multiplied by `2^16`
+    pub max_stake_factor: u32,
+}
+
+impl BitPack for StakeLimits {
+    #[inline]
+    fn pack<W>(&self, mut writer: W) -> Result<(), W::Error>
+    where
+        W: BitWriter,
+    {
+        writer
+            // min_stake:Grams
+            .pack_as::<_, &Grams>(&self.min_stake)?
+            // max_stake:Grams
+            .pack_as::<_, &Grams>(&self.max_stake)?
+            // min_total_stake:Grams
+            .pack_as::<_, &Grams>(&self.min_total_stake)?
+            // max_stake_factor:uint32
+            .pack(self.max_stake_factor)?;
+        Ok(())
+    }
+}
+
+impl BitUnpack for StakeLimits {
+    #[inline]
+    fn unpack<R>(mut reader: R) -> Result<Self, R::Error>
+    where
+        R: BitReader,
+    {
+        Ok(Self {
+            // min_stake:Grams
+            min_stake: reader.unpack_as::<_, Grams>()?,
+            // max_stake:Grams
+            max_stake: reader.unpack_as::<_, Grams>()?,
+            // min_total_stake:Grams
+            min_total_stake: reader.unpack_as::<_, Grams>()?,
+            // max_stake_factor:uint32
+            max_stake_factor: reader.unpack()?,
+        })
+    }
+}
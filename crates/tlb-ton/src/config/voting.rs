@@ -0,0 +1,146 @@
+use tlb::{
+    bits::{
+        de::{BitReader, BitReaderExt, BitUnpack},
+        ser::{BitPack, BitWriter, BitWriterExt},
+    },
+    de::{CellDeserialize, CellParser, CellParserError},
+    r#as::{Data, Ref},
+    ser::{CellBuilder, CellBuilderError, CellSerialize},
+    Error, ResultExt,
+};
+
+/// Rules of voting for config proposals,
+/// see [`ConfigParams::voting_setup()`](super::ConfigParams::voting_setup)
+/// ```tlb
+/// cfg_vote_setup#91 normal_params:^ConfigProposalSetup
+/// critical_params:^ConfigProposalSetup = ConfigVotingSetup;
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct ConfigVotingSetup {
+    pub normal_params: ConfigProposalSetup,
+    /// Applied to proposals changing critical params
+    pub critical_params: ConfigProposalSetup,
+}
+
+impl ConfigVotingSetup {
+    const TAG: u8 = 0x91;
+}
+
+impl CellSerialize for ConfigVotingSetup {
+    #[inline]
+    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        builder
+            // cfg_vote_setup#91
+            .pack(Self::TAG)?
+            // normal_params:^ConfigProposalSetup
+            .store_as::<_, Ref<Data>>(self.normal_params)
+            .context("normal_params")?
+            // critical_params:^ConfigProposalSetup
+            .store_as::<_, Ref<Data>>(self.critical_params)
+            .context("critical_params")?;
+        Ok(())
+    }
+}
+
+impl<'de> CellDeserialize<'de> for ConfigVotingSetup {
+    #[inline]
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        let tag: u8 = parser.unpack()?;
+        if tag != Self::TAG {
+            return Err(Error::custom(format!(
+                "unknown cfg_vote_setup tag: {tag:#0x}"
+            )));
+        }
+        Ok(Self {
+            // normal_params:^ConfigProposalSetup
+            normal_params: parser.parse_as::<_, Ref<Data>>().context("normal_params")?,
+            // critical_params:^ConfigProposalSetup
+            critical_params: parser
+                .parse_as::<_, Ref<Data>>()
+                .context("critical_params")?,
+        })
+    }
+}
+
+/// ```tlb
+/// cfg_vote_cfg#36 min_tot_rounds:uint8 max_tot_rounds:uint8 min_wins:uint8
+/// max_losses:uint8 min_store_sec:uint32 max_store_sec:uint32
+/// bit_price:uint32 cell_price:uint32 = ConfigProposalSetup;
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct ConfigProposalSetup {
+    pub min_tot_rounds: u8,
+    pub max_tot_rounds: u8,
+    pub min_wins: u8,
+    pub max_losses: u8,
+    pub min_store_sec: u32,
+    pub max_store_sec: u32,
+    pub bit_price: u32,
+    pub cell_price: u32,
+}
+
+impl ConfigProposalSetup {
+    const TAG: u8 = 0x36;
+}
+
+impl BitPack for ConfigProposalSetup {
+    #[inline]
+    fn pack<W>(&self, mut writer: W) -> Result<(), W::Error>
+    where
+        W: BitWriter,
+    {
+        writer
+            // cfg_vote_cfg#36
+            .pack(Self::TAG)?
+            // min_tot_rounds:uint8
+            .pack(self.min_tot_rounds)?
+            // max_tot_rounds:uint8
+            .pack(self.max_tot_rounds)?
+            // min_wins:uint8
+            .pack(self.min_wins)?
+            // max_losses:uint8
+            .pack(self.max_losses)?
+            // min_store_sec:uint32
+            .pack(self.min_store_sec)?
+            // max_store_sec:uint32
+            .pack(self.max_store_sec)?
+            // bit_price:uint32
+            .pack(self.bit_price)?
+            // cell_price:uint32
+            .pack(self.cell_price)?;
+        Ok(())
+    }
+}
+
+impl BitUnpack for ConfigProposalSetup {
+    #[inline]
+    fn unpack<R>(mut reader: R) -> Result<Self, R::Error>
+    where
+        R: BitReader,
+    {
+        let tag: u8 = reader.unpack()?;
+        if tag != Self::TAG {
+            return Err(Error::custom(format!(
+                "unknown cfg_vote_cfg tag: {tag:#0x}"
+            )));
+        }
+        Ok(Self {
+            // min_tot_rounds:uint8
+            min_tot_rounds: reader.unpack()?,
+            // max_tot_rounds:uint8
+            max_tot_rounds: reader.unpack()?,
+            // min_wins:uint8
+            min_wins: reader.unpack()?,
+            // max_losses:uint8
+            max_losses: reader.unpack()?,
+            // min_store_sec:uint32
+            min_store_sec: reader.unpack()?,
+            // max_store_sec:uint32
+            max_store_sec: reader.unpack()?,
+            // bit_price:uint32
+            bit_price: reader.unpack()?,
+            // cell_price:uint32
+            cell_price: reader.unpack()?,
+        })
+    }
+}
@@ -0,0 +1,314 @@
+use chrono::{DateTime, Utc};
+use tlb::{
+    bits::{
+        de::{BitReader, BitReaderExt, BitUnpack},
+        r#as::NBits,
+        ser::{BitPack, BitWriter, BitWriterExt},
+    },
+    Error,
+};
+
+use crate::UnixTimestamp;
+
+/// Workchain description, see [`ConfigParams::workchains()`](super::ConfigParams::workchains)
+///
+/// `basic` flag is derived from [`format`](WorkchainDescr::format).
+/// ```tlb
+/// workchain#a6 enabled_since:uint32 monitor_min_split:(## 8)
+/// min_split:(## 8) max_split:(## 8) { monitor_min_split <= min_split }
+/// basic:(## 1) active:Bool accept_msgs:Bool flags:(## 13) { flags = 0 }
+/// zerostate_root_hash:bits256 zerostate_file_hash:bits256
+/// version:uint32 format:(WorkchainFormat basic)
+/// = WorkchainDescr;
+///
+/// workchain_v2#a7 enabled_since:uint32 monitor_min_split:(## 8)
+/// min_split:(## 8) max_split:(## 8) { monitor_min_split <= min_split }
+/// basic:(## 1) active:Bool accept_msgs:Bool flags:(## 13) { flags = 0 }
+/// zerostate_root_hash:bits256 zerostate_file_hash:bits256
+/// version:uint32 format:(WorkchainFormat basic)
+/// split_merge_timings:WcSplitMergeTimings
+/// = WorkchainDescr;
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct WorkchainDescr {
+    pub enabled_since: DateTime<Utc>,
+    pub monitor_min_split: u8,
+    pub min_split: u8,
+    pub max_split: u8,
+    pub active: bool,
+    pub accept_msgs: bool,
+    pub zerostate_root_hash: [u8; 32],
+    pub zerostate_file_hash: [u8; 32],
+    pub version: u32,
+    pub format: WorkchainFormat,
+    /// Present only in `workchain_v2`
+    pub split_merge_timings: Option<WcSplitMergeTimings>,
+}
+
+impl WorkchainDescr {
+    const TAG: u8 = 0xa6;
+    const V2_TAG: u8 = 0xa7;
+}
+
+impl BitPack for WorkchainDescr {
+    fn pack<W>(&self, mut writer: W) -> Result<(), W::Error>
+    where
+        W: BitWriter,
+    {
+        if self.monitor_min_split > self.min_split {
+            return Err(Error::custom("monitor_min_split > min_split"));
+        }
+        writer
+            .pack(if self.split_merge_timings.is_some() {
+                Self::V2_TAG
+            } else {
+                Self::TAG
+            })?
+            // enabled_since:uint32
+            .pack_as::<_, UnixTimestamp>(self.enabled_since)?
+            // monitor_min_split:(## 8)
+            .pack(self.monitor_min_split)?
+            // min_split:(## 8)
+            .pack(self.min_split)?
+            // max_split:(## 8)
+            .pack(self.max_split)?
+            // basic:(## 1)
+            .pack(self.format.is_basic())?
+            // active:Bool
+            .pack(self.active)?
+            // accept_msgs:Bool
+            .pack(self.accept_msgs)?
+            // flags:(## 13) { flags = 0 }
+            .pack_as::<_, NBits<13>>(0u16)?
+            // zerostate_root_hash:bits256
+            .pack(self.zerostate_root_hash)?
+            // zerostate_file_hash:bits256
+            .pack(self.zerostate_file_hash)?
+            // version:uint32
+            .pack(self.version)?
+            // format:(WorkchainFormat basic)
+            .pack(self.format)?;
+        if let Some(split_merge_timings) = self.split_merge_timings {
+            // split_merge_timings:WcSplitMergeTimings
+            writer.pack(split_merge_timings)?;
+        }
+        Ok(())
+    }
+}
+
+impl BitUnpack for WorkchainDescr {
+    fn unpack<R>(mut reader: R) -> Result<Self, R::Error>
+    where
+        R: BitReader,
+    {
+        let is_v2 = match reader.unpack()? {
+            Self::TAG => false,
+            Self::V2_TAG => true,
+            tag => return Err(Error::custom(format!("unknown workchain tag: {tag:#0x}"))),
+        };
+        let enabled_since = reader.unpack_as::<_, UnixTimestamp>()?;
+        let monitor_min_split = reader.unpack()?;
+        let min_split = reader.unpack()?;
+        if monitor_min_split > min_split {
+            return Err(Error::custom("monitor_min_split > min_split"));
+        }
+        let max_split = reader.unpack()?;
+        // basic:(## 1)
+        let basic: bool = reader.unpack()?;
+        let active = reader.unpack()?;
+        let accept_msgs = reader.unpack()?;
+        // flags:(## 13) { flags = 0 }
+        let flags: u16 = reader.unpack_as::<_, NBits<13>>()?;
+        if flags != 0 {
+            return Err(Error::custom(format!("flags = 0, got: {flags}")));
+        }
+        let zerostate_root_hash = reader.unpack()?;
+        let zerostate_file_hash = reader.unpack()?;
+        let version = reader.unpack()?;
+        // format:(WorkchainFormat basic)
+        let format: WorkchainFormat = reader.unpack()?;
+        if format.is_basic() != basic {
+            return Err(Error::custom("format does not match basic flag"));
+        }
+        Ok(Self {
+            enabled_since,
+            monitor_min_split,
+            min_split,
+            max_split,
+            active,
+            accept_msgs,
+            zerostate_root_hash,
+            zerostate_file_hash,
+            version,
+            format,
+            // split_merge_timings:WcSplitMergeTimings
+            split_merge_timings: if is_v2 { Some(reader.unpack()?) } else { None },
+        })
+    }
+}
+
+/// ```tlb
+/// wfmt_basic#1 vm_version:int32 vm_mode:uint64 = WorkchainFormat 1;
+/// wfmt_ext#0 min_addr_len:(## 12) max_addr_len:(## 12) addr_len_step:(## 12)
+/// { min_addr_len >= 64 } { min_addr_len <= max_addr_len }
+/// { max_addr_len <= 1023 } { addr_len_step <= 1023 }
+/// workchain_type_id:(## 32) { workchain_type_id >= 1 }
+/// = WorkchainFormat 0;
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WorkchainFormat {
+    /// ```tlb
+    /// wfmt_basic#1 vm_version:int32 vm_mode:uint64 = WorkchainFormat 1;
+    /// ```
+    Basic { vm_version: i32, vm_mode: u64 },
+    /// ```tlb
+    /// wfmt_ext#0 min_addr_len:(## 12) max_addr_len:(## 12) addr_len_step:(## 12)
+    /// { min_addr_len >= 64 } { min_addr_len <= max_addr_len }
+    /// { max_addr_len <= 1023 } { addr_len_step <= 1023 }
+    /// workchain_type_id:(## 32) { workchain_type_id >= 1 }
+    /// = WorkchainFormat 0;
+    /// ```
+    Extended {
+        min_addr_len: u16,
+        max_addr_len: u16,
+        addr_len_step: u16,
+        workchain_type_id: u32,
+    },
+}
+
+impl WorkchainFormat {
+    #[inline]
+    pub const fn is_basic(&self) -> bool {
+        matches!(self, Self::Basic { .. })
+    }
+}
+
+impl BitPack for WorkchainFormat {
+    #[inline]
+    fn pack<W>(&self, mut writer: W) -> Result<(), W::Error>
+    where
+        W: BitWriter,
+    {
+        match *self {
+            Self::Basic {
+                vm_version,
+                vm_mode,
+            } => {
+                writer
+                    // wfmt_basic#1
+                    .pack_as::<_, NBits<4>>(0x1)?
+                    // vm_version:int32
+                    .pack(vm_version)?
+                    // vm_mode:uint64
+                    .pack(vm_mode)?;
+            }
+            Self::Extended {
+                min_addr_len,
+                max_addr_len,
+                addr_len_step,
+                workchain_type_id,
+            } => {
+                writer
+                    // wfmt_ext#0
+                    .pack_as::<_, NBits<4>>(0x0)?
+                    // min_addr_len:(## 12)
+                    .pack_as::<_, NBits<12>>(min_addr_len)?
+                    // max_addr_len:(## 12)
+                    .pack_as::<_, NBits<12>>(max_addr_len)?
+                    // addr_len_step:(## 12)
+                    .pack_as::<_, NBits<12>>(addr_len_step)?
+                    // workchain_type_id:(## 32)
+                    .pack(workchain_type_id)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl BitUnpack for WorkchainFormat {
+    #[inline]
+    fn unpack<R>(mut reader: R) -> Result<Self, R::Error>
+    where
+        R: BitReader,
+    {
+        Ok(match reader.unpack_as::<u8, NBits<4>>()? {
+            // wfmt_basic#1
+            0x1 => Self::Basic {
+                vm_version: reader.unpack()?,
+                vm_mode: reader.unpack()?,
+            },
+            // wfmt_ext#0
+            0x0 => Self::Extended {
+                min_addr_len: reader.unpack_as::<_, NBits<12>>()?,
+                max_addr_len: reader.unpack_as::<_, NBits<12>>()?,
+                addr_len_step: reader.unpack_as::<_, NBits<12>>()?,
+                workchain_type_id: reader.unpack()?,
+            },
+            tag => {
+                return Err(Error::custom(format!(
+                    "unknown WorkchainFormat tag: {tag:#x}"
+                )))
+            }
+        })
+    }
+}
+
+/// ```tlb
+/// wc_split_merge_timings#0
+/// split_merge_delay:uint32 split_merge_interval:uint32
+/// min_split_merge_interval:uint32 max_split_merge_delay:uint32
+/// = WcSplitMergeTimings;
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct WcSplitMergeTimings {
+    pub split_merge_delay: u32,
+    pub split_merge_interval: u32,
+    pub min_split_merge_interval: u32,
+    pub max_split_merge_delay: u32,
+}
+
+impl BitPack for WcSplitMergeTimings {
+    #[inline]
+    fn pack<W>(&self, mut writer: W) -> Result<(), W::Error>
+    where
+        W: BitWriter,
+    {
+        writer
+            // wc_split_merge_timings#0
+            .pack_as::<_, NBits<4>>(0x0)?
+            // split_merge_delay:uint32
+            .pack(self.split_merge_delay)?
+            // split_merge_interval:uint32
+            .pack(self.split_merge_interval)?
+            // min_split_merge_interval:uint32
+            .pack(self.min_split_merge_interval)?
+            // max_split_merge_delay:uint32
+            .pack(self.max_split_merge_delay)?;
+        Ok(())
+    }
+}
+
+impl BitUnpack for WcSplitMergeTimings {
+    #[inline]
+    fn unpack<R>(mut reader: R) -> Result<Self, R::Error>
+    where
+        R: BitReader,
+    {
+        let tag: u8 = reader.unpack_as::<_, NBits<4>>()?;
+        if tag != 0x0 {
+            return Err(Error::custom(format!(
+                "unknown wc_split_merge_timings tag: {tag:#x}"
+            )));
+        }
+        Ok(Self {
+            // split_merge_delay:uint32
+            split_merge_delay: reader.unpack()?,
+            // split_merge_interval:uint32
+            split_merge_interval: reader.unpack()?,
+            // min_split_merge_interval:uint32
+            min_split_merge_interval: reader.unpack()?,
+            // max_split_merge_delay:uint32
+            max_split_merge_delay: reader.unpack()?,
+        })
+    }
+}
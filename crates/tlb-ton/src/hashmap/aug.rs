@@ -1,4 +1,7 @@
-use std::iter::once;
+use std::{
+    iter::{self, once},
+    mem,
+};
 
 use impl_tools::autoimpl;
 use tlb::{
@@ -16,7 +19,7 @@ use tlb::{
         args::{r#as::CellSerializeAsWithArgs, CellSerializeWithArgs},
         CellBuilder, CellBuilderError,
     },
-    Error, ResultExt,
+    Error, ResultExt, StringError,
 };
 
 use super::hm_label::HmLabel;
@@ -28,7 +31,7 @@ use super::hm_label::HmLabel;
 /// ahme_root$1 {n:#} {X:Type} {Y:Type} root:^(HashmapAug n X Y)
 /// extra:Y = HashmapAugE n X Y;
 /// ```
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 #[autoimpl(Deref using self.m)]
 #[autoimpl(DerefMut using self.m)]
 #[autoimpl(Default where E: Default)]
@@ -156,6 +159,81 @@ impl<T, E> HashmapE<T, E> {
             Self::Root(root) => root.get_mut(key),
         }
     }
+
+    /// Returns iterator over `(key, value)` pairs in ascending order of keys
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = (Key, &T)> {
+        match self {
+            Self::Empty => None,
+            Self::Root(root) => Some(root.iter()),
+        }
+        .into_iter()
+        .flatten()
+    }
+}
+
+impl<T> HashmapE<T> {
+    /// Inserts value at given key and returns the previous value associated
+    /// with it, if any.
+    ///
+    /// Keys are not required to be of the same length, so it can be used for
+    /// [`PfxHashmapE`](super::pfx::PfxHashmapE) as well. But no key can be a
+    /// prefix of another one, so an error is returned in this case.
+    pub fn insert(
+        &mut self,
+        key: impl AsRef<BitSlice<u8, Msb0>>,
+        value: T,
+    ) -> Result<Option<T>, StringError> {
+        let key = key.as_ref();
+        if let Self::Root(root) = self {
+            root.check_insert(key)?;
+        }
+        Ok(match mem::take(self) {
+            Self::Empty => {
+                *self = Self::Root(Hashmap::new(
+                    key,
+                    HashmapAugNode::new(HashmapNode::Leaf(value), ()),
+                ));
+                None
+            }
+            Self::Root(root) => {
+                let (root, old) = root.into_inserted(key, value);
+                *self = Self::Root(root);
+                old
+            }
+        })
+    }
+
+    /// Removes given key from this hashmap and returns its value, if any
+    pub fn remove(&mut self, key: impl AsRef<BitSlice<u8, Msb0>>) -> Option<T> {
+        let Self::Root(root) = mem::take(self) else {
+            return None;
+        };
+        let (root, removed) = root.into_removed(key.as_ref());
+        if let Some(root) = root {
+            *self = Self::Root(root);
+        }
+        removed
+    }
+
+    /// Returns the longest prefix of given `key` stored in this
+    /// [`PfxHashmapE`](super::pfx::PfxHashmapE) along with its value.
+    ///
+    /// Since stored keys form a prefix code, there can be at most one such
+    /// prefix.
+    ///
+    /// Use [`.insert()`](HashmapE::insert) and [`.remove()`](HashmapE::remove)
+    /// to modify prefix dictionaries.
+    #[inline]
+    pub fn lookup_prefix<'a>(
+        &self,
+        key: &'a BitSlice<u8, Msb0>,
+    ) -> Option<(&'a BitSlice<u8, Msb0>, &T)> {
+        match self {
+            Self::Empty => None,
+            Self::Root(root) => root.lookup_prefix(key),
+        }
+    }
 }
 
 impl<T, AsT, E, AsE> CellSerializeAsWithArgs<HashmapE<T, E>> for HashmapE<AsT, AsE>
@@ -315,7 +393,7 @@ impl<T, E> Hashmap<T, E> {
     pub fn contains_key(&self, key: impl AsRef<BitSlice<u8, Msb0>>) -> bool {
         key.as_ref()
             .strip_prefix(&self.prefix)
-            .map_or(false, |key| self.node.contains_key(key))
+            .is_some_and(|key| self.node.contains_key(key))
     }
 
     #[inline]
@@ -327,6 +405,150 @@ impl<T, E> Hashmap<T, E> {
     pub fn get_mut(&mut self, key: impl AsRef<BitSlice<u8, Msb0>>) -> Option<&mut T> {
         self.node.get_mut(key.as_ref().strip_prefix(&self.prefix)?)
     }
+
+    /// Returns iterator over `(key, value)` pairs in ascending order of keys
+    pub fn iter(&self) -> impl Iterator<Item = (Key, &T)> {
+        let mut stack = vec![(Key::new(), self)];
+        iter::from_fn(move || loop {
+            let (mut key, edge) = stack.pop()?;
+            key.extend_from_bitslice(&edge.prefix);
+            match &edge.node.node {
+                HashmapNode::Leaf(v) => return Some((key, v)),
+                HashmapNode::Fork([left, right]) => {
+                    for (bit, child) in [(true, right), (false, left)] {
+                        let mut key = key.clone();
+                        key.push(bit);
+                        stack.push((key, child.as_ref()));
+                    }
+                }
+            }
+        })
+    }
+}
+
+impl<T> Hashmap<T> {
+    /// See [`HashmapE::lookup_prefix()`]
+    pub fn lookup_prefix<'a>(
+        &self,
+        key: &'a BitSlice<u8, Msb0>,
+    ) -> Option<(&'a BitSlice<u8, Msb0>, &T)> {
+        let mut edge = self;
+        let mut matched = 0;
+        loop {
+            let rest = key[matched..].strip_prefix(&edge.prefix)?;
+            matched += edge.prefix.len();
+            match &edge.node.node {
+                HashmapNode::Leaf(value) => return Some((&key[..matched], value)),
+                HashmapNode::Fork([left, right]) => {
+                    edge = if *rest.first()? { right } else { left };
+                    matched += 1;
+                }
+            }
+        }
+    }
+
+    /// Ensures that given key can be inserted without violating prefix code
+    fn check_insert(&self, key: &BitSlice<u8, Msb0>) -> Result<(), StringError> {
+        let Some(key) = key.strip_prefix(&self.prefix) else {
+            if self.prefix.starts_with(key) {
+                return Err(Error::custom("key is a prefix of another key"));
+            }
+            return Ok(());
+        };
+        match &self.node.node {
+            HashmapNode::Leaf(_) if key.is_empty() => Ok(()),
+            HashmapNode::Leaf(_) => Err(Error::custom("another key is a prefix of the key")),
+            HashmapNode::Fork([left, right]) => {
+                let (is_right, key) = key
+                    .split_first()
+                    .ok_or_else(|| Error::custom("key is a prefix of another key"))?;
+                if *is_right { right } else { left }.check_insert(key)
+            }
+        }
+    }
+
+    /// Inserts value at given key, which should be
+    /// [checked](Hashmap::check_insert) beforehand
+    fn into_inserted(self, key: &BitSlice<u8, Msb0>, value: T) -> (Self, Option<T>) {
+        let Self { prefix, node } = self;
+        let common = prefix
+            .iter()
+            .zip(key.iter())
+            .take_while(|(a, b)| a == b)
+            .count();
+
+        if common < prefix.len() {
+            // split the edge
+            let (shared, rest) = prefix.split_at(common);
+            let old = Box::new(Self::new(&rest[1..], node));
+            let new = Box::new(Self::new(
+                &key[common + 1..],
+                HashmapAugNode::new(HashmapNode::Leaf(value), ()),
+            ));
+            let fork = if rest[0] { [new, old] } else { [old, new] };
+            return (
+                Self::new(shared, HashmapAugNode::new(HashmapNode::Fork(fork), ())),
+                None,
+            );
+        }
+
+        let (node, old) = match node.node {
+            HashmapNode::Leaf(old) => (HashmapNode::Leaf(value), Some(old)),
+            HashmapNode::Fork([left, right]) => {
+                let (is_right, key) = key[common..].split_first().unwrap();
+                if *is_right {
+                    let (right, old) = right.into_inserted(key, value);
+                    (HashmapNode::Fork([left, Box::new(right)]), old)
+                } else {
+                    let (left, old) = left.into_inserted(key, value);
+                    (HashmapNode::Fork([Box::new(left), right]), old)
+                }
+            }
+        };
+        (Self::new(prefix, HashmapAugNode::new(node, ())), old)
+    }
+
+    /// Removes given key and returns remaining edge, if any, along with
+    /// removed value
+    fn into_removed(self, key: &BitSlice<u8, Msb0>) -> (Option<Self>, Option<T>) {
+        let Some(rest) = key.strip_prefix(&self.prefix) else {
+            return (Some(self), None);
+        };
+        let Self { prefix, node } = self;
+        let (node, removed) = match node.node {
+            HashmapNode::Leaf(v) if rest.is_empty() => return (None, Some(v)),
+            HashmapNode::Fork([left, right]) if !rest.is_empty() => {
+                let (is_right, rest) = rest.split_first().unwrap();
+                let (child, other) = if *is_right {
+                    (right, left)
+                } else {
+                    (left, right)
+                };
+                let (child, removed) = child.into_removed(rest);
+                let Some(child) = child else {
+                    // merge the only child left into this edge
+                    let mut prefix = prefix;
+                    prefix.push(!*is_right);
+                    prefix.extend_from_bitslice(&other.prefix);
+                    return (Some(Self::new(prefix, other.node)), removed);
+                };
+                let child = Box::new(child);
+                (
+                    HashmapNode::Fork(if *is_right {
+                        [other, child]
+                    } else {
+                        [child, other]
+                    }),
+                    removed,
+                )
+            }
+            node => (node, None),
+        };
+        (
+            Some(Self::new(prefix, HashmapAugNode::new(node, ()))),
+            removed,
+        )
+    }
 }
 
 impl<T, AsT, E, AsE> CellSerializeAsWithArgs<Hashmap<T, E>> for Hashmap<AsT, AsE>
@@ -693,7 +915,7 @@ where
 mod tests {
     use std::collections::{BTreeMap, HashMap};
     use tlb::{
-        bits::bitvec::{bits, order::Msb0, view::AsBits},
+        bits::bitvec::{bits, field::BitField, order::Msb0, view::AsBits},
         r#as::{Data, NoArgs},
         ser::{r#as::CellSerializeWrapAsExt, CellSerializeExt},
         Cell,
@@ -759,6 +981,32 @@ mod tests {
         assert_eq!(hm.get(128u8.to_be_bytes().as_bits()), Some(&777));
     }
 
+    #[test]
+    fn insert_remove() {
+        let mut hm = HashmapE::<u16>::new();
+        for (k, v) in [(128u8, 777u16), (1, 777), (17, 111)] {
+            assert_eq!(hm.insert(k.to_be_bytes().as_bits(), v).unwrap(), None);
+        }
+
+        let mut builder = Cell::builder();
+        builder
+            .store_as_with::<_, &HashmapE<Data<NoArgs<_>>, NoArgs<_>>>(&hm, (8, (), ()))
+            .unwrap();
+        assert_eq!(builder.into_cell(), given_cell_from_example());
+
+        assert_eq!(
+            hm.iter()
+                .map(|(k, v)| (k.load_be::<u8>(), *v))
+                .collect::<Vec<_>>(),
+            [(1, 777), (17, 111), (128, 777)],
+        );
+
+        assert_eq!(hm.remove(17u8.to_be_bytes().as_bits()), Some(111));
+        assert_eq!(hm.remove(17u8.to_be_bytes().as_bits()), None);
+        assert_eq!(hm.len(), 2);
+        assert_eq!(hm.get(1u8.to_be_bytes().as_bits()), Some(&777));
+    }
+
     /// See <https://docs.ton.org/develop/data-formats/tl-b-types#hashmap-parsing-example>
     fn given_cell_from_example() -> Cell {
         (
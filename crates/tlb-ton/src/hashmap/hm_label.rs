@@ -30,24 +30,23 @@ impl BitPackAsWithArgs<BitSlice<u8, Msb0>> for HmLabel {
     {
         let n = source.len() as u32;
         // {n <= m}
-        // here we check if strictly less as (Unary ~n) needs n+1 bits
-        if n < m {
-            writer
-                // hml_short$0
-                .pack(false)?
-                // len:(Unary ~n)
-                .pack_as::<_, Unary>(source.len())?
-                // s:(n * Bit)
-                .pack(source)?;
-            return Ok(());
+        if n > m {
+            return Err(Error::custom("n > m"));
         }
+        // n:(#<= m)
+        let n_bits = bits_for(m);
 
-        let n_bits = m.ilog2() + 1;
-        let v = if source.all() {
-            true
-        } else if source.not_any() {
-            false
-        } else {
+        // choose the shortest representation the same way as the reference
+        // implementation does, so that hashes of dictionaries match
+        if n > 1 && n_bits < 2 * n - 1 && (source.all() || source.not_any()) {
+            writer
+                // hml_same$11
+                .pack_as::<_, NBits<2>>(0b11)?
+                // v:Bit
+                .pack(source[0])?
+                // n:(#<= m)
+                .pack_as_with::<_, VarNBits>(n, n_bits)?;
+        } else if n_bits < n {
             writer
                 // hml_long$10
                 .pack_as::<_, NBits<2>>(0b10)?
@@ -55,19 +54,25 @@ impl BitPackAsWithArgs<BitSlice<u8, Msb0>> for HmLabel {
                 .pack_as_with::<_, VarNBits>(n, n_bits)?
                 // s:(n * Bit)
                 .pack(source)?;
-            return Ok(());
-        };
-        writer
-            // hml_same$11
-            .pack_as::<_, NBits<2>>(0b11)?
-            // v:Bit
-            .pack(v)?
-            // n:(#<= m)
-            .pack_as_with::<_, VarNBits>(n, n_bits)?;
+        } else {
+            writer
+                // hml_short$0
+                .pack(false)?
+                // len:(Unary ~n)
+                .pack_as::<_, Unary>(source.len())?
+                // s:(n * Bit)
+                .pack(source)?;
+        }
         Ok(())
     }
 }
 
+/// Number of bits needed for `(#<= m)`
+#[inline]
+fn bits_for(m: u32) -> u32 {
+    u32::BITS - m.leading_zeros()
+}
+
 impl BitUnpackAsWithArgs<BitVec<u8, Msb0>> for HmLabel {
     /// m
     type Args = u32;
@@ -92,7 +97,7 @@ impl BitUnpackAsWithArgs<BitVec<u8, Msb0>> for HmLabel {
                 // hml_long$10
                 false => {
                     // n:(#<= m)
-                    let n: u32 = reader.unpack_as_with::<_, VarNBits>(m.ilog2() + 1)?;
+                    let n: u32 = reader.unpack_as_with::<_, VarNBits>(bits_for(m))?;
                     // s:(n * Bit)
                     reader.unpack_with(n as usize)
                 }
@@ -101,7 +106,7 @@ impl BitUnpackAsWithArgs<BitVec<u8, Msb0>> for HmLabel {
                     // v:Bit
                     let v: bool = reader.unpack()?;
                     // n:(#<= m)
-                    let n: u32 = reader.unpack_as_with::<_, VarNBits>(m.ilog2() + 1)?;
+                    let n: u32 = reader.unpack_as_with::<_, VarNBits>(bits_for(m))?;
                     Ok(BitVec::repeat(v, n as usize))
                 }
             },
@@ -3,3 +3,4 @@ pub mod aug;
 pub use aug::{Hashmap, HashmapE, HashmapNode};
 mod hm_label;
 pub mod pfx;
+pub mod proof;
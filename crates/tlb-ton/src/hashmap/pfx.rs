@@ -205,6 +205,9 @@ where
             }
             // phmn_fork$1
             true => {
+                if n == 0 {
+                    return Err(Error::custom("key is too long"));
+                }
                 Ok(HashmapNode::Fork(
                     parser
                         // left:^(PfxHashmap n X) right:^(PfxHashmap n X)
@@ -217,3 +220,85 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use tlb::{
+        bits::bitvec::bits,
+        r#as::{Data, NoArgs},
+        ser::{r#as::CellSerializeWrapAsExt, CellSerializeExt},
+        Cell,
+    };
+
+    use super::*;
+
+    /// "0" -> 1, "10" -> 2, "11010" -> 3
+    fn given_cell() -> Cell {
+        (
+            // phme_root$1
+            bits![u8, Msb0; 1].wrap_as::<Data>(),
+            (
+                // hml_short$0 "", phmn_fork$1
+                bits![u8, Msb0; 0,0, 1].wrap_as::<Data>(),
+                // hml_short$0 "", phmn_leaf$0, 1
+                bits![u8, Msb0; 0,0, 0, 0,0,0,0,0,0,0,1].wrap_as::<Ref<Data>>(),
+                (
+                    // hml_short$0 "", phmn_fork$1
+                    bits![u8, Msb0; 0,0, 1].wrap_as::<Data>(),
+                    // hml_short$0 "", phmn_leaf$0, 2
+                    bits![u8, Msb0; 0,0, 0, 0,0,0,0,0,0,1,0].wrap_as::<Ref<Data>>(),
+                    // hml_short$0 "010", phmn_leaf$0, 3
+                    bits![u8, Msb0; 0,1,1,1,0,0,1,0, 0, 0,0,0,0,0,0,1,1].wrap_as::<Ref<Data>>(),
+                )
+                    .wrap_as::<Ref>(),
+            )
+                .wrap_as::<Ref>(),
+        )
+            .to_cell()
+            .unwrap()
+    }
+
+    #[test]
+    fn lookup_prefix() {
+        let hm: HashmapE<u8> = given_cell()
+            .parse_fully_as_with::<_, PfxHashmapE<Data<NoArgs<_>>>>((8, ()))
+            .unwrap();
+
+        assert_eq!(hm.len(), 3);
+        let key = bits![u8, Msb0; 1,1,0,1,0,1,1];
+        assert_eq!(hm.lookup_prefix(key), Some((&key[..5], &3)));
+        let key = bits![u8, Msb0; 0,1];
+        assert_eq!(hm.lookup_prefix(key), Some((&key[..1], &1)));
+        assert_eq!(hm.lookup_prefix(bits![u8, Msb0; 1]), None);
+        assert_eq!(hm.lookup_prefix(bits![u8, Msb0; 1,1,0,0,0]), None);
+    }
+
+    #[test]
+    fn insert_remove() {
+        let mut hm = HashmapE::<u8>::new();
+        assert_eq!(hm.insert(bits![u8, Msb0; 1,1,0,1,0], 3).unwrap(), None);
+        assert_eq!(hm.insert(bits![u8, Msb0; 0], 1).unwrap(), None);
+        assert_eq!(hm.insert(bits![u8, Msb0; 1,0], 7).unwrap(), None);
+        assert_eq!(hm.insert(bits![u8, Msb0; 1,0], 2).unwrap(), Some(7));
+
+        let mut builder = Cell::builder();
+        builder
+            .store_as_with::<_, &PfxHashmapE<Data<NoArgs<_>>>>(&hm, (8, ()))
+            .unwrap();
+        assert_eq!(builder.into_cell(), given_cell());
+
+        // prefix code violations
+        assert!(hm.insert(bits![u8, Msb0; 1,1,0], 4).is_err());
+        assert!(hm.insert(bits![u8, Msb0; 1,0,1], 4).is_err());
+        assert!(hm.insert(bits![u8, Msb0; 1], 4).is_err());
+        assert_eq!(hm.len(), 3);
+
+        assert_eq!(hm.remove(bits![u8, Msb0; 1,1]), None);
+        assert_eq!(hm.remove(bits![u8, Msb0; 1,0]), Some(2));
+        assert_eq!(hm.len(), 2);
+        assert_eq!(hm.get(bits![u8, Msb0; 1,1,0,1,0]), Some(&3));
+        assert_eq!(hm.remove(bits![u8, Msb0; 0]), Some(1));
+        assert_eq!(hm.remove(bits![u8, Msb0; 1,1,0,1,0]), Some(3));
+        assert!(hm.is_empty());
+    }
+}
@@ -0,0 +1,275 @@
+//! [Merkle proofs](https://docs.ton.org/develop/data-formats/exotic-cells#merkle-proof)
+//! of inclusion or absence of keys in [`HashmapE`](super::HashmapE)
+
+use std::sync::Arc;
+
+use tlb::{
+    bits::{
+        bitvec::{order::Msb0, slice::BitSlice},
+        de::BitReaderExt,
+    },
+    de::CellParser,
+    Cell, CellType, Error, ResultExt, StringError,
+};
+
+use super::hm_label::HmLabel;
+
+/// Create [Merkle proof](Cell::merkle_proof) for given `keys` in
+/// `HashmapE n X` stored at the beginning of `root` cell.
+///
+/// All cells on the paths to given keys (including leaf values) are kept,
+/// while the rest of branches are [pruned](Cell::pruned_branch). The proof
+/// can then be checked against [`root.hash()`](Cell::hash) with
+/// [`verify_proof()`].
+pub fn create_proof<K>(
+    root: &Cell,
+    n: u32,
+    keys: impl IntoIterator<Item = K>,
+) -> Result<Cell, StringError>
+where
+    K: AsRef<BitSlice<u8, Msb0>>,
+{
+    let keys: Vec<K> = keys.into_iter().collect();
+    let keys: Vec<&BitSlice<u8, Msb0>> = keys.iter().map(AsRef::as_ref).collect();
+    if let Some(key) = keys.iter().find(|k| k.len() != n as usize) {
+        return Err(Error::custom(format!(
+            "invalid key length: {}, expected: {n}",
+            key.len()
+        )));
+    }
+
+    let mut parser = root.parser();
+    let is_root: bool = parser.unpack()?;
+    let mut refs = root.references.iter();
+    let mut proven = Cell {
+        data: root.data.clone(),
+        references: Vec::with_capacity(root.references.len()),
+        is_exotic: root.is_exotic,
+    };
+    if is_root {
+        // hme_root$1 {n:#} {X:Type} root:^(Hashmap n X) = HashmapE n X;
+        let edge = refs
+            .next()
+            .ok_or_else(|| Error::custom("no root reference"))?;
+        proven
+            .references
+            .push(prove_edge(edge, n, &keys).context("root")?);
+    }
+    for r in refs {
+        proven.references.push(r.pruned_branch(1)?.into());
+    }
+
+    Ok(Cell::merkle_proof(proven))
+}
+
+/// Keeps the edge cell if at least one of `keys` passes through it
+fn prove_edge(
+    edge: &Arc<Cell>,
+    n: u32,
+    keys: &[&BitSlice<u8, Msb0>],
+) -> Result<Arc<Cell>, StringError> {
+    if keys.is_empty() {
+        return Ok(edge.pruned_branch(1)?.into());
+    }
+
+    let mut parser = edge.parser();
+    // label:(HmLabel ~l n)
+    let prefix = parser.unpack_as_with::<_, HmLabel>(n).context("label")?;
+    let m = n - prefix.len() as u32;
+    // hmn_leaf#_ {X:Type} value:X = HashmapNode 0 X;
+    if m == 0 {
+        return Ok(edge.clone());
+    }
+
+    // hmn_fork#_ {n:#} {X:Type} left:^(Hashmap n X)
+    //            right:^(Hashmap n X) = HashmapNode (n + 1) X;
+    let [left, right]: [Vec<&BitSlice<u8, Msb0>>; 2] = [false, true].map(|bit| {
+        keys.iter()
+            .filter_map(|key| key.strip_prefix(&prefix))
+            .filter_map(|key| key.split_first())
+            .filter(|(b, _)| *b == bit)
+            .map(|(_, key)| key)
+            .collect()
+    });
+    let mut refs = edge.references.iter();
+    let mut proven = Cell {
+        data: edge.data.clone(),
+        references: Vec::with_capacity(edge.references.len()),
+        is_exotic: edge.is_exotic,
+    };
+    for (i, keys) in [left, right].iter().enumerate() {
+        let child = refs
+            .next()
+            .ok_or_else(|| Error::custom("no more references left"))?;
+        proven
+            .references
+            .push(prove_edge(child, m - 1, keys).with_context(|| format!("[{i}]"))?);
+    }
+    for r in refs {
+        proven.references.push(r.pruned_branch(1)?.into());
+    }
+
+    Ok(proven.into())
+}
+
+/// Verify [Merkle proof](Cell::merkle_proof) created by [`create_proof()`]
+/// against trusted `root_hash` and look up given `key` in it.
+///
+/// Returns [`CellParser`] positioned at the value associated with `key` or
+/// `None` if the proof shows that there is no such key. An error is returned
+/// if the proof is invalid or does not cover given `key`.
+pub fn verify_proof<'a>(
+    proof: &'a Cell,
+    root_hash: &[u8; 32],
+    n: u32,
+    key: impl AsRef<BitSlice<u8, Msb0>>,
+) -> Result<Option<CellParser<'a>>, StringError> {
+    let mut key = key.as_ref();
+    if key.len() != n as usize {
+        return Err(Error::custom(format!(
+            "invalid key length: {}, expected: {n}",
+            key.len()
+        )));
+    }
+
+    if proof.cell_type()? != CellType::MerkleProof {
+        return Err(Error::custom("not a Merkle proof"));
+    }
+    let mut parser = proof.parser();
+    let _tag: u8 = parser.unpack()?;
+    // virtual_hash:bits256
+    let virtual_hash: [u8; 32] = parser.unpack()?;
+    let root = &proof.references[0];
+    if virtual_hash != *root_hash || root.hash_at(0) != *root_hash {
+        return Err(Error::custom("root hash mismatch"));
+    }
+
+    let mut parser = ensure_ordinary(root).context("root")?;
+    // hme_empty$0 {n:#} {X:Type} = HashmapE n X;
+    if !parser.unpack::<bool>()? {
+        return Ok(None);
+    }
+    // hme_root$1 {n:#} {X:Type} root:^(Hashmap n X) = HashmapE n X;
+    let mut edge = root
+        .references
+        .first()
+        .ok_or_else(|| Error::custom("no root reference"))?;
+    let mut m = n;
+    loop {
+        let mut parser = ensure_ordinary(edge)?;
+        // label:(HmLabel ~l n)
+        let prefix = parser.unpack_as_with::<_, HmLabel>(m).context("label")?;
+        let Some(rest) = key.strip_prefix(&prefix) else {
+            // key diverges from the only existing path
+            return Ok(None);
+        };
+        m -= prefix.len() as u32;
+        // hmn_leaf#_ {X:Type} value:X = HashmapNode 0 X;
+        let Some((is_right, rest)) = rest.split_first() else {
+            return Ok(Some(parser));
+        };
+        // hmn_fork#_ {n:#} {X:Type} left:^(Hashmap n X)
+        //            right:^(Hashmap n X) = HashmapNode (n + 1) X;
+        edge = edge
+            .references
+            .get(*is_right as usize)
+            .ok_or_else(|| Error::custom("no fork reference"))?;
+        key = rest;
+        m -= 1;
+    }
+}
+
+/// Returns parser for the cell if it was not pruned from the proof
+#[inline]
+fn ensure_ordinary(cell: &Cell) -> Result<CellParser<'_>, StringError> {
+    if cell.is_exotic {
+        return Err(Error::custom("key is not covered by the proof"));
+    }
+    Ok(cell.parser())
+}
+
+#[cfg(test)]
+mod tests {
+    use tlb::{
+        bits::bitvec::{bits, view::AsBits},
+        r#as::{Data, NoArgs},
+    };
+
+    use crate::hashmap::{aug::HashmapAugNode, Hashmap, HashmapE, HashmapNode};
+
+    use super::*;
+
+    /// 1 -> 777, 17 -> 111, 128 -> 777, 129 -> 42
+    fn given_dict() -> Cell {
+        let leaf = |prefix: &BitSlice<u8, Msb0>, v: u16| {
+            Box::new(Hashmap::new(
+                prefix,
+                HashmapAugNode::new(HashmapNode::Leaf(v), ()),
+            ))
+        };
+        let fork = |prefix: &BitSlice<u8, Msb0>, children| {
+            Hashmap::new(prefix, HashmapAugNode::new(HashmapNode::Fork(children), ()))
+        };
+        let hm = HashmapE::Root(fork(
+            bits![u8, Msb0;],
+            [
+                Box::new(fork(
+                    bits![u8, Msb0; 0, 0],
+                    [
+                        leaf(bits![u8, Msb0; 0, 0, 0, 1], 777),
+                        leaf(bits![u8, Msb0; 0, 0, 0, 1], 111),
+                    ],
+                )),
+                Box::new(fork(
+                    bits![u8, Msb0; 0, 0, 0, 0, 0, 0],
+                    [leaf(bits![u8, Msb0;], 777), leaf(bits![u8, Msb0;], 42)],
+                )),
+            ],
+        ));
+        assert_eq!(hm.get(17u8.to_be_bytes().as_bits()), Some(&111));
+
+        let mut builder = Cell::builder();
+        builder
+            .store_as_with::<_, HashmapE<Data<NoArgs<_>>, NoArgs<_>>>(hm, (8, (), ()))
+            .unwrap();
+        builder.into_cell()
+    }
+
+    #[test]
+    fn inclusion_and_absence() {
+        let root = given_dict();
+        let root_hash = root.hash();
+        let proof = create_proof(
+            &root,
+            8,
+            [129u8, 16]
+                .map(|k| k.to_be_bytes())
+                .iter()
+                .map(AsBits::as_bits),
+        )
+        .unwrap();
+
+        assert_eq!(proof.level(), 0);
+        let value: u16 = verify_proof(&proof, &root_hash, 8, 129u8.to_be_bytes().as_bits())
+            .unwrap()
+            .unwrap()
+            .parse_as::<_, Data>()
+            .unwrap();
+        assert_eq!(value, 42);
+        assert!(
+            verify_proof(&proof, &root_hash, 8, 16u8.to_be_bytes().as_bits())
+                .unwrap()
+                .is_none()
+        );
+        // pruned away
+        assert!(verify_proof(&proof, &root_hash, 8, 1u8.to_be_bytes().as_bits()).is_err());
+    }
+
+    #[test]
+    fn wrong_root_hash() {
+        let root = given_dict();
+        let proof = create_proof(&root, 8, [1u8.to_be_bytes().as_bits::<Msb0>()]).unwrap();
+
+        assert!(verify_proof(&proof, &[0; 32], 8, 1u8.to_be_bytes().as_bits()).is_err());
+    }
+}
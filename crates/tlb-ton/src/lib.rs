@@ -1,8 +1,11 @@
 #![doc = include_str!("../README.md")]
+pub mod account;
 pub mod action;
 mod address;
 pub mod bin_tree;
+pub mod block;
 pub mod boc;
+pub mod config;
 pub mod currency;
 pub mod hashmap;
 pub mod library;
@@ -10,5 +13,6 @@ pub mod list;
 pub mod message;
 pub mod state_init;
 mod timestamp;
+pub mod transaction;
 
 pub use self::{address::*, timestamp::*};
@@ -38,11 +38,12 @@ where
         let mut v = Vec::new();
         let mut p: CellParser<'de> = parser.parse()?;
         while !p.no_references_left() {
+            let prev = p.parse_as::<_, Ref>()?;
             v.push(
                 p.parse_as::<_, As>()
                     .with_context(|| format!("[{}]", v.len()))?,
             );
-            p = p.parse_as::<_, Ref>()?;
+            p = prev;
         }
         v.reverse();
         Ok(v)
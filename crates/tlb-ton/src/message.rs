@@ -8,16 +8,17 @@ use tlb::{
         ser::{BitPack, BitWriter, BitWriterExt},
     },
     de::{CellDeserialize, CellParser, CellParserError},
-    r#as::{DefaultOnNone, EitherInlineOrRef},
+    either::Either,
+    r#as::{DefaultOnNone, EitherInlineOrRef, Ref, Same},
     ser::{CellBuilder, CellBuilderError, CellSerialize, CellSerializeExt},
-    Cell, ResultExt,
+    Cell, Error, ResultExt,
 };
 
 use crate::{
     currency::{CurrencyCollection, ExtraCurrencyCollection, Grams},
     hashmap::HashmapE,
     state_init::StateInit,
-    MsgAddress, UnixTimestamp,
+    MsgAddress, MsgAddressExt, UnixTimestamp,
 };
 
 /// [Message](https://docs.ton.org/develop/data-formats/msg-tlb#message-tl-b)
@@ -53,6 +54,41 @@ where
             body: self.body.to_cell()?,
         })
     }
+
+    /// Representation hash of the message cell
+    #[inline]
+    pub fn hash(&self) -> Result<[u8; 32], CellBuilderError> {
+        Ok(self.to_cell()?.hash())
+    }
+
+    /// [TEP-467](https://github.com/ton-blockchain/TEPs/blob/master/text/0467-normalized-message-hash.md)
+    /// normalized hash of inbound external message.
+    ///
+    /// It does not depend on `src`, `import_fee`, `init` and placement of
+    /// the body, so it can be used to find the transaction that processed
+    /// the message.
+    pub fn normalized_hash(&self) -> Result<[u8; 32], CellBuilderError> {
+        let CommonMsgInfo::ExternalIn(info) = &self.info else {
+            return Err(Error::custom(
+                "normalized hash is defined only for inbound external messages",
+            ));
+        };
+        let mut builder = Cell::builder();
+        // info:CommonMsgInfo
+        CommonMsgInfo::ExternalIn(ExternalInMsgInfo {
+            src: MsgAddressExt::None,
+            dst: info.dst,
+            import_fee: BigUint::ZERO,
+        })
+        .store(&mut builder)?;
+        builder
+            // init:(Maybe (Either StateInit ^StateInit))
+            .pack(false)?
+            // body:(Either X ^X)
+            .pack(true)?
+            .store_as::<_, Ref>(&self.body)?;
+        Ok(builder.into_cell().hash())
+    }
 }
 
 impl Message<()> {
@@ -74,13 +110,34 @@ where
     ID: CellSerialize,
 {
     fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        let init = self
+            .init
+            .as_ref()
+            .map(CellSerializeExt::to_cell)
+            .transpose()
+            .context("init")?;
+        let body = self.body.to_cell().context("body")?;
+
+        // info:CommonMsgInfo
+        builder.store(&self.info)?;
+        // init:(Maybe (Either StateInit ^StateInit))
+        builder.pack(init.is_some())?;
+        if let Some(init) = init {
+            // keep StateInit inline only if the body still fits inline after it
+            let inline = init.data.len() + body.data.len() + 2 <= builder.capacity_left()
+                && init.references.len() + body.references.len() <= builder.references_left();
+            builder
+                .store_as::<_, Either<Same, Ref>>(if inline {
+                    Either::Left(init)
+                } else {
+                    Either::Right(init)
+                })
+                .context("init")?;
+        }
+        // body:(Either X ^X)
         builder
-            // info:CommonMsgInfo
-            .store(&self.info)?
-            // init:(Maybe (Either StateInit ^StateInit))
-            .store_as::<_, &Option<EitherInlineOrRef>>(&self.init)?
-            // body:(Either X ^X)
-            .store_as::<_, EitherInlineOrRef>(&self.body)?;
+            .store_as::<_, EitherInlineOrRef>(body)
+            .context("body")?;
         Ok(())
     }
 }
@@ -105,6 +162,343 @@ where
     }
 }
 
+/// Builder for [`Message`] with validation of its fields.
+///
+/// Placement of state init and body (inline or in a reference) is decided
+/// on serialization of the [`Message`] so that it fits into a single cell.
+/// ```rust
+/// # use tlb::{r#as::Data, ser::r#as::CellSerializeWrapAsExt};
+/// # use tlb_ton::{message::MessageBuilder, currency::ONE_TON};
+/// let msg = MessageBuilder::internal(
+///     "EQAWezezpqKTbO6xjCussXDdIeJ7XxTcErjA6uD3T3r7AwTk"
+///         .parse()
+///         .unwrap(),
+///     ONE_TON.clone(),
+/// )
+/// .bounce(false)
+/// .body(0x12345678u32.wrap_as::<Data>())
+/// .build()
+/// .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+#[must_use]
+pub struct MessageBuilder<T = (), IC = Cell, ID = Cell> {
+    info: CommonMsgInfo,
+    init: Option<StateInit<IC, ID>>,
+    body: T,
+    error: Option<String>,
+}
+
+impl MessageBuilder {
+    /// Internal message carrying `value` to `dst`, bounceable by default
+    #[inline]
+    pub fn internal(dst: MsgAddress, value: BigUint) -> Self {
+        Self::new(CommonMsgInfo::transfer(dst, value, true))
+    }
+
+    /// Inbound external message to `dst`
+    #[inline]
+    pub fn external_in(dst: MsgAddress) -> Self {
+        Self::new(CommonMsgInfo::ExternalIn(ExternalInMsgInfo {
+            src: MsgAddressExt::None,
+            dst,
+            import_fee: BigUint::ZERO,
+        }))
+    }
+
+    /// Outbound external message to `dst`
+    #[inline]
+    pub fn external_out(dst: MsgAddressExt) -> Self {
+        Self::new(CommonMsgInfo::ExternalOut(ExternalOutMsgInfo {
+            src: MsgAddress::NULL,
+            dst,
+            created_lt: 0,
+            created_at: DateTime::UNIX_EPOCH,
+        }))
+    }
+
+    #[inline]
+    fn new(info: CommonMsgInfo) -> Self {
+        Self {
+            info,
+            init: None,
+            body: (),
+            error: None,
+        }
+    }
+}
+
+impl<T, IC, ID> MessageBuilder<T, IC, ID> {
+    /// Set source address of internal or outbound external message
+    #[inline]
+    pub fn src(mut self, src: MsgAddress) -> Self {
+        match &mut self.info {
+            CommonMsgInfo::Internal(info) => info.src = src,
+            CommonMsgInfo::ExternalOut(info) => info.src = src,
+            CommonMsgInfo::ExternalIn(_) => self.not_applicable("src"),
+        }
+        self
+    }
+
+    /// Set source address of inbound external message
+    #[inline]
+    pub fn external_src(mut self, src: MsgAddressExt) -> Self {
+        match &mut self.info {
+            CommonMsgInfo::ExternalIn(info) => info.src = src,
+            _ => self.not_applicable("external_src"),
+        }
+        self
+    }
+
+    #[inline]
+    pub fn bounce(mut self, bounce: bool) -> Self {
+        match &mut self.info {
+            CommonMsgInfo::Internal(info) => info.bounce = bounce,
+            _ => self.not_applicable("bounce"),
+        }
+        self
+    }
+
+    #[inline]
+    pub fn bounced(mut self, bounced: bool) -> Self {
+        match &mut self.info {
+            CommonMsgInfo::Internal(info) => info.bounced = bounced,
+            _ => self.not_applicable("bounced"),
+        }
+        self
+    }
+
+    #[inline]
+    pub fn ihr_disabled(mut self, ihr_disabled: bool) -> Self {
+        match &mut self.info {
+            CommonMsgInfo::Internal(info) => info.ihr_disabled = ihr_disabled,
+            _ => self.not_applicable("ihr_disabled"),
+        }
+        self
+    }
+
+    #[inline]
+    pub fn extra_currencies(mut self, other: ExtraCurrencyCollection) -> Self {
+        match &mut self.info {
+            CommonMsgInfo::Internal(info) => info.value.other = other,
+            _ => self.not_applicable("extra_currencies"),
+        }
+        self
+    }
+
+    /// See [`MsgForwardPrices::remaining_fwd_fee()`](crate::config::MsgForwardPrices::remaining_fwd_fee)
+    #[inline]
+    pub fn fwd_fee(mut self, fwd_fee: BigUint) -> Self {
+        match &mut self.info {
+            CommonMsgInfo::Internal(info) => info.fwd_fee = fwd_fee,
+            _ => self.not_applicable("fwd_fee"),
+        }
+        self
+    }
+
+    #[inline]
+    pub fn import_fee(mut self, import_fee: BigUint) -> Self {
+        match &mut self.info {
+            CommonMsgInfo::ExternalIn(info) => info.import_fee = import_fee,
+            _ => self.not_applicable("import_fee"),
+        }
+        self
+    }
+
+    #[inline]
+    pub fn created_lt(mut self, created_lt: u64) -> Self {
+        match &mut self.info {
+            CommonMsgInfo::Internal(info) => info.created_lt = created_lt,
+            CommonMsgInfo::ExternalOut(info) => info.created_lt = created_lt,
+            CommonMsgInfo::ExternalIn(_) => self.not_applicable("created_lt"),
+        }
+        self
+    }
+
+    #[inline]
+    pub fn created_at(mut self, created_at: DateTime<Utc>) -> Self {
+        match &mut self.info {
+            CommonMsgInfo::Internal(info) => info.created_at = Some(created_at),
+            CommonMsgInfo::ExternalOut(info) => info.created_at = created_at,
+            CommonMsgInfo::ExternalIn(_) => self.not_applicable("created_at"),
+        }
+        self
+    }
+
+    #[inline]
+    pub fn state_init<C, D>(self, state_init: StateInit<C, D>) -> MessageBuilder<T, C, D> {
+        MessageBuilder {
+            info: self.info,
+            init: Some(state_init),
+            body: self.body,
+            error: self.error,
+        }
+    }
+
+    #[inline]
+    pub fn body<B>(self, body: B) -> MessageBuilder<B, IC, ID> {
+        MessageBuilder {
+            info: self.info,
+            init: self.init,
+            body,
+            error: self.error,
+        }
+    }
+
+    fn not_applicable(&mut self, field: &str) {
+        let kind = match self.info {
+            CommonMsgInfo::Internal(_) => "internal",
+            CommonMsgInfo::ExternalIn(_) => "inbound external",
+            CommonMsgInfo::ExternalOut(_) => "outbound external",
+        };
+        self.error
+            .get_or_insert_with(|| format!("{field} is not applicable to {kind} message"));
+    }
+}
+
+impl<T, IC, ID> MessageBuilder<T, IC, ID>
+where
+    T: CellSerialize,
+    IC: CellSerialize,
+    ID: CellSerialize,
+{
+    /// Validate the message and return it in normalized form
+    pub fn build(self) -> Result<Message, CellBuilderError> {
+        if let Some(err) = self.error {
+            return Err(Error::custom(err));
+        }
+        match &self.info {
+            CommonMsgInfo::Internal(info) => {
+                if info.dst.is_null() {
+                    return Err(Error::custom("dst: addr_none is not allowed"));
+                }
+                if info.bounced && info.bounce {
+                    return Err(Error::custom("bounced message cannot be bounceable"));
+                }
+            }
+            CommonMsgInfo::ExternalIn(info) => {
+                if info.dst.is_null() {
+                    return Err(Error::custom("dst: addr_none is not allowed"));
+                }
+            }
+            CommonMsgInfo::ExternalOut(_) => {}
+        }
+        let msg = Message {
+            info: self.info,
+            init: self.init,
+            body: self.body,
+        }
+        .normalize()?;
+        // ensure that it fits into a single cell
+        msg.to_cell()?;
+        Ok(msg)
+    }
+}
+
+/// Body of a bounced message as created by the node
+/// ```tlb
+/// _#ffffffff original_body:(bits 256) = BouncedBody;
+/// _#ffffffff original_body:(bits 256) full_original_body:^Cell = BouncedBody;
+/// ```
+/// where `original_body` is the original body truncated to its first 256
+/// bits (or less, if it is shorter) without references. Newer versions of
+/// the node also keep the complete original body in a reference.
+/// Fields that could be recovered are `Some`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BouncedBody<T = Cell> {
+    /// Whether only the first 256 bits of the original body are available
+    pub truncated: bool,
+    /// First 32 bits of the original body
+    pub op: Option<u32>,
+    /// Next 64 bits of the original body following [`op`](BouncedBody::op)
+    pub query_id: Option<u64>,
+    /// Original body, if it could be fully parsed from what is available
+    pub body: Option<T>,
+}
+
+impl BouncedBody {
+    const PREFIX: u32 = 0xffffffff;
+    const TRUNCATED_BITS: usize = 256;
+
+    /// Returns whether the body starts with bounce prefix
+    #[inline]
+    pub fn is_bounced(body: &Cell) -> bool {
+        body.parser().unpack::<u32>().ok() == Some(Self::PREFIX)
+    }
+}
+
+impl<T> CellSerialize for BouncedBody<T>
+where
+    T: CellSerialize,
+{
+    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        builder.pack(BouncedBody::PREFIX)?;
+        let Some(body) = &self.body else {
+            if !self.truncated {
+                return Err(Error::custom("body: missing original body"));
+            }
+            // original_body:(bits 256)
+            match (self.op, self.query_id) {
+                (Some(op), Some(query_id)) => builder.pack(op)?.pack(query_id)?,
+                (None, None) => builder,
+                _ => return Err(Error::custom("op and query_id must be set together")),
+            };
+            return Ok(());
+        };
+        let body = body.to_cell()?;
+        // original_body:(bits 256)
+        builder.pack(&body.data[..body.data.len().min(BouncedBody::TRUNCATED_BITS)])?;
+        if !self.truncated {
+            // full_original_body:^Cell
+            builder.store_as::<_, Ref>(body)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'de, T> CellDeserialize<'de> for BouncedBody<T>
+where
+    T: CellDeserialize<'de>,
+{
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        let prefix: u32 = parser.unpack()?;
+        if prefix != BouncedBody::PREFIX {
+            return Err(Error::custom(format!(
+                "unknown bounced body prefix: {prefix:#0x}"
+            )));
+        }
+        if parser.bits_left() > BouncedBody::TRUNCATED_BITS {
+            return Err(Error::custom(format!(
+                "original_body: more than {} bits",
+                BouncedBody::TRUNCATED_BITS
+            )));
+        }
+        // original_body:(bits 256)
+        let mut head = parser.clone();
+        let op = head.unpack().ok();
+        let query_id = head.unpack().ok();
+        if parser.no_references_left() {
+            let body = T::parse(&mut parser.clone()).ok();
+            parser.skip(parser.bits_left())?;
+            return Ok(Self {
+                truncated: true,
+                op,
+                query_id,
+                body,
+            });
+        }
+        parser.skip(parser.bits_left())?;
+        // full_original_body:^Cell
+        let mut original: CellParser<'de> = parser.parse_as::<_, Ref>()?;
+        Ok(Self {
+            truncated: false,
+            op,
+            query_id,
+            body: Some(original.parse().context("full_original_body")?),
+        })
+    }
+}
+
 /// `info` field for [`Message`]
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum CommonMsgInfo {
@@ -267,7 +661,7 @@ impl<'de> CellDeserialize<'de> for InternalMsgInfo {
 /// ```
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ExternalInMsgInfo {
-    pub src: MsgAddress,
+    pub src: MsgAddressExt,
     pub dst: MsgAddress,
     pub import_fee: BigUint,
 }
@@ -278,7 +672,7 @@ impl BitPack for ExternalInMsgInfo {
         W: BitWriter,
     {
         writer
-            .pack(self.src)?
+            .pack(&self.src)?
             .pack(self.dst)?
             .pack_as::<_, &Grams>(&self.import_fee)?;
         Ok(())
@@ -306,7 +700,7 @@ impl BitUnpack for ExternalInMsgInfo {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ExternalOutMsgInfo {
     pub src: MsgAddress,
-    pub dst: MsgAddress,
+    pub dst: MsgAddressExt,
     pub created_lt: u64,
     pub created_at: DateTime<Utc>,
 }
@@ -318,7 +712,7 @@ impl BitPack for ExternalOutMsgInfo {
     {
         writer
             .pack(self.src)?
-            .pack(self.dst)?
+            .pack(&self.dst)?
             .pack(self.created_lt)?
             .pack_as::<_, UnixTimestamp>(self.created_at)?;
         Ok(())
@@ -341,7 +735,11 @@ impl BitUnpack for ExternalOutMsgInfo {
 
 #[cfg(test)]
 mod tests {
-    use tlb::ser::CellSerializeExt;
+    use tlb::{
+        bits::bitvec::{bitvec, order::Msb0},
+        r#as::Data,
+        ser::r#as::CellSerializeWrapAsExt,
+    };
 
     use super::*;
 
@@ -370,6 +768,321 @@ mod tests {
         assert_eq!(got, msg);
     }
 
+    #[test]
+    fn builder_internal() {
+        let dst = MsgAddress {
+            workchain_id: 0,
+            address: [1; 32],
+        };
+        let msg = MessageBuilder::internal(dst, BigUint::from(1_000u32))
+            .bounce(false)
+            .created_lt(5)
+            .state_init(StateInit::<Cell, Cell> {
+                code: Some(Cell::default()),
+                ..Default::default()
+            })
+            .body([0xffu8; 32].wrap_as::<Data>())
+            .build()
+            .unwrap();
+
+        let CommonMsgInfo::Internal(info) = &msg.info else {
+            unreachable!()
+        };
+        assert!(!info.bounce);
+        assert_eq!(info.created_lt, 5);
+
+        let cell = msg.to_cell().unwrap();
+        // both state init and body fit inline
+        assert_eq!(cell.references.len(), 1);
+        let got: Message = cell.parse_fully().unwrap();
+        assert_eq!(got, msg);
+    }
+
+    #[test]
+    fn builder_placement() {
+        let dst = MsgAddress {
+            workchain_id: 0,
+            address: [1; 32],
+        };
+        let big = Cell {
+            data: bitvec![u8, Msb0; 1; 1000],
+            ..Default::default()
+        };
+        let msg = MessageBuilder::internal(dst, BigUint::from(1_000u32))
+            .state_init(StateInit::<Cell, Cell> {
+                code: Some(Cell::default()),
+                data: Some(Cell::default()),
+                ..Default::default()
+            })
+            .body(big.clone())
+            .build()
+            .unwrap();
+        let cell = msg.to_cell().unwrap();
+        assert_eq!(cell.references.len(), 2);
+        assert_eq!(*cell.references[1], big);
+        assert_eq!(cell.parse_fully::<Message>().unwrap(), msg);
+
+        // body with all 4 references does not fit next to state init
+        let wide = Cell {
+            references: vec![Cell::default().into(); 4],
+            ..Default::default()
+        };
+        let msg = MessageBuilder::external_in(dst)
+            .state_init(StateInit::<Cell, Cell> {
+                code: Some(Cell::default()),
+                ..Default::default()
+            })
+            .body(wide.clone())
+            .build()
+            .unwrap();
+        let cell = msg.to_cell().unwrap();
+        assert_eq!(cell.references.len(), 2);
+        assert_eq!(*cell.references[1], wide);
+        assert_eq!(cell.parse_fully::<Message>().unwrap(), msg);
+    }
+
+    #[test]
+    fn builder_validation() {
+        let dst = MsgAddress {
+            workchain_id: 0,
+            address: [1; 32],
+        };
+        assert!(MessageBuilder::internal(MsgAddress::NULL, BigUint::ZERO)
+            .build()
+            .is_err());
+        assert!(MessageBuilder::internal(dst, BigUint::ZERO)
+            .bounced(true)
+            .build()
+            .is_err());
+        assert!(MessageBuilder::internal(dst, BigUint::ZERO)
+            .bounce(false)
+            .bounced(true)
+            .build()
+            .is_ok());
+        assert!(MessageBuilder::external_in(dst)
+            .bounce(true)
+            .build()
+            .is_err());
+        assert!(MessageBuilder::external_out(MsgAddressExt::None)
+            .src(dst)
+            .import_fee(BigUint::ZERO)
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn normalized_hash() {
+        let dst = MsgAddress {
+            workchain_id: 0,
+            address: [1; 32],
+        };
+        let body = 0xdeadbeef_u32.wrap_as::<Data>().to_cell().unwrap();
+        let msg = MessageBuilder::external_in(dst)
+            .body(body.clone())
+            .build()
+            .unwrap();
+        let with_extras = MessageBuilder::external_in(dst)
+            .external_src(MsgAddressExt::Extern(bitvec![u8, Msb0; 1, 0, 1]))
+            .import_fee(BigUint::from(100u32))
+            .state_init(StateInit::<Cell, Cell>::default())
+            .body(body.clone())
+            .build()
+            .unwrap();
+        assert_ne!(msg.hash().unwrap(), with_extras.hash().unwrap());
+        assert_eq!(
+            msg.normalized_hash().unwrap(),
+            with_extras.normalized_hash().unwrap()
+        );
+
+        // body is inline in the original message, but in a reference in normalized one
+        let mut builder = Cell::builder();
+        msg.info.store(&mut builder).unwrap();
+        builder
+            .pack([false, true])
+            .unwrap()
+            .store_as::<_, Ref>(&body)
+            .unwrap();
+        assert_eq!(msg.normalized_hash().unwrap(), builder.into_cell().hash());
+
+        assert!(Message::<()>::transfer(dst, BigUint::ZERO, false)
+            .normalized_hash()
+            .is_err());
+    }
+
+    /// Layout of common operation prefix: `op:uint32 query_id:uint64 amount:Grams dst:MsgAddress`
+    #[derive(Debug, PartialEq)]
+    struct Transfer {
+        query_id: u64,
+        amount: BigUint,
+        dst: MsgAddress,
+    }
+
+    impl CellSerialize for Transfer {
+        fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+            builder
+                .pack(0x0f8a7ea5u32)?
+                .pack(self.query_id)?
+                .pack_as::<_, &Grams>(&self.amount)?
+                .pack(self.dst)?;
+            Ok(())
+        }
+    }
+
+    impl<'de> CellDeserialize<'de> for Transfer {
+        fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+            let _op: u32 = parser.unpack()?;
+            Ok(Self {
+                query_id: parser.unpack()?,
+                amount: parser.unpack_as::<_, Grams>()?,
+                dst: parser.unpack().context("dst")?,
+            })
+        }
+    }
+
+    #[test]
+    fn bounced_body_truncated() {
+        let transfer = Transfer {
+            query_id: 42,
+            amount: BigUint::from(1_000_000u32),
+            dst: MsgAddress {
+                workchain_id: 0,
+                address: [1; 32],
+            },
+        };
+        let bounced = BouncedBody {
+            truncated: true,
+            op: Some(0x0f8a7ea5),
+            query_id: Some(42),
+            body: Some(transfer),
+        };
+        let cell = bounced.to_cell().unwrap();
+        assert!(BouncedBody::is_bounced(&cell));
+        assert_eq!(cell.data.len(), 32 + 256);
+
+        // dst does not fit into first 256 bits
+        let got: BouncedBody<Transfer> = cell.parse_fully().unwrap();
+        assert_eq!(
+            got,
+            BouncedBody {
+                body: None,
+                ..bounced
+            }
+        );
+    }
+
+    #[test]
+    fn bounced_body_full() {
+        let transfer = Transfer {
+            query_id: 42,
+            amount: BigUint::from(1_000_000u32),
+            dst: MsgAddress {
+                workchain_id: 0,
+                address: [1; 32],
+            },
+        };
+        let bounced = BouncedBody {
+            truncated: false,
+            op: Some(0x0f8a7ea5),
+            query_id: Some(42),
+            body: Some(transfer),
+        };
+        let cell = bounced.to_cell().unwrap();
+        assert_eq!(cell.data.len(), 32 + 256);
+        assert_eq!(cell.references.len(), 1);
+        let got: BouncedBody<Transfer> = cell.parse_fully().unwrap();
+        assert_eq!(got, bounced);
+
+        assert!(!BouncedBody::is_bounced(&Cell::default()));
+    }
+
+    #[test]
+    fn bounced_body_without_original() {
+        let bounced = BouncedBody::<Transfer> {
+            truncated: true,
+            op: Some(0x0f8a7ea5),
+            query_id: Some(42),
+            body: None,
+        };
+        let cell = bounced.to_cell().unwrap();
+        assert_eq!(cell.data.len(), 32 + 32 + 64);
+        let got: BouncedBody<Transfer> = cell.parse_fully().unwrap();
+        assert_eq!(got, bounced);
+
+        assert!(BouncedBody::<Transfer> {
+            query_id: None,
+            ..bounced
+        }
+        .to_cell()
+        .is_err());
+    }
+
+    #[test]
+    fn bounced_body_node_layout() {
+        let dst = MsgAddress {
+            workchain_id: 0,
+            address: [1; 32],
+        };
+        // op:uint32 query_id:uint64 amount:Grams dst:MsgAddress
+        let mut original = Cell::builder();
+        original
+            .pack(0x0f8a7ea5u32)
+            .unwrap()
+            .pack(42u64)
+            .unwrap()
+            // len:(## 4) value:(uint (len * 8))
+            .pack_as::<_, NBits<4>>(3u8)
+            .unwrap()
+            .pack_as::<_, NBits<24>>(1_000_000u32)
+            .unwrap()
+            .pack(dst)
+            .unwrap();
+        let original = original.into_cell();
+        assert!(original.data.len() > 256);
+
+        // bounce prefix, first 256 bits of the original body and,
+        // in newer versions, the whole original body in a reference
+        let mut truncated = Cell::builder();
+        truncated
+            .pack(0xffffffffu32)
+            .unwrap()
+            .pack(&original.data[..256])
+            .unwrap();
+        let truncated = truncated.into_cell();
+        let full = Cell {
+            references: [original.into()].into(),
+            ..truncated.clone()
+        };
+
+        let expected = Transfer {
+            query_id: 42,
+            amount: BigUint::from(1_000_000u32),
+            dst,
+        };
+        assert_eq!(
+            full.parse_fully::<BouncedBody<Transfer>>().unwrap(),
+            BouncedBody {
+                truncated: false,
+                op: Some(0x0f8a7ea5),
+                query_id: Some(42),
+                body: Some(expected),
+            },
+        );
+        assert_eq!(
+            truncated.parse_fully::<BouncedBody<Transfer>>().unwrap(),
+            BouncedBody {
+                truncated: true,
+                op: Some(0x0f8a7ea5),
+                query_id: Some(42),
+                body: None,
+            },
+        );
+
+        for cell in [truncated, full] {
+            let bounced: BouncedBody = cell.parse_fully().unwrap();
+            assert_eq!(bounced.to_cell().unwrap(), cell);
+        }
+    }
+
     #[test]
     fn internal_msg_info_serde() {
         let info = CommonMsgInfo::Internal(InternalMsgInfo {
@@ -394,7 +1107,7 @@ mod tests {
     #[test]
     fn external_in_msg_info_serde() {
         let info = CommonMsgInfo::ExternalIn(ExternalInMsgInfo {
-            src: MsgAddress::NULL,
+            src: MsgAddressExt::Extern(bitvec![u8, Msb0; 1, 0, 1]),
             dst: MsgAddress::NULL,
             import_fee: BigUint::ZERO,
         });
@@ -409,7 +1122,7 @@ mod tests {
     fn external_out_msg_info_serde() {
         let info = CommonMsgInfo::ExternalOut(ExternalOutMsgInfo {
             src: MsgAddress::NULL,
-            dst: MsgAddress::NULL,
+            dst: MsgAddressExt::Extern(bitvec![u8, Msb0; 1, 0, 1]),
             created_lt: 0,
             created_at: DateTime::UNIX_EPOCH,
         });
@@ -0,0 +1,1387 @@
+//! Collection of types related to [Transaction](https://docs.ton.org/develop/data-formats/transaction-layout)
+use chrono::{DateTime, Utc};
+use num_bigint::BigUint;
+use tlb::{
+    bits::{
+        de::{BitReader, BitReaderExt, BitUnpack},
+        r#as::{NBits, VarInt},
+        ser::{BitPack, BitWriter, BitWriterExt},
+    },
+    de::{CellDeserialize, CellParser, CellParserError},
+    r#as::{Data, NoArgs, Ref},
+    ser::{CellBuilder, CellBuilderError, CellSerialize},
+    Cell, Error, ResultExt,
+};
+
+use crate::{
+    account::AccountStatus,
+    currency::{CurrencyCollection, Grams},
+    hashmap::HashmapE,
+    message::Message,
+    UnixTimestamp,
+};
+
+/// [Transaction](https://docs.ton.org/develop/data-formats/transaction-layout#transaction)
+/// ```tlb
+/// transaction$0111 account_addr:bits256 lt:uint64
+/// prev_trans_hash:bits256 prev_trans_lt:uint64 now:uint32
+/// outmsg_cnt:uint15
+/// orig_status:AccountStatus end_status:AccountStatus
+/// ^[ in_msg:(Maybe ^(Message Any)) out_msgs:(HashmapE 15 ^(Message Any)) ]
+/// total_fees:CurrencyCollection state_update:^(HASH_UPDATE Account)
+/// description:^TransactionDescr = Transaction;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transaction {
+    pub account_addr: [u8; 32],
+    pub lt: u64,
+    pub prev_trans_hash: [u8; 32],
+    pub prev_trans_lt: u64,
+    pub now: DateTime<Utc>,
+    pub outmsg_cnt: u16,
+    pub orig_status: AccountStatus,
+    pub end_status: AccountStatus,
+    pub in_msg: Option<Message>,
+    /// Outbound messages by their index
+    pub out_msgs: HashmapE<Message>,
+    pub total_fees: CurrencyCollection,
+    pub state_update: HashUpdate,
+    pub description: TransactionDescr,
+}
+
+impl Transaction {
+    const TAG: u8 = 0b0111;
+}
+
+impl CellSerialize for Transaction {
+    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        let mut msgs = Cell::builder();
+        msgs
+            // in_msg:(Maybe ^(Message Any))
+            .store_as::<_, Option<Ref>>(self.in_msg.as_ref())
+            .context("in_msg")?
+            // out_msgs:(HashmapE 15 ^(Message Any))
+            .store_as_with::<_, &HashmapE<Ref<NoArgs<_>>, NoArgs<_>>>(&self.out_msgs, (15, (), ()))
+            .context("out_msgs")?;
+
+        builder
+            // transaction$0111
+            .pack_as::<_, NBits<4>>(Self::TAG)?
+            // account_addr:bits256
+            .pack(self.account_addr)?
+            // lt:uint64
+            .pack(self.lt)?
+            // prev_trans_hash:bits256
+            .pack(self.prev_trans_hash)?
+            // prev_trans_lt:uint64
+            .pack(self.prev_trans_lt)?
+            // now:uint32
+            .pack_as::<_, UnixTimestamp>(self.now)?
+            // outmsg_cnt:uint15
+            .pack_as::<_, NBits<15>>(self.outmsg_cnt)?
+            // orig_status:AccountStatus
+            .pack(self.orig_status)?
+            // end_status:AccountStatus
+            .pack(self.end_status)?
+            // ^[ in_msg:(Maybe ^(Message Any)) out_msgs:(HashmapE 15 ^(Message Any)) ]
+            .store_as::<_, Ref>(msgs)?
+            // total_fees:CurrencyCollection
+            .store(&self.total_fees)
+            .context("total_fees")?
+            // state_update:^(HASH_UPDATE Account)
+            .store_as::<_, Ref<Data>>(self.state_update)?
+            // description:^TransactionDescr
+            .store_as::<_, Ref>(&self.description)
+            .context("description")?;
+        Ok(())
+    }
+}
+
+impl<'de> CellDeserialize<'de> for Transaction {
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        let tag: u8 = parser.unpack_as::<_, NBits<4>>()?;
+        if tag != Self::TAG {
+            return Err(Error::custom(format!("unknown transaction tag: {tag:#b}")));
+        }
+        let account_addr = parser.unpack()?;
+        let lt = parser.unpack()?;
+        let prev_trans_hash = parser.unpack()?;
+        let prev_trans_lt = parser.unpack()?;
+        let now = parser.unpack_as::<_, UnixTimestamp>()?;
+        let outmsg_cnt = parser.unpack_as::<_, NBits<15>>()?;
+        let orig_status = parser.unpack().context("orig_status")?;
+        let end_status = parser.unpack().context("end_status")?;
+        // ^[ in_msg:(Maybe ^(Message Any)) out_msgs:(HashmapE 15 ^(Message Any)) ]
+        let mut msgs: CellParser<'de> = parser.parse_as::<_, Ref>()?;
+        let in_msg = msgs.parse_as::<_, Option<Ref>>().context("in_msg")?;
+        let out_msgs = msgs
+            .parse_as_with::<_, HashmapE<Ref<NoArgs<_>>, NoArgs<_>>>((15, (), ()))
+            .context("out_msgs")?;
+        msgs.ensure_empty()?;
+        Ok(Self {
+            account_addr,
+            lt,
+            prev_trans_hash,
+            prev_trans_lt,
+            now,
+            outmsg_cnt,
+            orig_status,
+            end_status,
+            in_msg,
+            out_msgs,
+            // total_fees:CurrencyCollection
+            total_fees: parser.parse().context("total_fees")?,
+            // state_update:^(HASH_UPDATE Account)
+            state_update: parser.parse_as::<_, Ref<Data>>().context("state_update")?,
+            // description:^TransactionDescr
+            description: parser.parse_as::<_, Ref>().context("description")?,
+        })
+    }
+}
+
+/// ```tlb
+/// update_hashes#72 {X:Type} old_hash:bits256 new_hash:bits256 = HASH_UPDATE X;
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HashUpdate {
+    pub old_hash: [u8; 32],
+    pub new_hash: [u8; 32],
+}
+
+impl HashUpdate {
+    const TAG: u8 = 0x72;
+}
+
+impl BitPack for HashUpdate {
+    #[inline]
+    fn pack<W>(&self, mut writer: W) -> Result<(), W::Error>
+    where
+        W: BitWriter,
+    {
+        writer
+            // update_hashes#72
+            .pack(Self::TAG)?
+            // old_hash:bits256
+            .pack(self.old_hash)?
+            // new_hash:bits256
+            .pack(self.new_hash)?;
+        Ok(())
+    }
+}
+
+impl BitUnpack for HashUpdate {
+    #[inline]
+    fn unpack<R>(mut reader: R) -> Result<Self, R::Error>
+    where
+        R: BitReader,
+    {
+        let tag: u8 = reader.unpack()?;
+        if tag != Self::TAG {
+            return Err(Error::custom(format!(
+                "unknown update_hashes tag: {tag:#0x}"
+            )));
+        }
+        Ok(Self {
+            // old_hash:bits256
+            old_hash: reader.unpack()?,
+            // new_hash:bits256
+            new_hash: reader.unpack()?,
+        })
+    }
+}
+
+/// Description of a [`Transaction`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransactionDescr {
+    /// ```tlb
+    /// trans_ord$0000 credit_first:Bool
+    /// storage_ph:(Maybe TrStoragePhase)
+    /// credit_ph:(Maybe TrCreditPhase)
+    /// compute_ph:TrComputePhase action:(Maybe ^TrActionPhase)
+    /// aborted:Bool bounce:(Maybe TrBouncePhase)
+    /// destroyed:Bool
+    /// = TransactionDescr;
+    /// ```
+    Ordinary {
+        credit_first: bool,
+        storage_ph: Option<TrStoragePhase>,
+        credit_ph: Option<TrCreditPhase>,
+        compute_ph: TrComputePhase,
+        action: Option<TrActionPhase>,
+        aborted: bool,
+        bounce: Option<TrBouncePhase>,
+        destroyed: bool,
+    },
+    /// ```tlb
+    /// trans_storage$0001 storage_ph:TrStoragePhase
+    /// = TransactionDescr;
+    /// ```
+    Storage { storage_ph: TrStoragePhase },
+    /// ```tlb
+    /// trans_tick_tock$001 is_tock:Bool storage_ph:TrStoragePhase
+    /// compute_ph:TrComputePhase action:(Maybe ^TrActionPhase)
+    /// aborted:Bool destroyed:Bool = TransactionDescr;
+    /// ```
+    TickTock {
+        is_tock: bool,
+        storage_ph: TrStoragePhase,
+        compute_ph: TrComputePhase,
+        action: Option<TrActionPhase>,
+        aborted: bool,
+        destroyed: bool,
+    },
+    /// ```tlb
+    /// trans_split_prepare$0100 split_info:SplitMergeInfo
+    /// storage_ph:(Maybe TrStoragePhase)
+    /// compute_ph:TrComputePhase action:(Maybe ^TrActionPhase)
+    /// aborted:Bool destroyed:Bool
+    /// = TransactionDescr;
+    /// ```
+    SplitPrepare {
+        split_info: SplitMergeInfo,
+        storage_ph: Option<TrStoragePhase>,
+        compute_ph: TrComputePhase,
+        action: Option<TrActionPhase>,
+        aborted: bool,
+        destroyed: bool,
+    },
+    /// ```tlb
+    /// trans_split_install$0101 split_info:SplitMergeInfo
+    /// prepare_transaction:^Transaction
+    /// installed:Bool = TransactionDescr;
+    /// ```
+    SplitInstall {
+        split_info: SplitMergeInfo,
+        prepare_transaction: Box<Transaction>,
+        installed: bool,
+    },
+    /// ```tlb
+    /// trans_merge_prepare$0110 split_info:SplitMergeInfo
+    /// storage_ph:TrStoragePhase aborted:Bool
+    /// = TransactionDescr;
+    /// ```
+    MergePrepare {
+        split_info: SplitMergeInfo,
+        storage_ph: TrStoragePhase,
+        aborted: bool,
+    },
+    /// ```tlb
+    /// trans_merge_install$0111 split_info:SplitMergeInfo
+    /// prepare_transaction:^Transaction
+    /// storage_ph:(Maybe TrStoragePhase)
+    /// credit_ph:(Maybe TrCreditPhase)
+    /// compute_ph:TrComputePhase action:(Maybe ^TrActionPhase)
+    /// aborted:Bool destroyed:Bool
+    /// = TransactionDescr;
+    /// ```
+    MergeInstall {
+        split_info: SplitMergeInfo,
+        prepare_transaction: Box<Transaction>,
+        storage_ph: Option<TrStoragePhase>,
+        credit_ph: Option<TrCreditPhase>,
+        compute_ph: TrComputePhase,
+        action: Option<TrActionPhase>,
+        aborted: bool,
+        destroyed: bool,
+    },
+}
+
+impl TransactionDescr {
+    /// Returns compute phase, if the transaction has one
+    #[inline]
+    pub fn compute_ph(&self) -> Option<&TrComputePhase> {
+        match self {
+            Self::Ordinary { compute_ph, .. }
+            | Self::TickTock { compute_ph, .. }
+            | Self::SplitPrepare { compute_ph, .. }
+            | Self::MergeInstall { compute_ph, .. } => Some(compute_ph),
+            _ => None,
+        }
+    }
+
+    /// Returns action phase, if the transaction has one
+    #[inline]
+    pub fn action_ph(&self) -> Option<&TrActionPhase> {
+        match self {
+            Self::Ordinary { action, .. }
+            | Self::TickTock { action, .. }
+            | Self::SplitPrepare { action, .. }
+            | Self::MergeInstall { action, .. } => action.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// Returns whether the transaction was aborted
+    #[inline]
+    pub fn aborted(&self) -> bool {
+        match self {
+            Self::Ordinary { aborted, .. }
+            | Self::TickTock { aborted, .. }
+            | Self::SplitPrepare { aborted, .. }
+            | Self::MergePrepare { aborted, .. }
+            | Self::MergeInstall { aborted, .. } => *aborted,
+            Self::Storage { .. } | Self::SplitInstall { .. } => false,
+        }
+    }
+}
+
+impl CellSerialize for TransactionDescr {
+    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        match self {
+            Self::Ordinary {
+                credit_first,
+                storage_ph,
+                credit_ph,
+                compute_ph,
+                action,
+                aborted,
+                bounce,
+                destroyed,
+            } => {
+                builder
+                    // trans_ord$0000
+                    .pack_as::<_, NBits<4>>(0b0000)?
+                    // credit_first:Bool
+                    .pack(credit_first)?
+                    // storage_ph:(Maybe TrStoragePhase)
+                    .pack(storage_ph)
+                    .context("storage_ph")?
+                    // credit_ph:(Maybe TrCreditPhase)
+                    .store(credit_ph)
+                    .context("credit_ph")?
+                    // compute_ph:TrComputePhase
+                    .store(compute_ph)
+                    .context("compute_ph")?
+                    // action:(Maybe ^TrActionPhase)
+                    .store_as::<_, Option<Ref>>(action.as_ref())
+                    .context("action")?
+                    // aborted:Bool
+                    .pack(aborted)?
+                    // bounce:(Maybe TrBouncePhase)
+                    .pack(bounce)
+                    .context("bounce")?
+                    // destroyed:Bool
+                    .pack(destroyed)?;
+            }
+            Self::Storage { storage_ph } => {
+                builder
+                    // trans_storage$0001
+                    .pack_as::<_, NBits<4>>(0b0001)?
+                    // storage_ph:TrStoragePhase
+                    .pack(storage_ph)
+                    .context("storage_ph")?;
+            }
+            Self::TickTock {
+                is_tock,
+                storage_ph,
+                compute_ph,
+                action,
+                aborted,
+                destroyed,
+            } => {
+                builder
+                    // trans_tick_tock$001
+                    .pack_as::<_, NBits<3>>(0b001)?
+                    // is_tock:Bool
+                    .pack(is_tock)?
+                    // storage_ph:TrStoragePhase
+                    .pack(storage_ph)
+                    .context("storage_ph")?
+                    // compute_ph:TrComputePhase
+                    .store(compute_ph)
+                    .context("compute_ph")?
+                    // action:(Maybe ^TrActionPhase)
+                    .store_as::<_, Option<Ref>>(action.as_ref())
+                    .context("action")?
+                    // aborted:Bool
+                    .pack(aborted)?
+                    // destroyed:Bool
+                    .pack(destroyed)?;
+            }
+            Self::SplitPrepare {
+                split_info,
+                storage_ph,
+                compute_ph,
+                action,
+                aborted,
+                destroyed,
+            } => {
+                builder
+                    // trans_split_prepare$0100
+                    .pack_as::<_, NBits<4>>(0b0100)?
+                    // split_info:SplitMergeInfo
+                    .pack(split_info)
+                    .context("split_info")?
+                    // storage_ph:(Maybe TrStoragePhase)
+                    .pack(storage_ph)
+                    .context("storage_ph")?
+                    // compute_ph:TrComputePhase
+                    .store(compute_ph)
+                    .context("compute_ph")?
+                    // action:(Maybe ^TrActionPhase)
+                    .store_as::<_, Option<Ref>>(action.as_ref())
+                    .context("action")?
+                    // aborted:Bool
+                    .pack(aborted)?
+                    // destroyed:Bool
+                    .pack(destroyed)?;
+            }
+            Self::SplitInstall {
+                split_info,
+                prepare_transaction,
+                installed,
+            } => {
+                builder
+                    // trans_split_install$0101
+                    .pack_as::<_, NBits<4>>(0b0101)?
+                    // split_info:SplitMergeInfo
+                    .pack(split_info)
+                    .context("split_info")?
+                    // prepare_transaction:^Transaction
+                    .store_as::<_, Ref>(prepare_transaction.as_ref())
+                    .context("prepare_transaction")?
+                    // installed:Bool
+                    .pack(installed)?;
+            }
+            Self::MergePrepare {
+                split_info,
+                storage_ph,
+                aborted,
+            } => {
+                builder
+                    // trans_merge_prepare$0110
+                    .pack_as::<_, NBits<4>>(0b0110)?
+                    // split_info:SplitMergeInfo
+                    .pack(split_info)
+                    .context("split_info")?
+                    // storage_ph:TrStoragePhase
+                    .pack(storage_ph)
+                    .context("storage_ph")?
+                    // aborted:Bool
+                    .pack(aborted)?;
+            }
+            Self::MergeInstall {
+                split_info,
+                prepare_transaction,
+                storage_ph,
+                credit_ph,
+                compute_ph,
+                action,
+                aborted,
+                destroyed,
+            } => {
+                builder
+                    // trans_merge_install$0111
+                    .pack_as::<_, NBits<4>>(0b0111)?
+                    // split_info:SplitMergeInfo
+                    .pack(split_info)
+                    .context("split_info")?
+                    // prepare_transaction:^Transaction
+                    .store_as::<_, Ref>(prepare_transaction.as_ref())
+                    .context("prepare_transaction")?
+                    // storage_ph:(Maybe TrStoragePhase)
+                    .pack(storage_ph)
+                    .context("storage_ph")?
+                    // credit_ph:(Maybe TrCreditPhase)
+                    .store(credit_ph)
+                    .context("credit_ph")?
+                    // compute_ph:TrComputePhase
+                    .store(compute_ph)
+                    .context("compute_ph")?
+                    // action:(Maybe ^TrActionPhase)
+                    .store_as::<_, Option<Ref>>(action.as_ref())
+                    .context("action")?
+                    // aborted:Bool
+                    .pack(aborted)?
+                    // destroyed:Bool
+                    .pack(destroyed)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'de> CellDeserialize<'de> for TransactionDescr {
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        let tag: u8 = parser.unpack_as::<_, NBits<4>>()?;
+        Ok(match tag {
+            // trans_ord$0000
+            0b0000 => Self::Ordinary {
+                credit_first: parser.unpack()?,
+                storage_ph: parser.unpack().context("storage_ph")?,
+                credit_ph: parser.parse().context("credit_ph")?,
+                compute_ph: parser.parse().context("compute_ph")?,
+                action: parser.parse_as::<_, Option<Ref>>().context("action")?,
+                aborted: parser.unpack()?,
+                bounce: parser.unpack().context("bounce")?,
+                destroyed: parser.unpack()?,
+            },
+            // trans_storage$0001
+            0b0001 => Self::Storage {
+                storage_ph: parser.unpack().context("storage_ph")?,
+            },
+            // trans_tick_tock$001 is_tock:Bool
+            0b0010 | 0b0011 => Self::TickTock {
+                is_tock: tag & 1 == 1,
+                storage_ph: parser.unpack().context("storage_ph")?,
+                compute_ph: parser.parse().context("compute_ph")?,
+                action: parser.parse_as::<_, Option<Ref>>().context("action")?,
+                aborted: parser.unpack()?,
+                destroyed: parser.unpack()?,
+            },
+            // trans_split_prepare$0100
+            0b0100 => Self::SplitPrepare {
+                split_info: parser.unpack().context("split_info")?,
+                storage_ph: parser.unpack().context("storage_ph")?,
+                compute_ph: parser.parse().context("compute_ph")?,
+                action: parser.parse_as::<_, Option<Ref>>().context("action")?,
+                aborted: parser.unpack()?,
+                destroyed: parser.unpack()?,
+            },
+            // trans_split_install$0101
+            0b0101 => Self::SplitInstall {
+                split_info: parser.unpack().context("split_info")?,
+                prepare_transaction: parser.parse_as::<_, Ref>().context("prepare_transaction")?,
+                installed: parser.unpack()?,
+            },
+            // trans_merge_prepare$0110
+            0b0110 => Self::MergePrepare {
+                split_info: parser.unpack().context("split_info")?,
+                storage_ph: parser.unpack().context("storage_ph")?,
+                aborted: parser.unpack()?,
+            },
+            // trans_merge_install$0111
+            0b0111 => Self::MergeInstall {
+                split_info: parser.unpack().context("split_info")?,
+                prepare_transaction: parser.parse_as::<_, Ref>().context("prepare_transaction")?,
+                storage_ph: parser.unpack().context("storage_ph")?,
+                credit_ph: parser.parse().context("credit_ph")?,
+                compute_ph: parser.parse().context("compute_ph")?,
+                action: parser.parse_as::<_, Option<Ref>>().context("action")?,
+                aborted: parser.unpack()?,
+                destroyed: parser.unpack()?,
+            },
+            _ => {
+                return Err(Error::custom(format!(
+                    "unknown transaction description tag: {tag:#06b}"
+                )))
+            }
+        })
+    }
+}
+
+/// ```tlb
+/// split_merge_info$_ cur_shard_pfx_len:(## 6)
+/// acc_split_depth:(## 6) this_addr:bits256 sibling_addr:bits256
+/// = SplitMergeInfo;
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SplitMergeInfo {
+    pub cur_shard_pfx_len: u8,
+    pub acc_split_depth: u8,
+    pub this_addr: [u8; 32],
+    pub sibling_addr: [u8; 32],
+}
+
+impl BitPack for SplitMergeInfo {
+    #[inline]
+    fn pack<W>(&self, mut writer: W) -> Result<(), W::Error>
+    where
+        W: BitWriter,
+    {
+        writer
+            // cur_shard_pfx_len:(## 6)
+            .pack_as::<_, NBits<6>>(self.cur_shard_pfx_len)?
+            // acc_split_depth:(## 6)
+            .pack_as::<_, NBits<6>>(self.acc_split_depth)?
+            // this_addr:bits256
+            .pack(self.this_addr)?
+            // sibling_addr:bits256
+            .pack(self.sibling_addr)?;
+        Ok(())
+    }
+}
+
+impl BitUnpack for SplitMergeInfo {
+    #[inline]
+    fn unpack<R>(mut reader: R) -> Result<Self, R::Error>
+    where
+        R: BitReader,
+    {
+        Ok(Self {
+            // cur_shard_pfx_len:(## 6)
+            cur_shard_pfx_len: reader.unpack_as::<_, NBits<6>>()?,
+            // acc_split_depth:(## 6)
+            acc_split_depth: reader.unpack_as::<_, NBits<6>>()?,
+            // this_addr:bits256
+            this_addr: reader.unpack()?,
+            // sibling_addr:bits256
+            sibling_addr: reader.unpack()?,
+        })
+    }
+}
+
+/// ```tlb
+/// tr_phase_storage$_ storage_fees_collected:Grams
+/// storage_fees_due:(Maybe Grams)
+/// status_change:AccStatusChange
+/// = TrStoragePhase;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrStoragePhase {
+    pub storage_fees_collected: BigUint,
+    pub storage_fees_due: Option<BigUint>,
+    pub status_change: AccStatusChange,
+}
+
+impl BitPack for TrStoragePhase {
+    #[inline]
+    fn pack<W>(&self, mut writer: W) -> Result<(), W::Error>
+    where
+        W: BitWriter,
+    {
+        writer
+            // storage_fees_collected:Grams
+            .pack_as::<_, &Grams>(&self.storage_fees_collected)?
+            // storage_fees_due:(Maybe Grams)
+            .pack_as::<_, &Option<Grams>>(&self.storage_fees_due)?
+            // status_change:AccStatusChange
+            .pack(self.status_change)?;
+        Ok(())
+    }
+}
+
+impl BitUnpack for TrStoragePhase {
+    #[inline]
+    fn unpack<R>(mut reader: R) -> Result<Self, R::Error>
+    where
+        R: BitReader,
+    {
+        Ok(Self {
+            // storage_fees_collected:Grams
+            storage_fees_collected: reader.unpack_as::<_, Grams>()?,
+            // storage_fees_due:(Maybe Grams)
+            storage_fees_due: reader.unpack_as::<_, Option<Grams>>()?,
+            // status_change:AccStatusChange
+            status_change: reader.unpack()?,
+        })
+    }
+}
+
+/// ```tlb
+/// acst_unchanged$0 = AccStatusChange;  // x -> x
+/// acst_frozen$10 = AccStatusChange;    // init -> frozen
+/// acst_deleted$11 = AccStatusChange;   // frozen -> deleted
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum AccStatusChange {
+    #[default]
+    Unchanged,
+    Frozen,
+    Deleted,
+}
+
+impl BitPack for AccStatusChange {
+    #[inline]
+    fn pack<W>(&self, mut writer: W) -> Result<(), W::Error>
+    where
+        W: BitWriter,
+    {
+        match self {
+            // acst_unchanged$0
+            Self::Unchanged => writer.pack(false)?,
+            // acst_frozen$10
+            Self::Frozen => writer.pack_as::<_, NBits<2>>(0b10)?,
+            // acst_deleted$11
+            Self::Deleted => writer.pack_as::<_, NBits<2>>(0b11)?,
+        };
+        Ok(())
+    }
+}
+
+impl BitUnpack for AccStatusChange {
+    #[inline]
+    fn unpack<R>(mut reader: R) -> Result<Self, R::Error>
+    where
+        R: BitReader,
+    {
+        // acst_unchanged$0
+        if !reader.unpack::<bool>()? {
+            return Ok(Self::Unchanged);
+        }
+        Ok(match reader.unpack()? {
+            // acst_frozen$10
+            false => Self::Frozen,
+            // acst_deleted$11
+            true => Self::Deleted,
+        })
+    }
+}
+
+/// ```tlb
+/// tr_phase_credit$_ due_fees_collected:(Maybe Grams)
+/// credit:CurrencyCollection = TrCreditPhase;
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TrCreditPhase {
+    pub due_fees_collected: Option<BigUint>,
+    pub credit: CurrencyCollection,
+}
+
+impl CellSerialize for TrCreditPhase {
+    #[inline]
+    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        builder
+            // due_fees_collected:(Maybe Grams)
+            .pack_as::<_, &Option<Grams>>(&self.due_fees_collected)?
+            // credit:CurrencyCollection
+            .store(&self.credit)?;
+        Ok(())
+    }
+}
+
+impl<'de> CellDeserialize<'de> for TrCreditPhase {
+    #[inline]
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        Ok(Self {
+            // due_fees_collected:(Maybe Grams)
+            due_fees_collected: parser.unpack_as::<_, Option<Grams>>()?,
+            // credit:CurrencyCollection
+            credit: parser.parse()?,
+        })
+    }
+}
+
+/// ```tlb
+/// tr_phase_compute_skipped$0 reason:ComputeSkipReason
+/// = TrComputePhase;
+/// tr_phase_compute_vm$1 success:Bool msg_state_used:Bool
+/// account_activated:Bool gas_fees:Grams
+/// ^[ gas_used:(VarUInteger 7)
+/// gas_limit:(VarUInteger 7) gas_credit:(Maybe (VarUInteger 3))
+/// mode:int8 exit_code:int32 exit_arg:(Maybe int32)
+/// vm_steps:uint32
+/// vm_init_state_hash:bits256 vm_final_state_hash:bits256 ]
+/// = TrComputePhase;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TrComputePhase {
+    /// ```tlb
+    /// tr_phase_compute_skipped$0 reason:ComputeSkipReason
+    /// = TrComputePhase;
+    /// ```
+    Skipped(ComputeSkipReason),
+    /// ```tlb
+    /// tr_phase_compute_vm$1 success:Bool msg_state_used:Bool
+    /// account_activated:Bool gas_fees:Grams
+    /// ^[ gas_used:(VarUInteger 7)
+    /// gas_limit:(VarUInteger 7) gas_credit:(Maybe (VarUInteger 3))
+    /// mode:int8 exit_code:int32 exit_arg:(Maybe int32)
+    /// vm_steps:uint32
+    /// vm_init_state_hash:bits256 vm_final_state_hash:bits256 ]
+    /// = TrComputePhase;
+    /// ```
+    Vm(TrComputePhaseVm),
+}
+
+impl TrComputePhase {
+    /// Returns TVM exit code, if compute phase was not skipped
+    #[inline]
+    pub fn exit_code(&self) -> Option<i32> {
+        match self {
+            Self::Skipped(_) => None,
+            Self::Vm(vm) => Some(vm.exit_code),
+        }
+    }
+}
+
+impl CellSerialize for TrComputePhase {
+    #[inline]
+    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        match self {
+            Self::Skipped(reason) => {
+                builder
+                    // tr_phase_compute_skipped$0
+                    .pack(false)?
+                    // reason:ComputeSkipReason
+                    .pack(reason)?;
+            }
+            Self::Vm(vm) => {
+                builder
+                    // tr_phase_compute_vm$1
+                    .pack(true)?
+                    .store(vm)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'de> CellDeserialize<'de> for TrComputePhase {
+    #[inline]
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        Ok(match parser.unpack()? {
+            // tr_phase_compute_skipped$0
+            false => Self::Skipped(parser.unpack().context("reason")?),
+            // tr_phase_compute_vm$1
+            true => Self::Vm(parser.parse()?),
+        })
+    }
+}
+
+/// Body of `tr_phase_compute_vm` constructor of [`TrComputePhase`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrComputePhaseVm {
+    pub success: bool,
+    pub msg_state_used: bool,
+    pub account_activated: bool,
+    pub gas_fees: BigUint,
+    pub gas_used: BigUint,
+    pub gas_limit: BigUint,
+    pub gas_credit: Option<BigUint>,
+    pub mode: i8,
+    pub exit_code: i32,
+    pub exit_arg: Option<i32>,
+    pub vm_steps: u32,
+    pub vm_init_state_hash: [u8; 32],
+    pub vm_final_state_hash: [u8; 32],
+}
+
+impl CellSerialize for TrComputePhaseVm {
+    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        let mut details = Cell::builder();
+        details
+            // gas_used:(VarUInteger 7)
+            .pack_as::<_, &VarInt<3>>(&self.gas_used)?
+            // gas_limit:(VarUInteger 7)
+            .pack_as::<_, &VarInt<3>>(&self.gas_limit)?
+            // gas_credit:(Maybe (VarUInteger 3))
+            .pack_as::<_, &Option<VarInt<2>>>(&self.gas_credit)?
+            // mode:int8
+            .pack(self.mode)?
+            // exit_code:int32
+            .pack(self.exit_code)?
+            // exit_arg:(Maybe int32)
+            .pack(self.exit_arg)?
+            // vm_steps:uint32
+            .pack(self.vm_steps)?
+            // vm_init_state_hash:bits256
+            .pack(self.vm_init_state_hash)?
+            // vm_final_state_hash:bits256
+            .pack(self.vm_final_state_hash)?;
+
+        builder
+            // success:Bool
+            .pack(self.success)?
+            // msg_state_used:Bool
+            .pack(self.msg_state_used)?
+            // account_activated:Bool
+            .pack(self.account_activated)?
+            // gas_fees:Grams
+            .pack_as::<_, &Grams>(&self.gas_fees)?
+            // ^[ ... ]
+            .store_as::<_, Ref>(details)?;
+        Ok(())
+    }
+}
+
+impl<'de> CellDeserialize<'de> for TrComputePhaseVm {
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        let success = parser.unpack()?;
+        let msg_state_used = parser.unpack()?;
+        let account_activated = parser.unpack()?;
+        let gas_fees = parser.unpack_as::<_, Grams>().context("gas_fees")?;
+        // ^[ ... ]
+        let mut details: CellParser<'de> = parser.parse_as::<_, Ref>()?;
+        let vm = Self {
+            success,
+            msg_state_used,
+            account_activated,
+            gas_fees,
+            // gas_used:(VarUInteger 7)
+            gas_used: details.unpack_as::<_, VarInt<3>>().context("gas_used")?,
+            // gas_limit:(VarUInteger 7)
+            gas_limit: details.unpack_as::<_, VarInt<3>>().context("gas_limit")?,
+            // gas_credit:(Maybe (VarUInteger 3))
+            gas_credit: details
+                .unpack_as::<_, Option<VarInt<2>>>()
+                .context("gas_credit")?,
+            // mode:int8
+            mode: details.unpack()?,
+            // exit_code:int32
+            exit_code: details.unpack()?,
+            // exit_arg:(Maybe int32)
+            exit_arg: details.unpack()?,
+            // vm_steps:uint32
+            vm_steps: details.unpack()?,
+            // vm_init_state_hash:bits256
+            vm_init_state_hash: details.unpack()?,
+            // vm_final_state_hash:bits256
+            vm_final_state_hash: details.unpack()?,
+        };
+        details.ensure_empty()?;
+        Ok(vm)
+    }
+}
+
+/// ```tlb
+/// cskip_no_state$00 = ComputeSkipReason;
+/// cskip_bad_state$01 = ComputeSkipReason;
+/// cskip_no_gas$10 = ComputeSkipReason;
+/// cskip_suspended$110 = ComputeSkipReason;
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ComputeSkipReason {
+    NoState,
+    BadState,
+    NoGas,
+    Suspended,
+}
+
+impl BitPack for ComputeSkipReason {
+    #[inline]
+    fn pack<W>(&self, mut writer: W) -> Result<(), W::Error>
+    where
+        W: BitWriter,
+    {
+        match self {
+            // cskip_no_state$00
+            Self::NoState => writer.pack_as::<_, NBits<2>>(0b00)?,
+            // cskip_bad_state$01
+            Self::BadState => writer.pack_as::<_, NBits<2>>(0b01)?,
+            // cskip_no_gas$10
+            Self::NoGas => writer.pack_as::<_, NBits<2>>(0b10)?,
+            // cskip_suspended$110
+            Self::Suspended => writer.pack_as::<_, NBits<3>>(0b110)?,
+        };
+        Ok(())
+    }
+}
+
+impl BitUnpack for ComputeSkipReason {
+    #[inline]
+    fn unpack<R>(mut reader: R) -> Result<Self, R::Error>
+    where
+        R: BitReader,
+    {
+        Ok(match reader.unpack_as::<u8, NBits<2>>()? {
+            0b00 => Self::NoState,
+            0b01 => Self::BadState,
+            0b10 => Self::NoGas,
+            _ => match reader.unpack()? {
+                false => Self::Suspended,
+                true => return Err(Error::custom("unknown ComputeSkipReason tag: 0b111")),
+            },
+        })
+    }
+}
+
+/// ```tlb
+/// tr_phase_action$_ success:Bool valid:Bool no_funds:Bool
+/// status_change:AccStatusChange
+/// total_fwd_fees:(Maybe Grams) total_action_fees:(Maybe Grams)
+/// result_code:int32 result_arg:(Maybe int32) tot_actions:uint16
+/// spec_actions:uint16 skipped_actions:uint16 msgs_created:uint16
+/// action_list_hash:bits256 tot_msg_size:StorageUsedShort
+/// = TrActionPhase;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrActionPhase {
+    pub success: bool,
+    pub valid: bool,
+    pub no_funds: bool,
+    pub status_change: AccStatusChange,
+    pub total_fwd_fees: Option<BigUint>,
+    pub total_action_fees: Option<BigUint>,
+    pub result_code: i32,
+    pub result_arg: Option<i32>,
+    pub tot_actions: u16,
+    pub spec_actions: u16,
+    pub skipped_actions: u16,
+    pub msgs_created: u16,
+    pub action_list_hash: [u8; 32],
+    pub tot_msg_size: StorageUsedShort,
+}
+
+impl CellSerialize for TrActionPhase {
+    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        builder
+            // success:Bool
+            .pack(self.success)?
+            // valid:Bool
+            .pack(self.valid)?
+            // no_funds:Bool
+            .pack(self.no_funds)?
+            // status_change:AccStatusChange
+            .pack(self.status_change)?
+            // total_fwd_fees:(Maybe Grams)
+            .pack_as::<_, &Option<Grams>>(&self.total_fwd_fees)?
+            // total_action_fees:(Maybe Grams)
+            .pack_as::<_, &Option<Grams>>(&self.total_action_fees)?
+            // result_code:int32
+            .pack(self.result_code)?
+            // result_arg:(Maybe int32)
+            .pack(self.result_arg)?
+            // tot_actions:uint16
+            .pack(self.tot_actions)?
+            // spec_actions:uint16
+            .pack(self.spec_actions)?
+            // skipped_actions:uint16
+            .pack(self.skipped_actions)?
+            // msgs_created:uint16
+            .pack(self.msgs_created)?
+            // action_list_hash:bits256
+            .pack(self.action_list_hash)?
+            // tot_msg_size:StorageUsedShort
+            .pack(&self.tot_msg_size)?;
+        Ok(())
+    }
+}
+
+impl<'de> CellDeserialize<'de> for TrActionPhase {
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        Ok(Self {
+            // success:Bool
+            success: parser.unpack()?,
+            // valid:Bool
+            valid: parser.unpack()?,
+            // no_funds:Bool
+            no_funds: parser.unpack()?,
+            // status_change:AccStatusChange
+            status_change: parser.unpack()?,
+            // total_fwd_fees:(Maybe Grams)
+            total_fwd_fees: parser.unpack_as::<_, Option<Grams>>()?,
+            // total_action_fees:(Maybe Grams)
+            total_action_fees: parser.unpack_as::<_, Option<Grams>>()?,
+            // result_code:int32
+            result_code: parser.unpack()?,
+            // result_arg:(Maybe int32)
+            result_arg: parser.unpack()?,
+            // tot_actions:uint16
+            tot_actions: parser.unpack()?,
+            // spec_actions:uint16
+            spec_actions: parser.unpack()?,
+            // skipped_actions:uint16
+            skipped_actions: parser.unpack()?,
+            // msgs_created:uint16
+            msgs_created: parser.unpack()?,
+            // action_list_hash:bits256
+            action_list_hash: parser.unpack()?,
+            // tot_msg_size:StorageUsedShort
+            tot_msg_size: parser.unpack()?,
+        })
+    }
+}
+
+/// ```tlb
+/// tr_phase_bounce_negfunds$00 = TrBouncePhase;
+/// tr_phase_bounce_nofunds$01 msg_size:StorageUsedShort
+/// req_fwd_fees:Grams = TrBouncePhase;
+/// tr_phase_bounce_ok$1 msg_size:StorageUsedShort
+/// msg_fees:Grams fwd_fees:Grams = TrBouncePhase;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TrBouncePhase {
+    /// ```tlb
+    /// tr_phase_bounce_negfunds$00 = TrBouncePhase;
+    /// ```
+    NegFunds,
+    /// ```tlb
+    /// tr_phase_bounce_nofunds$01 msg_size:StorageUsedShort
+    /// req_fwd_fees:Grams = TrBouncePhase;
+    /// ```
+    NoFunds {
+        msg_size: StorageUsedShort,
+        req_fwd_fees: BigUint,
+    },
+    /// ```tlb
+    /// tr_phase_bounce_ok$1 msg_size:StorageUsedShort
+    /// msg_fees:Grams fwd_fees:Grams = TrBouncePhase;
+    /// ```
+    Ok {
+        msg_size: StorageUsedShort,
+        msg_fees: BigUint,
+        fwd_fees: BigUint,
+    },
+}
+
+impl BitPack for TrBouncePhase {
+    #[inline]
+    fn pack<W>(&self, mut writer: W) -> Result<(), W::Error>
+    where
+        W: BitWriter,
+    {
+        match self {
+            Self::NegFunds => {
+                // tr_phase_bounce_negfunds$00
+                writer.pack_as::<_, NBits<2>>(0b00)?;
+            }
+            Self::NoFunds {
+                msg_size,
+                req_fwd_fees,
+            } => {
+                writer
+                    // tr_phase_bounce_nofunds$01
+                    .pack_as::<_, NBits<2>>(0b01)?
+                    // msg_size:StorageUsedShort
+                    .pack(msg_size)?
+                    // req_fwd_fees:Grams
+                    .pack_as::<_, &Grams>(req_fwd_fees)?;
+            }
+            Self::Ok {
+                msg_size,
+                msg_fees,
+                fwd_fees,
+            } => {
+                writer
+                    // tr_phase_bounce_ok$1
+                    .pack(true)?
+                    // msg_size:StorageUsedShort
+                    .pack(msg_size)?
+                    // msg_fees:Grams
+                    .pack_as::<_, &Grams>(msg_fees)?
+                    // fwd_fees:Grams
+                    .pack_as::<_, &Grams>(fwd_fees)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl BitUnpack for TrBouncePhase {
+    #[inline]
+    fn unpack<R>(mut reader: R) -> Result<Self, R::Error>
+    where
+        R: BitReader,
+    {
+        // tr_phase_bounce_ok$1
+        if reader.unpack()? {
+            return Ok(Self::Ok {
+                msg_size: reader.unpack()?,
+                msg_fees: reader.unpack_as::<_, Grams>()?,
+                fwd_fees: reader.unpack_as::<_, Grams>()?,
+            });
+        }
+        Ok(match reader.unpack()? {
+            // tr_phase_bounce_negfunds$00
+            false => Self::NegFunds,
+            // tr_phase_bounce_nofunds$01
+            true => Self::NoFunds {
+                msg_size: reader.unpack()?,
+                req_fwd_fees: reader.unpack_as::<_, Grams>()?,
+            },
+        })
+    }
+}
+
+/// ```tlb
+/// storage_used_short$_ cells:(VarUInteger 7)
+/// bits:(VarUInteger 7) = StorageUsedShort;
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StorageUsedShort {
+    pub cells: BigUint,
+    pub bits: BigUint,
+}
+
+impl BitPack for StorageUsedShort {
+    #[inline]
+    fn pack<W>(&self, mut writer: W) -> Result<(), W::Error>
+    where
+        W: BitWriter,
+    {
+        writer
+            // cells:(VarUInteger 7)
+            .pack_as::<_, &VarInt<3>>(&self.cells)?
+            // bits:(VarUInteger 7)
+            .pack_as::<_, &VarInt<3>>(&self.bits)?;
+        Ok(())
+    }
+}
+
+impl BitUnpack for StorageUsedShort {
+    #[inline]
+    fn unpack<R>(mut reader: R) -> Result<Self, R::Error>
+    where
+        R: BitReader,
+    {
+        Ok(Self {
+            // cells:(VarUInteger 7)
+            cells: reader.unpack_as::<_, VarInt<3>>()?,
+            // bits:(VarUInteger 7)
+            bits: reader.unpack_as::<_, VarInt<3>>()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tlb::{
+        bits::bitvec::{order::Msb0, view::AsBits},
+        ser::CellSerializeExt,
+    };
+
+    use crate::MsgAddress;
+
+    use super::*;
+
+    fn given_compute_vm() -> TrComputePhase {
+        TrComputePhase::Vm(TrComputePhaseVm {
+            success: true,
+            msg_state_used: false,
+            account_activated: false,
+            gas_fees: 1_000_000u64.into(),
+            gas_used: 3_308u32.into(),
+            gas_limit: 0u32.into(),
+            gas_credit: Some(10_000u32.into()),
+            mode: 0,
+            exit_code: 0,
+            exit_arg: None,
+            vm_steps: 68,
+            vm_init_state_hash: [0; 32],
+            vm_final_state_hash: [0; 32],
+        })
+    }
+
+    fn given_transaction(description: TransactionDescr) -> Transaction {
+        let mut out_msgs = HashmapE::Empty;
+        out_msgs
+            .insert(
+                &0u16.to_be_bytes().as_bits::<Msb0>()[1..],
+                Message::transfer(MsgAddress::NULL, 1u32.into(), true)
+                    .normalize()
+                    .unwrap(),
+            )
+            .unwrap();
+        Transaction {
+            account_addr: [1; 32],
+            lt: 47_000_000_000_001,
+            prev_trans_hash: [2; 32],
+            prev_trans_lt: 46_000_000_000_001,
+            now: DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+            outmsg_cnt: 1,
+            orig_status: AccountStatus::Active,
+            end_status: AccountStatus::Active,
+            in_msg: Some(
+                Message::transfer(MsgAddress::NULL, 2u32.into(), false)
+                    .normalize()
+                    .unwrap(),
+            ),
+            out_msgs,
+            total_fees: CurrencyCollection {
+                grams: 1_500_000u64.into(),
+                ..Default::default()
+            },
+            state_update: HashUpdate {
+                old_hash: [3; 32],
+                new_hash: [4; 32],
+            },
+            description,
+        }
+    }
+
+    #[test]
+    fn ordinary_transaction_serde() {
+        let tx = given_transaction(TransactionDescr::Ordinary {
+            credit_first: false,
+            storage_ph: Some(TrStoragePhase {
+                storage_fees_collected: 12u32.into(),
+                storage_fees_due: None,
+                status_change: AccStatusChange::Unchanged,
+            }),
+            credit_ph: Some(TrCreditPhase {
+                due_fees_collected: None,
+                credit: CurrencyCollection {
+                    grams: 2u32.into(),
+                    ..Default::default()
+                },
+            }),
+            compute_ph: given_compute_vm(),
+            action: Some(TrActionPhase {
+                success: true,
+                valid: true,
+                no_funds: false,
+                status_change: AccStatusChange::Unchanged,
+                total_fwd_fees: Some(400_000u32.into()),
+                total_action_fees: Some(133_000u32.into()),
+                result_code: 0,
+                result_arg: None,
+                tot_actions: 1,
+                spec_actions: 0,
+                skipped_actions: 0,
+                msgs_created: 1,
+                action_list_hash: [5; 32],
+                tot_msg_size: StorageUsedShort {
+                    cells: 1u32.into(),
+                    bits: 705u32.into(),
+                },
+            }),
+            aborted: false,
+            bounce: Some(TrBouncePhase::NoFunds {
+                msg_size: Default::default(),
+                req_fwd_fees: 7u32.into(),
+            }),
+            destroyed: false,
+        });
+
+        let cell = tx.to_cell().unwrap();
+        let got: Transaction = cell.parse_fully().unwrap();
+        assert_eq!(got, tx);
+        assert_eq!(
+            got.description
+                .compute_ph()
+                .and_then(TrComputePhase::exit_code),
+            Some(0)
+        );
+        assert!(!got.description.aborted());
+    }
+
+    #[test]
+    fn tick_tock_transaction_serde() {
+        for is_tock in [false, true] {
+            let tx = given_transaction(TransactionDescr::TickTock {
+                is_tock,
+                storage_ph: TrStoragePhase {
+                    storage_fees_collected: 0u32.into(),
+                    storage_fees_due: Some(1u32.into()),
+                    status_change: AccStatusChange::Frozen,
+                },
+                compute_ph: TrComputePhase::Skipped(ComputeSkipReason::Suspended),
+                action: None,
+                aborted: true,
+                destroyed: false,
+            });
+
+            let cell = tx.to_cell().unwrap();
+            let got: Transaction = cell.parse_fully().unwrap();
+            assert_eq!(got, tx);
+        }
+    }
+
+    #[test]
+    fn split_install_transaction_serde() {
+        let split_info = SplitMergeInfo {
+            cur_shard_pfx_len: 2,
+            acc_split_depth: 3,
+            this_addr: [6; 32],
+            sibling_addr: [7; 32],
+        };
+        let prepare = given_transaction(TransactionDescr::SplitPrepare {
+            split_info,
+            storage_ph: None,
+            compute_ph: TrComputePhase::Skipped(ComputeSkipReason::NoGas),
+            action: None,
+            aborted: false,
+            destroyed: false,
+        });
+        let tx = given_transaction(TransactionDescr::SplitInstall {
+            split_info,
+            prepare_transaction: Box::new(prepare),
+            installed: true,
+        });
+
+        let cell = tx.to_cell().unwrap();
+        let got: Transaction = cell.parse_fully().unwrap();
+        assert_eq!(got, tx);
+    }
+}
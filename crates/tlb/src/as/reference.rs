@@ -74,6 +74,8 @@ where
 /// ```tlb
 /// {X:Type} Either X ^X = EitherInlineOrRef X
 /// ```
+/// Value is stored inline if both its bits and references fit into the
+/// builder, otherwise it is stored in a reference.
 pub struct EitherInlineOrRef<As: ?Sized = Same>(PhantomData<As>);
 
 impl<T, As> CellSerializeAs<T> for EitherInlineOrRef<As>
@@ -101,13 +103,13 @@ where
         let mut b = Cell::builder();
         As::store_as_with(source, &mut b, args)?;
         let cell = b.into_cell();
-        builder.store_as::<_, Either<Same, Ref>>(
-            if cell.data.len() <= builder.capacity_left() {
-                Either::Left
-            } else {
-                Either::Right
-            }(cell),
-        )?;
+        builder.store_as::<_, Either<Same, Ref>>(if cell.data.len() < builder.capacity_left()
+            && cell.references.len() <= builder.references_left()
+        {
+            Either::Left
+        } else {
+            Either::Right
+        }(cell))?;
         Ok(())
     }
 }
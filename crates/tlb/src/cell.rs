@@ -1,11 +1,11 @@
 use core::{
     fmt::{self, Debug},
     hash::Hash,
-    ops::Deref,
+    marker::PhantomData,
 };
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
-use bitvec::{order::Msb0, vec::BitVec};
+use bitvec::{field::BitField, order::Msb0, vec::BitVec};
 use sha2::{Digest, Sha256};
 
 use crate::{
@@ -15,13 +15,55 @@ use crate::{
         CellDeserialize, CellParser, CellParserError,
     },
     ser::CellBuilder,
+    Error, StringError,
 };
 
+/// Maximum [level](https://docs.ton.org/develop/data-formats/cell-boc#cell-level)
+/// of a [`Cell`]
+pub const MAX_LEVEL: u8 = 3;
+
 /// A [Cell](https://docs.ton.org/develop/data-formats/cell-boc#cell).  
 #[derive(Clone, Default, PartialEq, Eq, Hash)]
 pub struct Cell {
     pub data: BitVec<u8, Msb0>,
     pub references: Vec<Arc<Self>>,
+    /// Whether this is an [exotic cell](https://docs.ton.org/develop/data-formats/exotic-cells).
+    /// Its [`CellType`] is then stored in the first 8 bits of `data`.
+    ///
+    /// Exotic cells with invalid layout, see [`.cell_type()`](Cell::cell_type),
+    /// can not be stored in a [`CellBuilder`] or serialized to BoC, as
+    /// they would be hashed as ordinary ones.
+    pub is_exotic: bool,
+}
+
+/// Type of a [`Cell`], see [exotic cells](https://docs.ton.org/develop/data-formats/exotic-cells)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CellType {
+    Ordinary,
+    /// ```tlb
+    /// pruned_branch#01 level_mask:uint8 hashes:(n * bits256) depths:(n * uint16)
+    /// ```
+    PrunedBranch,
+    /// ```tlb
+    /// library_reference#02 hash:bits256
+    /// ```
+    LibraryReference,
+    /// ```tlb
+    /// merkle_proof#03 virtual_hash:bits256 depth:uint16 ^Cell
+    /// ```
+    MerkleProof,
+    /// ```tlb
+    /// merkle_update#04 old_hash:bits256 new_hash:bits256
+    ///   old_depth:uint16 new_depth:uint16 ^Cell ^Cell
+    /// ```
+    MerkleUpdate,
+}
+
+impl CellType {
+    const PRUNED_BRANCH_TAG: u8 = 0x01;
+    const LIBRARY_REFERENCE_TAG: u8 = 0x02;
+    const MERKLE_PROOF_TAG: u8 = 0x03;
+    const MERKLE_UPDATE_TAG: u8 = 0x04;
 }
 
 impl Cell {
@@ -39,6 +81,48 @@ impl Cell {
         Self {
             data: BitVec::EMPTY,
             references: Vec::new(),
+            is_exotic: false,
+        }
+    }
+
+    /// Create [pruned branch](https://docs.ton.org/develop/data-formats/exotic-cells#pruned-branch)
+    /// of given `level`, which stands in for this cell in Merkle proofs.
+    /// The `level` should be greater than [level](Cell::level) of this cell.
+    pub fn pruned_branch(&self, level: u8) -> Result<Self, StringError> {
+        let hashes = self.hashes();
+        let cell_level = mask_level(hashes.mask);
+        if level <= cell_level || level > MAX_LEVEL {
+            return Err(Error::custom(format!(
+                "invalid level {level} for pruned branch of cell with level {cell_level}"
+            )));
+        }
+        let significant = || (0..=cell_level).filter(|&l| is_significant(hashes.mask, l));
+
+        let mut data = vec![
+            CellType::PRUNED_BRANCH_TAG,
+            hashes.mask | (1 << (level - 1)),
+        ];
+        data.extend(significant().flat_map(|l| hashes.hashes[l as usize]));
+        data.extend(significant().flat_map(|l| hashes.depths[l as usize].to_be_bytes()));
+
+        Ok(Self {
+            data: BitVec::from_vec(data),
+            references: Vec::new(),
+            is_exotic: true,
+        })
+    }
+
+    /// Create [Merkle proof](https://docs.ton.org/develop/data-formats/exotic-cells#merkle-proof)
+    /// for given (possibly pruned) tree
+    pub fn merkle_proof(root: impl Into<Arc<Self>>) -> Self {
+        let root: Arc<Self> = root.into();
+        let mut data = vec![CellType::MERKLE_PROOF_TAG];
+        data.extend(root.hash_at(0));
+        data.extend(root.depth_at(0).to_be_bytes());
+        Self {
+            data: BitVec::from_vec(data),
+            references: [root].into(),
+            is_exotic: true,
         }
     }
 
@@ -46,7 +130,7 @@ impl Cell {
     #[inline]
     #[must_use]
     pub fn parser(&self) -> CellParser<'_> {
-        CellParser::new(&self.data, &self.references)
+        CellParser::new(self.is_exotic, &self.data, &self.references)
     }
 
     /// Shortcut for [`.parser()`](Cell::parser)[`.parse()`](CellParser::parse)[`.ensure_empty()`](CellParser::ensure_empty).
@@ -111,88 +195,277 @@ impl Cell {
         (self.data.len(), self.data.as_raw_slice())
     }
 
+    /// Returns [`CellType`] of this cell and checks the layout of its
+    /// data and references if it is exotic
+    pub fn cell_type(&self) -> Result<CellType, StringError> {
+        if !self.is_exotic {
+            return Ok(CellType::Ordinary);
+        }
+        let tag: u8 = self
+            .data
+            .get(..8)
+            .ok_or_else(|| Error::custom("exotic cell must have at least 8 bits of data"))?
+            .load_be();
+        let (r#type, bits_len, refs_len) = match tag {
+            CellType::PRUNED_BRANCH_TAG => {
+                let mask: u8 = self
+                    .data
+                    .get(8..16)
+                    .ok_or_else(|| Error::custom("pruned branch: no level mask"))?
+                    .load_be();
+                if mask == 0 || mask_level(mask) > MAX_LEVEL {
+                    return Err(Error::custom(format!(
+                        "pruned branch: invalid level mask: {mask:#b}"
+                    )));
+                }
+                (
+                    CellType::PrunedBranch,
+                    16 + mask.count_ones() as usize * (256 + 16),
+                    0,
+                )
+            }
+            CellType::LIBRARY_REFERENCE_TAG => (CellType::LibraryReference, 8 + 256, 0),
+            CellType::MERKLE_PROOF_TAG => (CellType::MerkleProof, 8 + 256 + 16, 1),
+            CellType::MERKLE_UPDATE_TAG => (CellType::MerkleUpdate, 8 + 2 * (256 + 16), 2),
+            _ => {
+                return Err(Error::custom(format!(
+                    "unknown exotic cell type: {tag:#04x}"
+                )))
+            }
+        };
+        if self.data.len() != bits_len || self.references.len() != refs_len {
+            return Err(Error::custom(format!(
+                "{type:?}: invalid layout: {} bits, {} references",
+                self.data.len(),
+                self.references.len(),
+            )));
+        }
+        Ok(r#type)
+    }
+
+    /// Returns [level mask](https://docs.ton.org/develop/data-formats/cell-boc#cell-level)
+    /// of this cell
+    #[inline]
+    pub fn level_mask(&self) -> u8 {
+        self.hashes().mask
+    }
+
     /// See [Cell level](https://docs.ton.org/develop/data-formats/cell-boc#cell-level)
     #[inline]
     pub fn level(&self) -> u8 {
-        self.references
-            .iter()
-            .map(Deref::deref)
-            .map(Cell::level)
-            .max()
-            .unwrap_or(0)
+        mask_level(self.level_mask())
     }
 
     /// See [Cell serialization](https://docs.ton.org/develop/data-formats/cell-boc#cell-serialization)
     #[inline]
-    fn refs_descriptor(&self) -> u8 {
-        // TODO: exotic cells
-        self.references.len() as u8 | (self.level() << 5)
+    fn refs_descriptor(&self, level_mask: u8) -> u8 {
+        self.references.len() as u8 | (self.is_exotic as u8) << 3 | level_mask << 5
     }
 
     /// See [Cell serialization](https://docs.ton.org/develop/data-formats/cell-boc#cell-serialization)
     #[inline]
     fn bits_descriptor(&self) -> u8 {
         let b = self.data.len();
-        (b / 8) as u8 + ((b + 7) / 8) as u8
+        (b / 8) as u8 + b.div_ceil(8) as u8
     }
 
-    #[inline]
-    fn max_depth(&self) -> u16 {
-        self.references
-            .iter()
-            .map(Deref::deref)
-            .map(Cell::max_depth)
-            .max()
-            .map(|d| d + 1)
-            .unwrap_or(0)
-    }
-
-    /// [Standard Cell representation hash](https://docs.ton.org/develop/data-formats/cell-boc#standard-cell-representation-hash-calculation)
-    fn repr(&self) -> Vec<u8> {
-        let mut buf = Vec::new();
-        buf.push(self.refs_descriptor());
-        buf.push(self.bits_descriptor());
-
+    /// Data bytes padded with completion tag
+    fn padded_data(&self) -> Vec<u8> {
         let rest_bits = self.data.len() % 8;
-
         if rest_bits == 0 {
-            buf.extend(self.data.as_raw_slice());
-        } else {
-            let (last, data) = self.data.as_raw_slice().split_last().unwrap();
-            buf.extend(data);
-            let mut last = last & (!0u8 << (8 - rest_bits)); // clear the rest
-                                                             // let mut last = last;
-            last |= 1 << (8 - rest_bits - 1); // put stop-bit
-            buf.push(last)
+            return self.data.as_raw_slice().to_vec();
         }
 
-        // refs depth
-        buf.extend(
-            self.references
-                .iter()
-                .flat_map(|r| r.max_depth().to_be_bytes()),
-        );
+        let (last, data) = self.data.as_raw_slice().split_last().unwrap();
+        let mut buf = data.to_vec();
+        let mut last = last & (!0u8 << (8 - rest_bits)); // clear the rest
+        last |= 1 << (8 - rest_bits - 1); // put stop-bit
+        buf.push(last);
+        buf
+    }
+
+    /// Calculates [standard Cell representation hashes](https://docs.ton.org/develop/data-formats/cell-boc#standard-cell-representation-hash-calculation)
+    /// and depths for all levels
+    #[inline]
+    fn hashes(&self) -> CellHashes {
+        CellHasher::new().hashes(self)
+    }
 
-        // refs hashes
-        buf.extend(
-            self.references
+    /// Calculates hashes and depths of this cell given the ones of its
+    /// references
+    fn hashes_with_refs(&self, refs: &[CellHashes]) -> CellHashes {
+        // invalid exotic cells can not be stored or serialized, see `is_exotic`
+        let r#type = self.cell_type().unwrap_or(CellType::Ordinary);
+        let refs_mask = refs.iter().fold(0, |mask, r| mask | r.mask);
+        let mask = match r#type {
+            CellType::Ordinary => refs_mask,
+            CellType::PrunedBranch => self.data[8..16].load_be(),
+            CellType::LibraryReference => 0,
+            CellType::MerkleProof | CellType::MerkleUpdate => refs_mask >> 1,
+        };
+        let is_pruned = r#type == CellType::PrunedBranch;
+        let is_merkle = matches!(r#type, CellType::MerkleProof | CellType::MerkleUpdate);
+        let level = mask_level(mask);
+
+        // hashes and depths for each significant level, pruned branches
+        // only have the one of the highest level
+        let mut computed: Vec<([u8; 32], u16)> = Vec::new();
+        for l in (0..=level).filter(|&l| is_significant(mask, l)) {
+            if is_pruned && l != level {
+                continue;
+            }
+            let ref_level = if is_merkle { (l + 1).min(MAX_LEVEL) } else { l } as usize;
+
+            let mut hasher = Sha256::new();
+            hasher.update([
+                self.refs_descriptor(mask & ((1 << l) - 1)),
+                self.bits_descriptor(),
+            ]);
+            match computed.last() {
+                Some((hash, _)) => hasher.update(hash),
+                None => hasher.update(self.padded_data()),
+            }
+            for r in refs {
+                hasher.update(r.depths[ref_level].to_be_bytes());
+            }
+            for r in refs {
+                hasher.update(r.hashes[ref_level]);
+            }
+
+            let depth = refs
                 .iter()
-                .map(Deref::deref)
-                .flat_map(Cell::hash),
-        );
+                .map(|r| r.depths[ref_level] + 1)
+                .max()
+                .unwrap_or(0);
+            computed.push((hasher.finalize().into(), depth));
+        }
 
-        buf
+        let mut hashes = CellHashes {
+            mask,
+            hashes: Default::default(),
+            depths: Default::default(),
+        };
+        for l in 0..=MAX_LEVEL {
+            let i = (mask & ((1 << l) - 1)).count_ones() as usize;
+            let (hash, depth) = if !is_pruned {
+                computed[i]
+            } else if i != mask.count_ones() as usize {
+                // hashes and depths of the original cell are stored in data
+                let n = mask.count_ones() as usize;
+                let hash_offset = 16 + i * 256;
+                let depth_offset = 16 + n * 256 + i * 16;
+                (
+                    self.data[hash_offset..hash_offset + 256]
+                        .to_bitvec()
+                        .into_vec()
+                        .try_into()
+                        .unwrap(),
+                    self.data[depth_offset..depth_offset + 16].load_be(),
+                )
+            } else {
+                computed[0]
+            };
+            hashes.hashes[l as usize] = hash;
+            hashes.depths[l as usize] = depth;
+        }
+        hashes
+    }
+
+    /// Calculates [standard Cell representation hash](https://docs.ton.org/develop/data-formats/cell-boc#cell-hash)
+    /// at given `level`
+    #[inline]
+    pub fn hash_at(&self, level: u8) -> [u8; 32] {
+        self.hashes().hashes[level.min(MAX_LEVEL) as usize]
+    }
+
+    /// Returns depth of this cell at given `level`
+    #[inline]
+    pub fn depth_at(&self, level: u8) -> u16 {
+        self.hashes().depths[level.min(MAX_LEVEL) as usize]
     }
 
     /// Calculates [standard Cell representation hash](https://docs.ton.org/develop/data-formats/cell-boc#cell-hash)
     #[inline]
     pub fn hash(&self) -> [u8; 32] {
-        let mut hasher = Sha256::new();
-        hasher.update(self.repr());
-        hasher.finalize().into()
+        self.hash_at(MAX_LEVEL)
     }
 }
 
+/// Calculates [hashes](Cell::hash), [depths](Cell::depth_at) and
+/// [level masks](Cell::level_mask) of many cells in one bottom-up pass.
+///
+/// Each of these methods on [`Cell`] traverses the whole tree under it, so
+/// calling them for every cell in a tree is quadratic. [`CellHasher`]
+/// remembers the results for every cell it has visited instead, so shared
+/// subtrees are only processed once.
+#[derive(Default)]
+pub struct CellHasher<'a> {
+    computed: HashMap<*const Cell, CellHashes>,
+    _cells: PhantomData<&'a Cell>,
+}
+
+impl<'a> CellHasher<'a> {
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Same as [`Cell::level_mask()`]
+    #[inline]
+    pub fn level_mask(&mut self, cell: &'a Cell) -> u8 {
+        self.hashes(cell).mask
+    }
+
+    /// Same as [`Cell::hash_at()`]
+    #[inline]
+    pub fn hash_at(&mut self, cell: &'a Cell, level: u8) -> [u8; 32] {
+        self.hashes(cell).hashes[level.min(MAX_LEVEL) as usize]
+    }
+
+    /// Same as [`Cell::depth_at()`]
+    #[inline]
+    pub fn depth_at(&mut self, cell: &'a Cell, level: u8) -> u16 {
+        self.hashes(cell).depths[level.min(MAX_LEVEL) as usize]
+    }
+
+    /// Same as [`Cell::hash()`]
+    #[inline]
+    pub fn hash(&mut self, cell: &'a Cell) -> [u8; 32] {
+        self.hash_at(cell, MAX_LEVEL)
+    }
+
+    fn hashes(&mut self, cell: &'a Cell) -> CellHashes {
+        // cells are borrowed for 'a, so their addresses can not be reused
+        // by other cells while they are in the cache
+        if let Some(hashes) = self.computed.get(&(cell as *const Cell)) {
+            return *hashes;
+        }
+        let refs: Vec<CellHashes> = cell.references.iter().map(|r| self.hashes(r)).collect();
+        let hashes = cell.hashes_with_refs(&refs);
+        self.computed.insert(cell, hashes);
+        hashes
+    }
+}
+
+/// Hashes and depths of a [`Cell`] for each level
+#[derive(Clone, Copy)]
+struct CellHashes {
+    mask: u8,
+    hashes: [[u8; 32]; MAX_LEVEL as usize + 1],
+    depths: [u16; MAX_LEVEL as usize + 1],
+}
+
+#[inline]
+fn mask_level(mask: u8) -> u8 {
+    (u8::BITS - mask.leading_zeros()) as u8
+}
+
+#[inline]
+fn is_significant(mask: u8, level: u8) -> bool {
+    level == 0 || (mask >> (level - 1)) & 1 == 1
+}
+
 impl Debug for Cell {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         if f.alternate() {
@@ -215,10 +488,11 @@ impl Debug for Cell {
 
 #[cfg(test)]
 mod tests {
+    use bitvec::view::AsBits;
     use hex_literal::hex;
 
     use crate::{
-        bits::{r#as::NBits, ser::BitWriterExt},
+        bits::{de::BitReader, r#as::NBits, ser::BitWriterExt},
         r#as::{Data, Ref},
         ser::{r#as::CellSerializeWrapAsExt, CellSerializeExt},
         tests::assert_store_parse_as_eq,
@@ -228,7 +502,7 @@ mod tests {
 
     #[test]
     fn zero_depth() {
-        assert_eq!(().to_cell().unwrap().max_depth(), 0)
+        assert_eq!(().to_cell().unwrap().depth_at(0), 0)
     }
 
     #[test]
@@ -242,7 +516,7 @@ mod tests {
         )
             .to_cell()
             .unwrap();
-        assert_eq!(cell.max_depth(), 4)
+        assert_eq!(cell.depth_at(0), 4)
     }
 
     #[test]
@@ -257,6 +531,27 @@ mod tests {
         >((0b1, 0x0AAAAA, (0x7F, 0x0AAAAA)));
     }
 
+    #[test]
+    fn parse_rest_unaligned() {
+        let mut builder = Cell::builder();
+        builder
+            .pack_as::<_, NBits<3>>(0b101)
+            .unwrap()
+            .pack_as::<_, NBits<32>>(0x0000000F)
+            .unwrap();
+        let cell = builder.into_cell();
+
+        let mut parser = cell.parser();
+        parser.skip(3).unwrap();
+        let rest: Cell = parser.parse().unwrap();
+
+        let mut builder = Cell::builder();
+        builder.pack_as::<_, NBits<32>>(0x0000000F).unwrap();
+        let expected = builder.into_cell();
+        assert_eq!(rest, expected);
+        assert_eq!(rest.hash(), expected.hash());
+    }
+
     #[test]
     fn hash_no_refs() {
         let mut builder = Cell::builder();
@@ -286,4 +581,65 @@ mod tests {
             hex!("f345277cc6cfa747f001367e1e873dcfa8a936b8492431248b7a3eeafa8030e7")
         );
     }
+
+    #[test]
+    fn hash_shared_subtrees() {
+        // 2^100 paths from the root, but only 101 unique cells
+        let mut cell = Arc::new(Cell::new());
+        for _ in 0..100 {
+            cell = Arc::new(Cell {
+                references: [cell.clone(), cell].into(),
+                ..Default::default()
+            });
+        }
+        assert_eq!(cell.depth_at(0), 100);
+
+        let mut hasher = CellHasher::new();
+        assert_eq!(hasher.hash(&cell), Cell::hash(&cell));
+        assert_eq!(hasher.depth_at(&cell.references[0], 0), 99);
+        assert_eq!(hasher.level_mask(&cell), 0);
+    }
+
+    #[test]
+    fn pruned_branch() {
+        let cell = (0x0000000F_u32.wrap_as::<Data>(), ().wrap_as::<Ref>())
+            .to_cell()
+            .unwrap();
+        let pruned = cell.pruned_branch(1).unwrap();
+
+        assert_eq!(pruned.cell_type().unwrap(), CellType::PrunedBranch);
+        assert_eq!(pruned.level(), 1);
+        assert_eq!(pruned.hash_at(0), cell.hash());
+        assert_eq!(pruned.depth_at(0), cell.depth_at(0));
+        assert_ne!(pruned.hash(), cell.hash());
+        assert!(cell.pruned_branch(0).is_err());
+    }
+
+    #[test]
+    fn merkle_proof() {
+        let cell = (
+            0x0000000F_u32.wrap_as::<Data>(),
+            0x0000000B_u32.wrap_as::<Ref<Data>>(),
+            ().wrap_as::<Ref>(),
+        )
+            .to_cell()
+            .unwrap();
+        let partial = Cell {
+            data: cell.data.clone(),
+            references: [
+                cell.references[0].clone(),
+                cell.references[1].pruned_branch(1).unwrap().into(),
+            ]
+            .into(),
+            is_exotic: false,
+        };
+        assert_eq!(partial.level(), 1);
+        assert_eq!(partial.hash_at(0), cell.hash());
+        assert_ne!(partial.hash(), cell.hash());
+
+        let proof = Cell::merkle_proof(partial);
+        assert_eq!(proof.cell_type().unwrap(), CellType::MerkleProof);
+        assert_eq!(proof.level(), 0);
+        assert_eq!(proof.data[8..8 + 256], *cell.hash().as_bits::<Msb0>());
+    }
 }
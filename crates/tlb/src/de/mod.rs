@@ -141,9 +141,14 @@ where
 impl<'de> CellDeserialize<'de> for Cell {
     #[inline]
     fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        let mut data = mem::take(&mut parser.data).to_bitvec();
+        // remaining bits may start in the middle of a byte, while hashing
+        // and serialization read raw bytes of the data
+        data.force_align();
         Ok(Self {
-            data: mem::take(&mut parser.data).to_bitvec(),
+            data,
             references: mem::take(&mut parser.references).to_vec(),
+            is_exotic: mem::take(&mut parser.is_exotic),
         })
     }
 }
@@ -23,14 +23,29 @@ pub type CellParserError<'de> = <CellParser<'de> as BitReader>::Error;
 /// Cell parser created with [`Cell::parser()`].
 #[derive(Clone)]
 pub struct CellParser<'de> {
+    pub(super) is_exotic: bool,
     pub(super) data: &'de BitSlice<u8, Msb0>,
     pub(super) references: &'de [Arc<Cell>],
 }
 
 impl<'de> CellParser<'de> {
     #[inline]
-    pub(crate) const fn new(data: &'de BitSlice<u8, Msb0>, references: &'de [Arc<Cell>]) -> Self {
-        Self { data, references }
+    pub(crate) const fn new(
+        is_exotic: bool,
+        data: &'de BitSlice<u8, Msb0>,
+        references: &'de [Arc<Cell>],
+    ) -> Self {
+        Self {
+            is_exotic,
+            data,
+            references,
+        }
+    }
+
+    /// Returns whether the cell being parsed is [exotic](Cell::is_exotic)
+    #[inline]
+    pub const fn is_exotic(&self) -> bool {
+        self.is_exotic
     }
 
     /// Parse the value using its [`CellDeserialize`] implementation
@@ -227,6 +242,7 @@ impl<'de> CellDeserialize<'de> for CellParser<'de> {
     #[inline]
     fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
         Ok(Self {
+            is_exotic: mem::take(&mut parser.is_exotic),
             data: mem::take(&mut parser.data),
             references: mem::take(&mut parser.references),
         })
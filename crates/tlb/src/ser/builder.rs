@@ -27,6 +27,7 @@ pub type CellBuilderError = <CellBuilder as BitWriter>::Error;
 pub struct CellBuilder {
     data: CellBitWriter,
     references: Vec<Arc<Cell>>,
+    is_exotic: bool,
 }
 
 const MAX_BITS_LEN: usize = 1023;
@@ -39,9 +40,17 @@ impl CellBuilder {
         Self {
             data: LimitWriter::new(BitVec::EMPTY, MAX_BITS_LEN),
             references: Vec::new(),
+            is_exotic: false,
         }
     }
 
+    /// Mark the cell being built as [exotic](Cell::is_exotic)
+    #[inline]
+    pub fn set_exotic(&mut self, is_exotic: bool) -> &mut Self {
+        self.is_exotic = is_exotic;
+        self
+    }
+
     /// Store the value using its [`CellSerialize`] implementation
     #[inline]
     pub fn store<T>(&mut self, value: T) -> Result<&mut Self, CellBuilderError>
@@ -160,6 +169,12 @@ impl CellBuilder {
         Ok(self)
     }
 
+    /// Returns number of references that can still be stored
+    #[inline]
+    pub fn references_left(&self) -> usize {
+        MAX_REFS_COUNT - self.references.len()
+    }
+
     #[inline]
     fn ensure_reference(&self) -> Result<(), CellBuilderError> {
         if self.references.len() == MAX_REFS_COUNT {
@@ -206,6 +221,7 @@ impl CellBuilder {
         Cell {
             data: self.data.into_inner(),
             references: self.references,
+            is_exotic: self.is_exotic,
         }
     }
 }
@@ -13,7 +13,7 @@ use crate::{
     bits::ser::BitWriterExt,
     either::Either,
     r#as::{Ref, Same},
-    Cell, ResultExt,
+    Cell, Error, ResultExt,
 };
 
 /// A type that can be **ser**ilalized into [`CellBuilder`].
@@ -125,6 +125,10 @@ where
 impl CellSerialize for Cell {
     #[inline]
     fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        if self.is_exotic {
+            self.cell_type().map_err(Error::custom)?;
+            builder.set_exotic(true);
+        }
         builder
             .pack(self.data.as_bitslice())?
             .store_many_as::<_, Ref>(&self.references)?;